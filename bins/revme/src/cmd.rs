@@ -1,7 +1,9 @@
 pub mod bench;
 pub mod bytecode;
+pub mod debug;
 pub mod evmrunner;
 pub mod statetest;
+pub mod tracediff;
 
 use clap::Parser;
 
@@ -17,6 +19,10 @@ pub enum MainCmd {
     Bytecode(bytecode::Cmd),
     /// Run bench from specified list.
     Bench(bench::Cmd),
+    /// Debug EVM bytecode or a statetest transaction interactively.
+    Debug(debug::Cmd),
+    /// Find the first divergence between two EIP-3155 traces.
+    Tracediff(tracediff::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -25,6 +31,10 @@ pub enum Error {
     Statetest(#[from] statetest::Error),
     #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    Debug(#[from] debug::Error),
+    #[error(transparent)]
+    TraceDiff(#[from] tracediff::Error),
     #[error("Custom error: {0}")]
     Custom(&'static str),
 }
@@ -40,6 +50,8 @@ impl MainCmd {
             Self::Bench(cmd) => {
                 cmd.run();
             }
+            Self::Debug(cmd) => cmd.run()?,
+            Self::Tracediff(cmd) => cmd.run()?,
         }
         Ok(())
     }
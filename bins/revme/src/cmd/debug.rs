@@ -0,0 +1,188 @@
+mod debugger;
+
+pub use debugger::{Breakpoint, Debugger};
+
+use clap::Parser;
+use context::TxEnv;
+use database::{BenchmarkDB, State, BENCH_CALLER, BENCH_TARGET};
+use inspector::InspectEvm;
+use revm::{
+    bytecode::{Bytecode, BytecodeDecodeError},
+    context::{cfg::CfgEnv, Context},
+    primitives::{hardfork::SpecId, hex, TxKind, U256},
+    MainBuilder, MainContext,
+};
+use statetest_types::{SpecName, TestSuite};
+use std::{fs, io, path::PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid bytecode")]
+    InvalidBytecode,
+    #[error("Invalid input")]
+    InvalidInput,
+    #[error("Invalid breakpoint: {0}")]
+    InvalidBreakpoint(String),
+    #[error("Test not found: {0}")]
+    TestNotFound(String),
+    #[error("Invalid test: {0}")]
+    InvalidTest(String),
+    #[error("EVM Error: {0}")]
+    EVMError(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    BytecodeDecodeError(#[from] BytecodeDecodeError),
+}
+
+/// `debug` subcommand
+///
+/// Runs bytecode or a statetest transaction under an interactive debugger that reads commands
+/// from stdin. Type `help` at the prompt for the list of commands.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Hex-encoded EVM bytecode to be executed
+    #[arg(required_unless_present_any = ["path", "statetest"])]
+    bytecode: Option<String>,
+    /// Path to a file containing the hex-encoded EVM bytecode to be executed
+    #[arg(long)]
+    path: Option<PathBuf>,
+    /// Hex-encoded input/calldata bytes
+    #[arg(long, default_value = "")]
+    input: String,
+    /// Gas limit
+    #[arg(long, default_value = "1000000000")]
+    gas_limit: u64,
+
+    /// Path to a statetest file whose transaction is executed
+    #[arg(long, conflicts_with_all = ["bytecode", "path"])]
+    statetest: Option<PathBuf>,
+    /// Name of the test inside of the statetest file, defaults to the first test
+    #[arg(long, requires = "statetest")]
+    test: Option<String>,
+    /// Fork of the test, e.g. `Prague`, defaults to the latest fork of the test
+    #[arg(long, requires = "statetest")]
+    fork: Option<String>,
+    /// Index of the transaction in the post state of the fork
+    #[arg(long, default_value = "0", requires = "statetest")]
+    index: usize,
+
+    /// Breakpoints to start with: pc:<n>, op:<name>, depth:<n> or sstore:<slot>
+    ///
+    /// Without breakpoints the debugger stops at the first instruction.
+    #[arg(short, long = "break")]
+    breakpoints: Vec<String>,
+}
+
+impl Cmd {
+    /// Runs `debug` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let breakpoints = self
+            .breakpoints
+            .iter()
+            .map(|breakpoint| breakpoint.parse())
+            .collect::<Result<Vec<Breakpoint>, _>>()
+            .map_err(Error::InvalidBreakpoint)?;
+        let debugger =
+            Debugger::new(io::stdin().lock(), io::stdout()).with_breakpoints(breakpoints);
+
+        let result = if let Some(path) = &self.statetest {
+            self.run_statetest(path, debugger)?
+        } else {
+            self.run_bytecode(debugger)?
+        };
+        println!("Result: {result:#?}");
+        if let Some(reason) = result.revert_reason() {
+            println!("Revert reason: {reason}");
+        }
+        Ok(())
+    }
+
+    fn run_bytecode<R: io::BufRead, W: io::Write>(
+        &self,
+        debugger: Debugger<R, W>,
+    ) -> Result<revm::context::result::ExecutionResult, Error> {
+        let bytecode = match &self.path {
+            Some(path) => fs::read_to_string(path)?,
+            None => self.bytecode.clone().unwrap_or_default(),
+        };
+        let bytecode = hex::decode(bytecode.trim()).map_err(|_| Error::InvalidBytecode)?;
+        let input = hex::decode(self.input.trim()).map_err(|_| Error::InvalidInput)?;
+
+        let mut evm = Context::mainnet()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw_checked(
+                bytecode.into(),
+            )?))
+            .build_mainnet_with_inspector(debugger);
+        let tx = TxEnv::builder()
+            .caller(BENCH_CALLER)
+            .kind(TxKind::Call(BENCH_TARGET))
+            .data(input.into())
+            .gas_limit(self.gas_limit)
+            .build()
+            .unwrap();
+        evm.inspect_one_tx(tx)
+            .map_err(|e| Error::EVMError(e.to_string()))
+    }
+
+    fn run_statetest<R: io::BufRead, W: io::Write>(
+        &self,
+        path: &PathBuf,
+        debugger: Debugger<R, W>,
+    ) -> Result<revm::context::result::ExecutionResult, Error> {
+        let suite: TestSuite = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let (name, unit) = match &self.test {
+            Some(name) => suite.0.get_key_value(name),
+            None => suite.0.iter().next(),
+        }
+        .ok_or_else(|| Error::TestNotFound(self.test.clone().unwrap_or_default()))?;
+
+        let (spec_name, tests) = match &self.fork {
+            Some(fork) => {
+                let spec_name: SpecName =
+                    serde_json::from_value(serde_json::Value::String(fork.clone()))?;
+                unit.post.get_key_value(&spec_name)
+            }
+            None => unit.post.iter().next_back(),
+        }
+        .ok_or_else(|| Error::TestNotFound(format!("{name} fork {:?}", self.fork)))?;
+        let test = tests
+            .get(self.index)
+            .ok_or_else(|| Error::TestNotFound(format!("{name} index {}", self.index)))?;
+        println!("Debugging {name} ({spec_name:?}, index {})", self.index);
+
+        let mut cfg = CfgEnv::default();
+        cfg.chain_id = unit
+            .env
+            .current_chain_id
+            .unwrap_or(U256::ONE)
+            .try_into()
+            .unwrap_or(1);
+        cfg.spec = spec_name.to_spec_id();
+        if cfg.spec.is_enabled_in(SpecId::PRAGUE) && !cfg.spec.is_enabled_in(SpecId::OSAKA) {
+            cfg.set_max_blobs_per_tx(9);
+        } else {
+            cfg.set_max_blobs_per_tx(6);
+        }
+        let block = unit.block_env(&cfg);
+        let tx = test
+            .tx_env(unit)
+            .map_err(|e| Error::InvalidTest(e.to_string()))?;
+
+        let mut cache = unit.state();
+        cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
+        let mut evm = Context::mainnet()
+            .with_db(&mut state)
+            .with_block(block)
+            .with_cfg(cfg)
+            .build_mainnet_with_inspector(debugger);
+        evm.inspect_one_tx(tx)
+            .map_err(|e| Error::EVMError(e.to_string()))
+    }
+}
@@ -0,0 +1,497 @@
+use inspector::{Inspector, JournalExt};
+use revm::{
+    bytecode::opcode::{self, OpCode},
+    context_interface::ContextTr,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{InputsTr, Jumps, MemoryTr, ReturnData},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Host, Interpreter,
+    },
+    primitives::{hex, U256},
+};
+use std::{
+    fmt,
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+const HELP: &str = "\
+Commands:
+  s, step               execute the next instruction
+  n, next               step over calls and creates
+  f, finish             run until the current frame returns
+  c, continue           run until the next breakpoint
+  b, break <spec>       add a breakpoint: pc:<n>, op:<name>, depth:<n> or sstore:<slot>
+  d, delete [index]     delete a breakpoint or all breakpoints
+  bl, breakpoints       list breakpoints
+  w, where              print the current location
+  stack                 print the stack, top first
+  mem, memory           print the memory
+  rd, returndata        print the return data of the last call
+  storage [slot]        print the loaded persistent storage of the current contract
+  tstorage <slot>       print a transient storage slot of the current contract
+  q, quit               detach the debugger and run to the end
+  h, help               print this help";
+
+/// Condition on which the debugger stops.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Program counter.
+    Pc(usize),
+    /// Opcode.
+    Opcode(u8),
+    /// First instruction of every frame at the call depth.
+    Depth(usize),
+    /// `SSTORE` to the storage slot.
+    SlotWrite(U256),
+}
+
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid breakpoint `{s}`, expected pc:<n>, op:<name>, depth:<n> or sstore:<slot>"
+            )
+        };
+        let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "pc" => parse_usize(value).map(Self::Pc).ok_or_else(invalid),
+            "op" => OpCode::parse(&value.to_uppercase())
+                .map(|op| Self::Opcode(op.get()))
+                .ok_or_else(invalid),
+            "depth" => parse_usize(value).map(Self::Depth).ok_or_else(invalid),
+            "sstore" => U256::from_str(value)
+                .map(Self::SlotWrite)
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pc(pc) => write!(f, "pc:{pc}"),
+            Self::Opcode(op) => write!(f, "op:{}", OpCode::name_by_op(*op)),
+            Self::Depth(depth) => write!(f, "depth:{depth}"),
+            Self::SlotWrite(slot) => write!(f, "sstore:{slot:#x}"),
+        }
+    }
+}
+
+fn parse_usize(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/// When the debugger stops next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// At the next instruction.
+    Step,
+    /// At the next instruction with at most the given depth.
+    StepOver(usize),
+    /// At the next instruction with a lower depth.
+    Finish(usize),
+    /// At the next breakpoint.
+    Continue,
+    /// Never.
+    Detached,
+}
+
+/// Interactive debugger [Inspector].
+///
+/// Stops before executing an instruction and reads commands from `input` until execution is
+/// resumed. Output is written to `output`.
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+    /// Call depth of the current frame.
+    depth: usize,
+    /// Whether the next step is the first instruction of a frame.
+    frame_start: bool,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    /// Creates a debugger that stops at the first instruction.
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            breakpoints: Vec::new(),
+            mode: Mode::Step,
+            depth: 0,
+            frame_start: false,
+        }
+    }
+
+    /// Sets the breakpoints. If any are set, the debugger runs until the first one is hit.
+    pub fn with_breakpoints(mut self, breakpoints: Vec<Breakpoint>) -> Self {
+        if !breakpoints.is_empty() {
+            self.mode = Mode::Continue;
+        }
+        self.breakpoints = breakpoints;
+        self
+    }
+
+    /// Returns the breakpoints.
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Consumes the debugger and returns the output.
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    fn hit_breakpoint(&self, interp: &Interpreter<EthInterpreter>) -> Option<usize> {
+        let pc = interp.bytecode.pc();
+        let op = interp.bytecode.opcode();
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| match *breakpoint {
+                Breakpoint::Pc(bp) => bp == pc,
+                Breakpoint::Opcode(bp) => bp == op,
+                Breakpoint::Depth(bp) => self.frame_start && bp == self.depth,
+                Breakpoint::SlotWrite(slot) => {
+                    op == opcode::SSTORE && interp.stack.data().last() == Some(&slot)
+                }
+            })
+    }
+
+    fn write_location(&mut self, interp: &Interpreter<EthInterpreter>) {
+        let _ = writeln!(
+            self.output,
+            "[depth {}] pc={} {} gas={}",
+            self.depth,
+            interp.bytecode.pc(),
+            OpCode::name_by_op(interp.bytecode.opcode()),
+            interp.gas.remaining()
+        );
+    }
+
+    fn write_stack(&mut self, interp: &Interpreter<EthInterpreter>) {
+        let stack = interp.stack.data();
+        if stack.is_empty() {
+            let _ = writeln!(self.output, "stack is empty");
+        }
+        for (i, value) in stack.iter().rev().enumerate() {
+            let _ = writeln!(self.output, "{i:>4}: {value:#x}");
+        }
+    }
+
+    fn write_memory(&mut self, interp: &Interpreter<EthInterpreter>) {
+        let size = interp.memory.size();
+        if size == 0 {
+            let _ = writeln!(self.output, "memory is empty");
+        }
+        let memory = interp.memory.slice(0..size);
+        for (i, row) in memory.chunks(32).enumerate() {
+            let _ = writeln!(self.output, "{:#06x}: {}", i * 32, hex::encode(row));
+        }
+    }
+
+    fn write_storage<CTX>(
+        &mut self,
+        interp: &Interpreter<EthInterpreter>,
+        context: &CTX,
+        slot: Option<U256>,
+    ) where
+        CTX: ContextTr<Journal: JournalExt>,
+    {
+        let address = interp.input.target_address();
+        let Some(account) = context.journal_ref().evm_state().get(&address) else {
+            let _ = writeln!(self.output, "account {address} is not loaded");
+            return;
+        };
+        let mut slots: Vec<_> = account
+            .storage
+            .iter()
+            .filter(|(key, _)| slot.is_none_or(|slot| slot == **key))
+            .collect();
+        if slots.is_empty() {
+            let _ = writeln!(self.output, "no loaded storage");
+        }
+        slots.sort_by_key(|(key, _)| **key);
+        for (key, value) in slots {
+            let _ = writeln!(
+                self.output,
+                "{key:#x}: {:#x} (original {:#x})",
+                value.present_value, value.original_value
+            );
+        }
+    }
+
+    /// Reads and executes commands until execution is resumed.
+    fn prompt<CTX>(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX)
+    where
+        CTX: ContextTr<Journal: JournalExt> + Host,
+    {
+        self.write_location(interp);
+        let mut line = String::new();
+        loop {
+            let _ = write!(self.output, "(revme) ");
+            let _ = self.output.flush();
+            line.clear();
+            // Detach on end of input.
+            if !matches!(self.input.read_line(&mut line), Ok(n) if n > 0) {
+                let _ = writeln!(self.output);
+                self.mode = Mode::Detached;
+                return;
+            }
+            let mut args = line.split_whitespace();
+            let Some(command) = args.next() else {
+                continue;
+            };
+            let arg = args.next();
+            match command {
+                "s" | "step" => {
+                    self.mode = Mode::Step;
+                    return;
+                }
+                "n" | "next" => {
+                    self.mode = Mode::StepOver(self.depth);
+                    return;
+                }
+                "f" | "finish" => {
+                    self.mode = Mode::Finish(self.depth);
+                    return;
+                }
+                "c" | "continue" => {
+                    self.mode = Mode::Continue;
+                    return;
+                }
+                "q" | "quit" => {
+                    self.mode = Mode::Detached;
+                    return;
+                }
+                "b" | "break" => match arg.unwrap_or_default().parse::<Breakpoint>() {
+                    Ok(breakpoint) => {
+                        let _ = writeln!(
+                            self.output,
+                            "breakpoint {}: {breakpoint}",
+                            self.breakpoints.len()
+                        );
+                        self.breakpoints.push(breakpoint);
+                    }
+                    Err(err) => {
+                        let _ = writeln!(self.output, "{err}");
+                    }
+                },
+                "d" | "delete" => match arg.map(parse_usize) {
+                    None => self.breakpoints.clear(),
+                    Some(Some(index)) if index < self.breakpoints.len() => {
+                        self.breakpoints.remove(index);
+                    }
+                    Some(_) => {
+                        let _ = writeln!(self.output, "no such breakpoint");
+                    }
+                },
+                "bl" | "breakpoints" => {
+                    for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                        let _ = writeln!(self.output, "{i}: {breakpoint}");
+                    }
+                }
+                "w" | "where" => self.write_location(interp),
+                "stack" => self.write_stack(interp),
+                "mem" | "memory" => self.write_memory(interp),
+                "rd" | "returndata" => {
+                    let data = interp.return_data.buffer();
+                    let _ = writeln!(self.output, "{data}");
+                }
+                "storage" => match arg.map(U256::from_str) {
+                    Some(Err(_)) => {
+                        let _ = writeln!(self.output, "invalid slot");
+                    }
+                    slot => self.write_storage(interp, context, slot.and_then(Result::ok)),
+                },
+                "tstorage" => match arg.map(U256::from_str) {
+                    Some(Ok(slot)) => {
+                        let address = interp.input.target_address();
+                        let value = context.tload(address, slot);
+                        let _ = writeln!(self.output, "{slot:#x}: {value:#x}");
+                    }
+                    _ => {
+                        let _ = writeln!(self.output, "usage: tstorage <slot>");
+                    }
+                },
+                "h" | "help" => {
+                    let _ = writeln!(self.output, "{HELP}");
+                }
+                _ => {
+                    let _ = writeln!(self.output, "unknown command `{command}`, try `help`");
+                }
+            }
+        }
+    }
+}
+
+impl<R, W, CTX> Inspector<CTX, EthInterpreter> for Debugger<R, W>
+where
+    R: BufRead,
+    W: Write,
+    CTX: ContextTr<Journal: JournalExt> + Host,
+{
+    fn initialize_interp(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.frame_start = true;
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        let stop = match self.mode {
+            Mode::Detached => false,
+            Mode::Step => true,
+            Mode::StepOver(depth) => self.depth <= depth,
+            Mode::Finish(depth) => self.depth < depth,
+            Mode::Continue => false,
+        };
+        if stop {
+            self.prompt(interp, context);
+        } else if self.mode != Mode::Detached {
+            if let Some(index) = self.hit_breakpoint(interp) {
+                let breakpoint = self.breakpoints[index];
+                let _ = writeln!(self.output, "breakpoint {index} hit: {breakpoint}");
+                self.prompt(interp, context);
+            }
+        }
+        self.frame_start = false;
+    }
+
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.depth -= 1;
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.depth -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::{BenchmarkDB, InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use inspector::InspectEvm;
+    use revm::{
+        bytecode::Bytecode,
+        context::TxEnv,
+        primitives::{address, Address, TxKind},
+        state::AccountInfo,
+        Context, MainBuilder, MainContext,
+    };
+
+    const CALLEE: Address = address!("0x1000000000000000000000000000000000000001");
+
+    fn tx() -> TxEnv {
+        TxEnv::builder()
+            .caller(BENCH_CALLER)
+            .kind(TxKind::Call(BENCH_TARGET))
+            .gas_limit(100_000)
+            .build()
+            .unwrap()
+    }
+
+    fn debug(code: Vec<u8>, breakpoints: Vec<Breakpoint>, commands: &str) -> String {
+        let debugger = Debugger::new(commands.as_bytes(), Vec::new()).with_breakpoints(breakpoints);
+        let mut evm = Context::mainnet()
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new_legacy(code.into())))
+            .build_mainnet_with_inspector(debugger);
+        evm.inspect_one_tx(tx()).unwrap();
+        String::from_utf8(evm.inspector.into_output()).unwrap()
+    }
+
+    #[test]
+    fn parse_breakpoint() {
+        assert_eq!("pc:0x10".parse(), Ok(Breakpoint::Pc(16)));
+        assert_eq!("op:sstore".parse(), Ok(Breakpoint::Opcode(opcode::SSTORE)));
+        assert_eq!("depth:2".parse(), Ok(Breakpoint::Depth(2)));
+        assert_eq!("sstore:1".parse(), Ok(Breakpoint::SlotWrite(U256::from(1))));
+        assert!("op:NOPE".parse::<Breakpoint>().is_err());
+        assert!("pc".parse::<Breakpoint>().is_err());
+    }
+
+    #[test]
+    fn step_and_inspect() {
+        // MSTORE 0x2a at 0, SSTORE 1 at slot 0.
+        let code = vec![
+            opcode::PUSH1,
+            0x2a,
+            opcode::PUSH1,
+            0x00,
+            opcode::MSTORE,
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+        let output = debug(
+            code,
+            vec![],
+            "s\ns\nstack\nb sstore:0\nc\nmem\ns\nstorage\nc\n",
+        );
+        assert!(output.contains("[depth 1] pc=0 PUSH1"));
+        assert!(output.contains("[depth 1] pc=4 MSTORE"));
+        assert!(output.contains("   0: 0x0\n   1: 0x2a\n"));
+        assert!(output.contains("breakpoint 0 hit: sstore:0x0\n[depth 1] pc=9 SSTORE"));
+        assert!(output.contains(&format!("0x0000: {}2a", "00".repeat(31))));
+        assert!(output.contains("0x0: 0x1 (original 0x0)"));
+    }
+
+    #[test]
+    fn step_over_and_finish() {
+        // Calls `CALLEE` that pushes zero and stops.
+        let mut code = [opcode::PUSH1, 0x00].repeat(5);
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend([opcode::GAS, opcode::CALL, opcode::POP, opcode::STOP]);
+
+        let mut db = InMemoryDB::default();
+        let bytecode = Bytecode::new_legacy(vec![opcode::PUSH1, 0x00, opcode::STOP].into());
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::default().with_code(Bytecode::new_legacy(code.into())),
+        );
+        db.insert_account_info(CALLEE, AccountInfo::default().with_code(bytecode));
+
+        let run = |breakpoints: Vec<Breakpoint>, commands: &str| {
+            let debugger =
+                Debugger::new(commands.as_bytes(), Vec::new()).with_breakpoints(breakpoints);
+            let mut evm = Context::mainnet()
+                .with_db(db.clone())
+                .build_mainnet_with_inspector(debugger);
+            evm.inspect_one_tx(tx()).unwrap();
+            String::from_utf8(evm.inspector.into_output()).unwrap()
+        };
+
+        let output = run(vec![Breakpoint::Opcode(opcode::CALL)], "n\nq\n");
+        assert!(output.contains("[depth 1] pc=32 CALL"));
+        assert!(output.contains("[depth 1] pc=33 POP"));
+        assert!(!output.contains("[depth 2]"));
+
+        let output = run(vec![Breakpoint::Depth(2)], "s\nf\nrd\nq\n");
+        assert!(output.contains("breakpoint 0 hit: depth:2\n[depth 2] pc=0 PUSH1"));
+        assert!(output.contains("[depth 2] pc=2 STOP"));
+        assert!(output.contains("[depth 1] pc=33 POP"));
+    }
+}
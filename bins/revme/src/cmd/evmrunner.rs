@@ -130,6 +130,9 @@ impl Cmd {
         let time = time.elapsed();
 
         println!("Result: {:#?}", r.result);
+        if let Some(reason) = r.result.revert_reason() {
+            println!("Revert reason: {reason}");
+        }
         if self.state {
             println!("State: {:#?}", r.state);
         }
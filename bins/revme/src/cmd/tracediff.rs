@@ -0,0 +1,366 @@
+use clap::Parser;
+use revm::{bytecode::opcode::OpCode, primitives::U256};
+use serde_json::{Map, Value};
+use std::{
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader, Error as IoError},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{path}: {error}")]
+    Io { path: PathBuf, error: IoError },
+    #[error("line {line}: invalid JSON: {error}")]
+    InvalidJson {
+        line: usize,
+        error: serde_json::Error,
+    },
+    #[error("line {line}: missing or invalid field `{field}`")]
+    InvalidField { line: usize, field: &'static str },
+    #[error("traces diverge at step {0}")]
+    Diverged(usize),
+}
+
+/// `tracediff` subcommand
+///
+/// Compares two EIP-3155 traces in the JSON lines format and reports the first step where
+/// the program counter, opcode, gas, stack, memory size or depth differ.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Path to the first trace
+    left: PathBuf,
+    /// Path to the second trace
+    right: PathBuf,
+    /// Number of steps printed before and after the divergence
+    #[arg(short, long, default_value = "3")]
+    context: usize,
+}
+
+impl Cmd {
+    /// Runs `tracediff` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let left = Trace::from_path(&self.left)?;
+        let right = Trace::from_path(&self.right)?;
+
+        let Some(divergence) = first_divergence(&left.steps, &right.steps) else {
+            println!("Traces match ({} steps)", left.steps.len());
+            if let (Some(left), Some(right)) = (&left.summary, &right.summary) {
+                if left != right {
+                    println!("Summaries differ:");
+                    println!("  left:  {left}");
+                    println!("  right: {right}");
+                }
+            }
+            return Ok(());
+        };
+
+        let index = divergence.index;
+        println!(
+            "Traces diverge at step {index}: {}",
+            divergence.reason(left.steps.len())
+        );
+        println!();
+        for (i, step) in left.steps[..index]
+            .iter()
+            .enumerate()
+            .skip(index.saturating_sub(self.context))
+        {
+            println!("  {i:>6}        {step}");
+        }
+        for (name, trace) in [("left ", &left), ("right", &right)] {
+            for (i, step) in trace
+                .steps
+                .iter()
+                .enumerate()
+                .skip(index)
+                .take(self.context + 1)
+            {
+                let marker = if i == index { ">" } else { " " };
+                println!("{marker} {i:>6} {name} {step}");
+            }
+        }
+        Err(Error::Diverged(index))
+    }
+}
+
+/// Parsed EIP-3155 trace.
+#[derive(Debug, Default, PartialEq)]
+pub struct Trace {
+    /// Executed steps.
+    pub steps: Vec<Step>,
+    /// Summary line, if present.
+    pub summary: Option<Value>,
+}
+
+impl Trace {
+    /// Reads the trace from a file.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let file = File::open(path).map_err(|error| Error::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        Self::from_reader(BufReader::new(file)).map_err(|error| match error {
+            Error::Io { error, .. } => Error::Io {
+                path: path.to_path_buf(),
+                error,
+            },
+            error => error,
+        })
+    }
+
+    /// Reads the trace from JSON lines.
+    ///
+    /// Empty lines are skipped and the summary line, that has no `pc` field, is stored
+    /// separately.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, Error> {
+        let mut trace = Self::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.map_err(|error| Error::Io {
+                path: PathBuf::new(),
+                error,
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: Value = serde_json::from_str(&line).map_err(|error| Error::InvalidJson {
+                line: line_number,
+                error,
+            })?;
+            match value {
+                Value::Object(object) if object.contains_key("pc") => {
+                    trace.steps.push(Step::from_json(&object, line_number)?)
+                }
+                value => trace.summary = Some(value),
+            }
+        }
+        Ok(trace)
+    }
+}
+
+/// Fields of a trace step that are compared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// Line of the step in the trace file.
+    pub line: usize,
+    /// Program counter.
+    pub pc: u64,
+    /// Opcode.
+    pub op: u8,
+    /// Gas left before executing the operation.
+    pub gas: u64,
+    /// Stack before executing the operation.
+    pub stack: Vec<U256>,
+    /// Size of the memory.
+    pub mem_size: u64,
+    /// Depth of the call stack.
+    pub depth: u64,
+}
+
+impl Step {
+    fn from_json(object: &Map<String, Value>, line: usize) -> Result<Self, Error> {
+        let number = |field: &'static str| {
+            object
+                .get(field)
+                .and_then(parse_u64)
+                .ok_or(Error::InvalidField { line, field })
+        };
+        let stack = object
+            .get("stack")
+            .and_then(Value::as_array)
+            .and_then(|stack| stack.iter().map(parse_u256).collect::<Option<Vec<_>>>())
+            .ok_or(Error::InvalidField {
+                line,
+                field: "stack",
+            })?;
+        Ok(Self {
+            line,
+            pc: number("pc")?,
+            op: u8::try_from(number("op")?)
+                .map_err(|_| Error::InvalidField { line, field: "op" })?,
+            gas: number("gas")?,
+            stack,
+            // Some clients omit the memory size when memory is empty.
+            mem_size: object.get("memSize").map_or(Some(0), parse_u64).ok_or(
+                Error::InvalidField {
+                    line,
+                    field: "memSize",
+                },
+            )?,
+            depth: number("depth")?,
+        })
+    }
+
+    /// Returns the names of the fields that differ.
+    pub fn diff(&self, other: &Self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.pc != other.pc {
+            fields.push("pc");
+        }
+        if self.op != other.op {
+            fields.push("op");
+        }
+        if self.gas != other.gas {
+            fields.push("gas");
+        }
+        if self.stack != other.stack {
+            fields.push("stack");
+        }
+        if self.mem_size != other.mem_size {
+            fields.push("memSize");
+        }
+        if self.depth != other.depth {
+            fields.push("depth");
+        }
+        fields
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(line {}) depth={} pc={} op={} gas={:#x} memSize={} stack=[",
+            self.line,
+            self.depth,
+            self.pc,
+            OpCode::name_by_op(self.op),
+            self.gas,
+            self.mem_size
+        )?;
+        for (i, value) in self.stack.iter().enumerate() {
+            if i != 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{value:#x}")?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// First step where two traces differ.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Index of the step.
+    pub index: usize,
+    /// Fields that differ, empty if one of the traces ended.
+    pub fields: Vec<&'static str>,
+}
+
+impl Divergence {
+    fn reason(&self, left_len: usize) -> String {
+        if !self.fields.is_empty() {
+            return format!("{} differ", self.fields.join(", "));
+        }
+        let ended = if left_len == self.index {
+            "left"
+        } else {
+            "right"
+        };
+        format!("{ended} trace ended")
+    }
+}
+
+/// Returns the first step where the traces differ.
+pub fn first_divergence(left: &[Step], right: &[Step]) -> Option<Divergence> {
+    for (index, (l, r)) in left.iter().zip(right).enumerate() {
+        let fields = l.diff(r);
+        if !fields.is_empty() {
+            return Some(Divergence { index, fields });
+        }
+    }
+    (left.len() != right.len()).then(|| Divergence {
+        index: left.len().min(right.len()),
+        fields: Vec::new(),
+    })
+}
+
+/// Parses a JSON number, a hex string or a decimal string.
+fn parse_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+fn parse_u256(value: &Value) -> Option<U256> {
+    match value {
+        Value::Number(number) => number.as_u64().map(U256::from),
+        Value::String(s) => U256::from_str(s).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACE: &str = r#"{"pc":0,"op":96,"gas":"0x5f5e100","gasCost":"0x3","stack":[],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0x0","opName":"PUSH1"}
+{"pc":2,"op":96,"gas":"0x5f5e0fd","gasCost":"0x3","stack":["0x1"],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0x0","opName":"PUSH1","memory":"0x"}
+{"pc":4,"op":1,"gas":"0x5f5e0fa","gasCost":"0x3","stack":["0x1","0x2"],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0x0","opName":"ADD"}
+{"stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","output":"0x","gasUsed":"0x5208","pass":true}
+"#;
+
+    #[test]
+    fn parse_trace() {
+        let trace = Trace::from_reader(TRACE.as_bytes()).unwrap();
+        assert_eq!(trace.steps.len(), 3);
+        assert!(trace.summary.is_some());
+        assert_eq!(trace.steps[2].op, 1);
+        assert_eq!(trace.steps[2].stack, [U256::from(1), U256::from(2)]);
+        assert_eq!(trace.steps[1].gas, 0x5f5e0fd);
+    }
+
+    #[test]
+    fn parse_geth_style_trace() {
+        // Decimal memory size, no summary.
+        let line = r#"{"pc":0,"op":96,"gas":"0x5f5e100","stack":[],"depth":1,"memSize":0}"#;
+        let trace = Trace::from_reader(line.as_bytes()).unwrap();
+        assert_eq!(trace.steps.len(), 1);
+        assert_eq!(trace.summary, None);
+    }
+
+    #[test]
+    fn find_divergence() {
+        let left = Trace::from_reader(TRACE.as_bytes()).unwrap().steps;
+        assert_eq!(first_divergence(&left, &left), None);
+
+        let mut right = left.clone();
+        right[2].gas += 1;
+        right[2].stack[1] = U256::from(3);
+        assert_eq!(
+            first_divergence(&left, &right),
+            Some(Divergence {
+                index: 2,
+                fields: vec!["gas", "stack"]
+            })
+        );
+
+        assert_eq!(
+            first_divergence(&left, &left[..2]),
+            Some(Divergence {
+                index: 2,
+                fields: Vec::new()
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_step() {
+        let line = r#"{"pc":0,"gas":"0x1","stack":[],"depth":1}"#;
+        assert!(matches!(
+            Trace::from_reader(line.as_bytes()),
+            Err(Error::InvalidField {
+                line: 1,
+                field: "op"
+            })
+        ));
+    }
+}
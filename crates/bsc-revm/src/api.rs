@@ -0,0 +1,9 @@
+//! BNB Smart Chain API types.
+
+pub mod builder;
+pub mod default_ctx;
+pub mod exec;
+
+pub use builder::BscBuilder;
+pub use default_ctx::DefaultBsc;
+pub use exec::{BscContextTr, BscError};
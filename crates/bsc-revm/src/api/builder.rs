@@ -0,0 +1,45 @@
+//! BNB Smart Chain builder trait [`BscBuilder`] used to build [`BscEvm`].
+use crate::{evm::BscEvm, precompiles::BscPrecompiles, BscSpecId};
+use revm::{
+    context::Cfg,
+    context_interface::{Block, JournalTr, Transaction},
+    handler::instructions::EthInstructions,
+    interpreter::interpreter::EthInterpreter,
+    state::EvmState,
+    Context, Database,
+};
+
+/// Type alias for default BscEvm
+pub type DefaultBscEvm<CTX, INSP = ()> =
+    BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, BscPrecompiles>;
+
+/// Trait that allows for BNB Smart Chain BscEvm to be built.
+pub trait BscBuilder: Sized {
+    /// Type of the context.
+    type Context;
+
+    /// Build the bsc.
+    fn build_bsc(self) -> DefaultBscEvm<Self::Context>;
+
+    /// Build the bsc with an inspector.
+    fn build_bsc_with_inspector<INSP>(self, inspector: INSP) -> DefaultBscEvm<Self::Context, INSP>;
+}
+
+impl<BLOCK, TX, CFG, DB, JOURNAL> BscBuilder for Context<BLOCK, TX, CFG, DB, JOURNAL>
+where
+    BLOCK: Block,
+    TX: Transaction,
+    CFG: Cfg<Spec = BscSpecId>,
+    DB: Database,
+    JOURNAL: JournalTr<Database = DB, State = EvmState>,
+{
+    type Context = Self;
+
+    fn build_bsc(self) -> DefaultBscEvm<Self::Context> {
+        BscEvm::new(self, ())
+    }
+
+    fn build_bsc_with_inspector<INSP>(self, inspector: INSP) -> DefaultBscEvm<Self::Context, INSP> {
+        BscEvm::new(self, inspector)
+    }
+}
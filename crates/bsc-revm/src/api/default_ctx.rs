@@ -0,0 +1,48 @@
+//! Contains trait [`DefaultBsc`] used to create a default context.
+use crate::BscSpecId;
+use revm::{
+    context::{BlockEnv, CfgEnv, TxEnv},
+    database_interface::EmptyDB,
+    Context, Journal, MainContext,
+};
+
+/// Chain id of BNB Smart Chain mainnet.
+pub const BSC_MAINNET_CHAIN_ID: u64 = 56;
+
+/// Type alias for the default context type of the BscEvm.
+pub type BscContext<DB> = Context<BlockEnv, TxEnv, CfgEnv<BscSpecId>, DB, Journal<DB>, ()>;
+
+/// Trait that allows for a default context to be created.
+pub trait DefaultBsc {
+    /// Create a default context.
+    fn bsc() -> BscContext<EmptyDB>;
+}
+
+impl DefaultBsc for BscContext<EmptyDB> {
+    fn bsc() -> Self {
+        Context::mainnet().with_cfg(
+            CfgEnv::new_with_spec(BscSpecId::default()).with_chain_id(BSC_MAINNET_CHAIN_ID),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::builder::BscBuilder;
+    use revm::{
+        inspector::{InspectEvm, NoOpInspector},
+        ExecuteEvm,
+    };
+
+    #[test]
+    fn default_run_bsc() {
+        let ctx = Context::bsc();
+        // convert to BSC context
+        let mut evm = ctx.build_bsc_with_inspector(NoOpInspector {});
+        // execute
+        let _ = evm.transact(TxEnv::default());
+        // inspect
+        let _ = evm.inspect_one_tx(TxEnv::default());
+    }
+}
@@ -0,0 +1,156 @@
+//! Implementation of the [`ExecuteEvm`] trait for the [`BscEvm`].
+use crate::{evm::BscEvm, handler::BscHandler, BscSpecId};
+use revm::{
+    context::{result::ExecResultAndState, ContextSetters},
+    context_interface::{
+        result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+        Cfg, ContextTr, Database, JournalTr,
+    },
+    handler::{
+        instructions::EthInstructions, system_call::SystemCallEvm, EthFrame, Handler,
+        PrecompileProvider, SystemCallTx,
+    },
+    inspector::{
+        InspectCommitEvm, InspectEvm, InspectSystemCallEvm, Inspector, InspectorHandler, JournalExt,
+    },
+    interpreter::{interpreter::EthInterpreter, InterpreterResult},
+    primitives::{Address, Bytes},
+    state::EvmState,
+    DatabaseCommit, ExecuteCommitEvm, ExecuteEvm,
+};
+
+/// Type alias for BNB Smart Chain context
+pub trait BscContextTr:
+    ContextTr<Journal: JournalTr<State = EvmState>, Cfg: Cfg<Spec = BscSpecId>>
+{
+}
+
+impl<T> BscContextTr for T where
+    T: ContextTr<Journal: JournalTr<State = EvmState>, Cfg: Cfg<Spec = BscSpecId>>
+{
+}
+
+/// Type alias for the error type of the BscEvm.
+pub type BscError<CTX> = EVMError<<<CTX as ContextTr>::Db as Database>::Error, InvalidTransaction>;
+
+impl<CTX, INSP, PRECOMPILE> ExecuteEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    type Tx = <CTX as ContextTr>::Tx;
+    type Block = <CTX as ContextTr>::Block;
+    type State = EvmState;
+    type Error = BscError<CTX>;
+    type ExecutionResult = ExecutionResult<HaltReason>;
+
+    fn set_block(&mut self, block: Self::Block) {
+        self.0.ctx.set_block(block);
+    }
+
+    fn transact_one(&mut self, tx: Self::Tx) -> Result<Self::ExecutionResult, Self::Error> {
+        self.0.ctx.set_tx(tx);
+        let mut h = BscHandler::<_, _, EthFrame<EthInterpreter>>::new();
+        h.run(self)
+    }
+
+    fn finalize(&mut self) -> Self::State {
+        self.0.ctx.journal_mut().finalize()
+    }
+
+    fn replay(
+        &mut self,
+    ) -> Result<ExecResultAndState<Self::ExecutionResult, Self::State>, Self::Error> {
+        let mut h = BscHandler::<_, _, EthFrame<EthInterpreter>>::new();
+        h.run(self).map(|result| {
+            let state = self.finalize();
+            ExecResultAndState::new(result, state)
+        })
+    }
+}
+
+impl<CTX, INSP, PRECOMPILE> ExecuteCommitEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr<Db: DatabaseCommit> + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    fn commit(&mut self, state: Self::State) {
+        self.0.ctx.db_mut().commit(state);
+    }
+}
+
+impl<CTX, INSP, PRECOMPILE> InspectEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr<Journal: JournalExt> + ContextSetters,
+    INSP: Inspector<CTX, EthInterpreter>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    type Inspector = INSP;
+
+    fn set_inspector(&mut self, inspector: Self::Inspector) {
+        self.0.inspector = inspector;
+    }
+
+    fn inspect_one_tx(&mut self, tx: Self::Tx) -> Result<Self::ExecutionResult, Self::Error> {
+        self.0.ctx.set_tx(tx);
+        let mut h = BscHandler::<_, _, EthFrame<EthInterpreter>>::new();
+        h.inspect_run(self)
+    }
+}
+
+impl<CTX, INSP, PRECOMPILE> InspectCommitEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr<Journal: JournalExt, Db: DatabaseCommit> + ContextSetters,
+    INSP: Inspector<CTX, EthInterpreter>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+}
+
+impl<CTX, INSP, PRECOMPILE> SystemCallEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr<Tx: SystemCallTx> + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    fn system_call_one_with_caller(
+        &mut self,
+        caller: Address,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Result<Self::ExecutionResult, Self::Error> {
+        self.0.ctx.set_tx(CTX::Tx::new_system_tx_with_caller(
+            caller,
+            system_contract_address,
+            data,
+        ));
+        let mut h = BscHandler::<_, _, EthFrame<EthInterpreter>>::new();
+        h.run_system_call(self)
+    }
+}
+
+impl<CTX, INSP, PRECOMPILE> InspectSystemCallEvm
+    for BscEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: BscContextTr<Journal: JournalExt, Tx: SystemCallTx> + ContextSetters,
+    INSP: Inspector<CTX, EthInterpreter>,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    fn inspect_one_system_call_with_caller(
+        &mut self,
+        caller: Address,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Result<Self::ExecutionResult, Self::Error> {
+        self.0.ctx.set_tx(CTX::Tx::new_system_tx_with_caller(
+            caller,
+            system_contract_address,
+            data,
+        ));
+        let mut h = BscHandler::<_, _, EthFrame<EthInterpreter>>::new();
+        h.inspect_run_system_call(self)
+    }
+}
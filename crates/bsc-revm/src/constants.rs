@@ -0,0 +1,72 @@
+//! BNB Smart Chain constants used in the BSC EVM.
+use revm::primitives::{address, Address};
+
+/// Address that collects the transaction fees of a block.
+///
+/// The validator moves the collected fees to the validator set and system reward contracts with
+/// the system transactions at the end of the block.
+pub const SYSTEM_ADDRESS: Address = address!("0xfffffffffffffffffffffffffffffffffffffffe");
+
+/// Validator set contract.
+pub const VALIDATOR_CONTRACT: Address = address!("0x0000000000000000000000000000000000001000");
+/// Slash contract.
+pub const SLASH_CONTRACT: Address = address!("0x0000000000000000000000000000000000001001");
+/// System reward contract.
+pub const SYSTEM_REWARD_CONTRACT: Address = address!("0x0000000000000000000000000000000000001002");
+/// Light client contract.
+pub const LIGHT_CLIENT_CONTRACT: Address = address!("0x0000000000000000000000000000000000001003");
+/// Token hub contract.
+pub const TOKEN_HUB_CONTRACT: Address = address!("0x0000000000000000000000000000000000001004");
+/// Relayer incentivize contract.
+pub const RELAYER_INCENTIVIZE_CONTRACT: Address =
+    address!("0x0000000000000000000000000000000000001005");
+/// Relayer hub contract.
+pub const RELAYER_HUB_CONTRACT: Address = address!("0x0000000000000000000000000000000000001006");
+/// Governance hub contract.
+pub const GOV_HUB_CONTRACT: Address = address!("0x0000000000000000000000000000000000001007");
+/// Token manager contract.
+pub const TOKEN_MANAGER_CONTRACT: Address = address!("0x0000000000000000000000000000000000001008");
+/// Cross chain contract.
+pub const CROSS_CHAIN_CONTRACT: Address = address!("0x0000000000000000000000000000000000002000");
+/// Staking contract.
+pub const STAKING_CONTRACT: Address = address!("0x0000000000000000000000000000000000002001");
+/// Stake hub contract.
+pub const STAKE_HUB_CONTRACT: Address = address!("0x0000000000000000000000000000000000002002");
+/// Stake credit contract.
+pub const STAKE_CREDIT_CONTRACT: Address = address!("0x0000000000000000000000000000000000002003");
+/// Governor contract.
+pub const GOVERNOR_CONTRACT: Address = address!("0x0000000000000000000000000000000000002004");
+/// Governance token contract.
+pub const GOV_TOKEN_CONTRACT: Address = address!("0x0000000000000000000000000000000000002005");
+/// Timelock contract.
+pub const TIMELOCK_CONTRACT: Address = address!("0x0000000000000000000000000000000000002006");
+/// Token recover portal contract.
+pub const TOKEN_RECOVER_PORTAL_CONTRACT: Address =
+    address!("0x0000000000000000000000000000000000003000");
+
+/// System contracts that can be called by system transactions.
+pub const SYSTEM_CONTRACTS: [Address; 17] = [
+    VALIDATOR_CONTRACT,
+    SLASH_CONTRACT,
+    SYSTEM_REWARD_CONTRACT,
+    LIGHT_CLIENT_CONTRACT,
+    TOKEN_HUB_CONTRACT,
+    RELAYER_INCENTIVIZE_CONTRACT,
+    RELAYER_HUB_CONTRACT,
+    GOV_HUB_CONTRACT,
+    TOKEN_MANAGER_CONTRACT,
+    CROSS_CHAIN_CONTRACT,
+    STAKING_CONTRACT,
+    STAKE_HUB_CONTRACT,
+    STAKE_CREDIT_CONTRACT,
+    GOVERNOR_CONTRACT,
+    GOV_TOKEN_CONTRACT,
+    TIMELOCK_CONTRACT,
+    TOKEN_RECOVER_PORTAL_CONTRACT,
+];
+
+/// Returns `true` if the address is a system contract.
+#[inline]
+pub fn is_system_contract(address: &Address) -> bool {
+    SYSTEM_CONTRACTS.contains(address)
+}
@@ -0,0 +1,299 @@
+//!Handler related to BNB Smart Chain
+use crate::{
+    api::exec::BscContextTr,
+    constants::{is_system_contract, SYSTEM_ADDRESS},
+    BscSpecId,
+};
+use revm::{
+    context::result::InvalidTransaction,
+    context_interface::{result::HaltReason, Block, Cfg, ContextTr, JournalTr, Transaction},
+    handler::{
+        evm::FrameTr, handler::EvmTrError, pre_execution::validate_account_nonce_and_code,
+        EthFrame, EvmTr, FrameResult, Handler, MainnetHandler,
+    },
+    inspector::{Inspector, InspectorEvmTr, InspectorHandler},
+    interpreter::{interpreter::EthInterpreter, interpreter_action::FrameInit},
+    primitives::{hardfork::SpecId, TxKind, U256},
+};
+use std::boxed::Box;
+
+/// BNB Smart Chain handler extends the [`Handler`] with BNB Smart Chain specific logic.
+///
+/// System transactions, see [`is_system_transaction`], are not charged for gas and do not pay
+/// fees. Fees of other transactions are collected in [`SYSTEM_ADDRESS`] instead of the block
+/// beneficiary.
+#[derive(Debug, Clone)]
+pub struct BscHandler<EVM, ERROR, FRAME> {
+    /// Mainnet handler allows us to use functions from the mainnet handler inside BSC handler.
+    /// So we dont duplicate the logic
+    pub mainnet: MainnetHandler<EVM, ERROR, FRAME>,
+    /// Phantom data to avoid type inference issues.
+    pub _phantom: core::marker::PhantomData<(EVM, ERROR, FRAME)>,
+}
+
+impl<EVM, ERROR, FRAME> BscHandler<EVM, ERROR, FRAME> {
+    /// Create a new BNB Smart Chain handler.
+    pub fn new() -> Self {
+        Self {
+            mainnet: MainnetHandler::default(),
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<EVM, ERROR, FRAME> Default for BscHandler<EVM, ERROR, FRAME> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns `true` if the transaction of the context is a system transaction.
+///
+/// System transactions are sent by the validator of the block, the block beneficiary, to one of
+/// the system contracts with zero gas price.
+pub fn is_system_transaction<CTX: ContextTr>(ctx: &CTX) -> bool {
+    let tx = ctx.tx();
+    tx.gas_price() == 0
+        && tx.caller() == ctx.block().beneficiary()
+        && matches!(tx.kind(), TxKind::Call(to) if is_system_contract(&to))
+}
+
+impl<EVM, ERROR, FRAME> Handler for BscHandler<EVM, ERROR, FRAME>
+where
+    EVM: EvmTr<Context: BscContextTr, Frame = FRAME>,
+    ERROR: EvmTrError<EVM>,
+    FRAME: FrameTr<FrameResult = FrameResult, FrameInit = FrameInit>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type HaltReason = HaltReason;
+
+    fn validate_env(&self, evm: &mut Self::Evm) -> Result<(), Self::Error> {
+        // System transactions are created by the validator with a gas limit that is above the
+        // block gas limit, so the environment checks are skipped.
+        if is_system_transaction(evm.ctx_ref()) {
+            return Ok(());
+        }
+        self.mainnet.validate_env(evm)
+    }
+
+    fn validate_against_state_and_deduct_caller(
+        &self,
+        evm: &mut Self::Evm,
+    ) -> Result<(), Self::Error> {
+        if !is_system_transaction(evm.ctx_ref()) {
+            return self.mainnet.validate_against_state_and_deduct_caller(evm);
+        }
+
+        let ctx = evm.ctx();
+        let is_balance_check_disabled = ctx.cfg().is_balance_check_disabled();
+        let is_eip3607_disabled = ctx.cfg().is_eip3607_disabled();
+        let is_nonce_check_disabled = ctx.cfg().is_nonce_check_disabled();
+
+        let (tx, journal) = ctx.tx_journal_mut();
+        let caller_account = journal.load_account_code(tx.caller())?.data;
+
+        validate_account_nonce_and_code(
+            &mut caller_account.info,
+            tx.nonce(),
+            is_eip3607_disabled,
+            is_nonce_check_disabled,
+        )?;
+
+        // Gas is free, only the value needs to be covered.
+        if tx.value() > caller_account.info.balance && !is_balance_check_disabled {
+            return Err(InvalidTransaction::LackOfFundForMaxFee {
+                fee: Box::new(tx.value()),
+                balance: Box::new(caller_account.info.balance),
+            }
+            .into());
+        }
+
+        let old_balance = caller_account.info.balance;
+        caller_account.mark_touch();
+        // System transactions are always calls, bump the nonce.
+        caller_account.info.nonce = caller_account.info.nonce.saturating_add(1);
+
+        journal.caller_accounting_journal_entry(tx.caller(), old_balance, true);
+        Ok(())
+    }
+
+    fn reimburse_caller(
+        &self,
+        evm: &mut Self::Evm,
+        exec_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        if is_system_transaction(evm.ctx_ref()) {
+            return Ok(());
+        }
+        self.mainnet.reimburse_caller(evm, exec_result)
+    }
+
+    fn reward_beneficiary(
+        &self,
+        evm: &mut Self::Evm,
+        exec_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
+    ) -> Result<(), Self::Error> {
+        let ctx = evm.ctx();
+        if is_system_transaction(ctx) {
+            return Ok(());
+        }
+
+        let spec = ctx.cfg().spec();
+        let basefee = ctx.block().basefee() as u128;
+        let effective_gas_price = ctx.tx().effective_gas_price(basefee);
+        let coinbase_gas_price = if spec.into_eth_spec().is_enabled_in(SpecId::LONDON) {
+            effective_gas_price.saturating_sub(basefee)
+        } else {
+            effective_gas_price
+        };
+        let mut fee = U256::from(coinbase_gas_price * exec_result.gas().used() as u128);
+
+        // Blob fees are not burned but collected together with the transaction fees.
+        if spec.is_enabled_in(BscSpecId::CANCUN) {
+            let blob_price = ctx.block().blob_gasprice().unwrap_or_default();
+            fee += U256::from(blob_price * ctx.tx().total_blob_gas() as u128);
+        }
+
+        // Same as bsc-geth, fees are not paid to the system reward contract directly. They are
+        // collected in the system address and Parlia moves them at the end of the block, the
+        // system reward contract receives its share from the `distributeIncoming` transactions.
+        ctx.journal_mut().balance_incr(SYSTEM_ADDRESS, fee)?;
+        Ok(())
+    }
+}
+
+impl<EVM, ERROR> InspectorHandler for BscHandler<EVM, ERROR, EthFrame<EthInterpreter>>
+where
+    EVM: InspectorEvmTr<
+        Context: BscContextTr,
+        Frame = EthFrame<EthInterpreter>,
+        Inspector: Inspector<<<Self as Handler>::Evm as EvmTr>::Context, EthInterpreter>,
+    >,
+    ERROR: EvmTrError<EVM>,
+{
+    type IT = EthInterpreter;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::builder::DefaultBscEvm,
+        constants::{SYSTEM_ADDRESS, SYSTEM_REWARD_CONTRACT, VALIDATOR_CONTRACT},
+        BscBuilder, BscContext, DefaultBsc,
+    };
+    use revm::{
+        context::{result::InvalidTransaction, BlockEnv, Context, TxEnv},
+        context_interface::result::EVMError,
+        database::InMemoryDB,
+        primitives::{address, Address, TxKind, U256},
+        state::AccountInfo,
+        ExecuteEvm,
+    };
+
+    const VALIDATOR: Address = address!("0x0000000000000000000000000000000000000abc");
+    const USER: Address = address!("0x0000000000000000000000000000000000000def");
+
+    fn evm_with_db() -> DefaultBscEvm<BscContext<InMemoryDB>> {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            VALIDATOR,
+            AccountInfo::default().with_balance(U256::from(1_000)),
+        );
+        db.insert_account_info(
+            USER,
+            AccountInfo::default().with_balance(U256::from(10_000_000)),
+        );
+        Context::bsc()
+            .with_db(db)
+            .with_block(BlockEnv {
+                beneficiary: VALIDATOR,
+                gas_limit: 30_000_000,
+                ..Default::default()
+            })
+            .build_bsc()
+    }
+
+    #[test]
+    fn test_system_transaction() {
+        let mut evm = evm_with_db();
+        // Gas limit above the block gas limit, like the system transactions of the validator.
+        let tx = TxEnv::builder()
+            .caller(VALIDATOR)
+            .kind(TxKind::Call(VALIDATOR_CONTRACT))
+            .value(U256::from(100))
+            .gas_limit(u64::MAX / 2)
+            .gas_price(0)
+            .build()
+            .unwrap();
+        let output = evm.transact(tx).unwrap();
+        assert!(output.result.is_success());
+
+        let state = output.state;
+        assert_eq!(state[&VALIDATOR].info.balance, U256::from(900));
+        assert_eq!(state[&VALIDATOR].info.nonce, 1);
+        assert_eq!(state[&VALIDATOR_CONTRACT].info.balance, U256::from(100));
+        assert!(!state.contains_key(&SYSTEM_ADDRESS));
+    }
+
+    #[test]
+    fn test_system_transaction_lack_of_funds() {
+        let mut evm = evm_with_db();
+        let tx = TxEnv::builder()
+            .caller(VALIDATOR)
+            .kind(TxKind::Call(VALIDATOR_CONTRACT))
+            .value(U256::from(1_001))
+            .gas_limit(u64::MAX / 2)
+            .gas_price(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            evm.transact(tx),
+            Err(EVMError::Transaction(
+                InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_fees_collected_in_system_address() {
+        let mut evm = evm_with_db();
+        let tx = TxEnv::builder()
+            .caller(USER)
+            .kind(TxKind::Call(VALIDATOR_CONTRACT))
+            .gas_limit(100_000)
+            .gas_price(10)
+            .build()
+            .unwrap();
+        let output = evm.transact(tx).unwrap();
+        let gas_used = output.result.gas_used();
+        assert_eq!(gas_used, 21_000);
+
+        let state = output.state;
+        let fee = U256::from(gas_used * 10);
+        assert_eq!(state[&SYSTEM_ADDRESS].info.balance, fee);
+        assert_eq!(state[&USER].info.balance, U256::from(10_000_000) - fee);
+        // Neither the block beneficiary nor the system reward contract is credited directly.
+        assert!(!state.contains_key(&VALIDATOR));
+        assert!(!state.contains_key(&SYSTEM_REWARD_CONTRACT));
+    }
+
+    #[test]
+    fn test_zero_gas_price_from_user_is_not_system_transaction() {
+        let mut evm = evm_with_db();
+        // Not sent by the block beneficiary, validated like any other transaction.
+        let tx = TxEnv::builder()
+            .caller(USER)
+            .kind(TxKind::Call(VALIDATOR_CONTRACT))
+            .gas_limit(u64::MAX / 2)
+            .gas_price(0)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            evm.transact(tx),
+            Err(EVMError::Transaction(
+                InvalidTransaction::CallerGasLimitMoreThanBlock
+            ))
+        ));
+    }
+}
@@ -0,0 +1,22 @@
+//! BNB Smart Chain specific types, handler and EVM.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc as std;
+
+pub mod api;
+pub mod constants;
+pub mod evm;
+pub mod handler;
+pub mod precompiles;
+pub mod spec;
+
+pub use api::{
+    builder::BscBuilder,
+    default_ctx::{BscContext, DefaultBsc},
+};
+pub use evm::BscEvm;
+pub use handler::{is_system_transaction, BscHandler};
+pub use precompiles::BscPrecompiles;
+pub use spec::*;
@@ -0,0 +1,311 @@
+//! Contains BNB Smart Chain specific precompiles.
+//!
+//! BNB Smart Chain precompiles are activated on top of the Ethereum precompiles of the matching
+//! Ethereum hardfork:
+//!
+//! | Hardfork            | Changes                                                                  |
+//! | :------------------ | :----------------------------------------------------------------------- |
+//! | Ramanujan (genesis) | `0x64` `tmHeaderValidate` and `0x65` `iavlMerkleProofValidate`           |
+//! | Nano                | `0x64` and `0x65` suspended                                              |
+//! | Moran               | `0x64` and `0x65` resumed                                                |
+//! | Luban               | `0x66` `blsSignatureVerify` and `0x67` `cometBFTLightBlockValidate`      |
+//! | Feynman             | `0x68` `verifyDoubleSignEvidence` and `0x69` `secp256k1SignatureRecover` |
+//!
+//! `0x64`, `0x65` and `0x67` decode their input like the BSC client but do not verify headers,
+//! proofs or light blocks, calls that pass decoding fail.
+use crate::BscSpecId;
+use revm::{
+    context::Cfg,
+    context_interface::ContextTr,
+    handler::{EthPrecompiles, PrecompileProvider},
+    interpreter::{CallInputs, InterpreterResult},
+    precompile::{bsc, Precompiles},
+    primitives::{hardfork::SpecId, Address, OnceLock},
+};
+use std::boxed::Box;
+use std::string::String;
+
+/// BNB Smart Chain precompile provider
+#[derive(Debug, Clone)]
+pub struct BscPrecompiles {
+    /// Inner precompile provider is same as Ethereums.
+    inner: EthPrecompiles,
+    /// Spec id of the precompile provider.
+    spec: BscSpecId,
+}
+
+impl BscPrecompiles {
+    /// Create a new precompile provider with the given BscSpec.
+    #[inline]
+    pub fn new_with_spec(spec: BscSpecId) -> Self {
+        let precompiles = match spec {
+            BscSpecId::RAMANUJAN
+            | BscSpecId::NIELS
+            | BscSpecId::MIRROR_SYNC
+            | BscSpecId::BRUNO
+            | BscSpecId::EULER => ramanujan(),
+            BscSpecId::NANO => nano(),
+            BscSpecId::MORAN | BscSpecId::GIBBS | BscSpecId::PLANCK => moran(),
+            BscSpecId::LUBAN | BscSpecId::PLATO => luban(),
+            BscSpecId::HERTZ | BscSpecId::HERTZ_FIX | BscSpecId::KEPLER => hertz(),
+            BscSpecId::FEYNMAN | BscSpecId::FEYNMAN_FIX => feynman(),
+            BscSpecId::CANCUN | BscSpecId::HABER | BscSpecId::HABER_FIX | BscSpecId::BOHR => {
+                cancun()
+            }
+            BscSpecId::PASCAL | BscSpecId::LORENTZ | BscSpecId::MAXWELL | BscSpecId::FERMI => {
+                pascal()
+            }
+        };
+
+        Self {
+            inner: EthPrecompiles {
+                precompiles,
+                spec: SpecId::default(),
+            },
+            spec,
+        }
+    }
+
+    /// Precompiles getter.
+    #[inline]
+    pub fn precompiles(&self) -> &'static Precompiles {
+        self.inner.precompiles
+    }
+}
+
+/// Returns precompiles for Ramanujan spec.
+pub fn ramanujan() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = Precompiles::istanbul().clone();
+        // Light client of the BNB Beacon Chain
+        precompiles.extend([
+            bsc::tendermint::TM_HEADER_VALIDATE,
+            bsc::iavl::IAVL_MERKLE_PROOF_VALIDATE,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns precompiles for Nano spec.
+pub fn nano() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = ramanujan().clone();
+        // Light client suspended after the cross chain bridge exploit
+        precompiles.extend([
+            bsc::tendermint::TM_HEADER_VALIDATE_NANO,
+            bsc::iavl::IAVL_MERKLE_PROOF_VALIDATE_NANO,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns precompiles for Moran spec.
+pub fn moran() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = nano().clone();
+        // Light client is resumed
+        precompiles.extend([
+            bsc::tendermint::TM_HEADER_VALIDATE,
+            bsc::iavl::IAVL_MERKLE_PROOF_VALIDATE,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns precompiles for Luban spec.
+pub fn luban() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = moran().clone();
+        // Fast finality votes and light client of Greenfield
+        precompiles.extend([
+            bsc::bls::BLS_SIGNATURE_VERIFY,
+            bsc::cometbft::COMETBFT_LIGHT_BLOCK_VALIDATE,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns precompiles for Hertz spec.
+pub fn hertz() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = luban().clone();
+        // Berlin modexp gas pricing
+        precompiles.extend(Precompiles::berlin().inner().values().cloned());
+        precompiles
+    })
+}
+
+/// Returns precompiles for Feynman spec.
+pub fn feynman() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = hertz().clone();
+        // Slashing of double signs and recovery of BNB Beacon Chain assets
+        precompiles.extend([
+            bsc::double_sign::VERIFY_DOUBLE_SIGN_EVIDENCE,
+            bsc::secp256k1::SECP256K1_SIGNATURE_RECOVER,
+        ]);
+        precompiles
+    })
+}
+
+/// Returns precompiles for Cancun spec.
+pub fn cancun() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = feynman().clone();
+        // EIP-4844 point evaluation
+        precompiles.extend(Precompiles::cancun().inner().values().cloned());
+        precompiles
+    })
+}
+
+/// Returns precompiles for Pascal spec.
+pub fn pascal() -> &'static Precompiles {
+    static INSTANCE: OnceLock<Precompiles> = OnceLock::new();
+    INSTANCE.get_or_init(|| {
+        let mut precompiles = cancun().clone();
+        // Prague bls12 precompiles
+        precompiles.extend(Precompiles::prague().inner().values().cloned());
+        precompiles
+    })
+}
+
+impl<CTX> PrecompileProvider<CTX> for BscPrecompiles
+where
+    CTX: ContextTr<Cfg: Cfg<Spec = BscSpecId>>,
+{
+    type Output = InterpreterResult;
+
+    #[inline]
+    fn set_spec(&mut self, spec: <CTX::Cfg as Cfg>::Spec) -> bool {
+        if spec == self.spec {
+            return false;
+        }
+        *self = Self::new_with_spec(spec);
+        true
+    }
+
+    #[inline]
+    fn run(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CallInputs,
+    ) -> Result<Option<Self::Output>, String> {
+        self.inner.run(context, inputs)
+    }
+
+    #[inline]
+    fn warm_addresses(&self) -> Box<impl Iterator<Item = Address>> {
+        self.inner.warm_addresses()
+    }
+
+    #[inline]
+    fn contains(&self, address: &Address) -> bool {
+        self.inner.contains(address)
+    }
+}
+
+impl Default for BscPrecompiles {
+    fn default() -> Self {
+        Self::new_with_spec(BscSpecId::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::precompile::{u64_to_address, PrecompileError, PrecompileSpecId};
+
+    const TM_HEADER_VALIDATE: Address = u64_to_address(0x64);
+    const IAVL_MERKLE_PROOF_VALIDATE: Address = u64_to_address(0x65);
+    const BLS_SIGNATURE_VERIFY: Address = u64_to_address(0x66);
+    const COMETBFT_LIGHT_BLOCK_VALIDATE: Address = u64_to_address(0x67);
+    const VERIFY_DOUBLE_SIGN_EVIDENCE: Address = u64_to_address(0x68);
+    const SECP256K1_SIGNATURE_RECOVER: Address = u64_to_address(0x69);
+
+    fn is_suspended(precompiles: &Precompiles, address: &Address) -> bool {
+        let precompile = precompiles.get(address).unwrap();
+        precompile.execute(&[], 3_000) == Err(PrecompileError::other("suspended"))
+    }
+
+    #[test]
+    fn test_precompiles_follow_eth_spec() {
+        let istanbul = BscPrecompiles::new_with_spec(BscSpecId::PLATO);
+        assert_eq!(
+            istanbul.precompiles().len(),
+            Precompiles::new(PrecompileSpecId::ISTANBUL).len() + 4
+        );
+
+        let prague = BscPrecompiles::new_with_spec(BscSpecId::MAXWELL);
+        assert_eq!(
+            prague.precompiles().len(),
+            Precompiles::new(PrecompileSpecId::PRAGUE).len() + 6
+        );
+        for address in Precompiles::prague().addresses() {
+            assert_eq!(
+                prague.precompiles().get(address).unwrap().id(),
+                Precompiles::prague().get(address).unwrap().id()
+            );
+        }
+    }
+
+    #[test]
+    fn test_light_client_activation() {
+        let ramanujan = BscPrecompiles::new_with_spec(BscSpecId::RAMANUJAN).precompiles();
+        assert!(!is_suspended(ramanujan, &TM_HEADER_VALIDATE));
+        assert!(!is_suspended(ramanujan, &IAVL_MERKLE_PROOF_VALIDATE));
+        assert!(!ramanujan.contains(&COMETBFT_LIGHT_BLOCK_VALIDATE));
+
+        let nano = BscPrecompiles::new_with_spec(BscSpecId::NANO).precompiles();
+        assert!(is_suspended(nano, &TM_HEADER_VALIDATE));
+        assert!(is_suspended(nano, &IAVL_MERKLE_PROOF_VALIDATE));
+        assert!(!nano.contains(&COMETBFT_LIGHT_BLOCK_VALIDATE));
+
+        for spec in [BscSpecId::EULER, BscSpecId::MORAN, BscSpecId::PLANCK] {
+            let precompiles = BscPrecompiles::new_with_spec(spec).precompiles();
+            assert!(!is_suspended(precompiles, &TM_HEADER_VALIDATE));
+            assert!(!is_suspended(precompiles, &IAVL_MERKLE_PROOF_VALIDATE));
+            assert!(!precompiles.contains(&COMETBFT_LIGHT_BLOCK_VALIDATE));
+        }
+
+        for spec in [
+            BscSpecId::LUBAN,
+            BscSpecId::PLATO,
+            BscSpecId::HERTZ,
+            BscSpecId::FEYNMAN,
+            BscSpecId::CANCUN,
+            BscSpecId::FERMI,
+        ] {
+            let precompiles = BscPrecompiles::new_with_spec(spec).precompiles();
+            assert!(!is_suspended(precompiles, &TM_HEADER_VALIDATE));
+            assert!(!is_suspended(precompiles, &IAVL_MERKLE_PROOF_VALIDATE));
+            assert!(precompiles.contains(&COMETBFT_LIGHT_BLOCK_VALIDATE));
+        }
+    }
+
+    #[test]
+    fn test_activation() {
+        let activated = |spec, address: &Address| {
+            BscPrecompiles::new_with_spec(spec)
+                .precompiles()
+                .contains(address)
+        };
+        for address in [BLS_SIGNATURE_VERIFY, COMETBFT_LIGHT_BLOCK_VALIDATE] {
+            assert!(!activated(BscSpecId::PLANCK, &address));
+            assert!(activated(BscSpecId::LUBAN, &address));
+            assert!(activated(BscSpecId::HERTZ, &address));
+        }
+        for address in [VERIFY_DOUBLE_SIGN_EVIDENCE, SECP256K1_SIGNATURE_RECOVER] {
+            assert!(!activated(BscSpecId::KEPLER, &address));
+            assert!(activated(BscSpecId::FEYNMAN, &address));
+            assert!(activated(BscSpecId::CANCUN, &address));
+            assert!(activated(BscSpecId::MAXWELL, &address));
+        }
+    }
+}
@@ -0,0 +1,289 @@
+//! Contains the `[BscSpecId]` type and its implementation.
+use core::str::FromStr;
+use revm::primitives::hardfork::{name as eth_name, SpecId, UnknownHardfork};
+
+/// BNB Smart Chain spec id.
+///
+/// Hardforks are ordered by their activation on BSC mainnet. The Ethereum rules that are enabled
+/// by a hardfork are returned by [`BscSpecId::into_eth_spec`].
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(non_camel_case_types)]
+pub enum BscSpecId {
+    /// Ramanujan spec id, the genesis rules of BSC.
+    RAMANUJAN = 100,
+    /// Niels spec id.
+    NIELS,
+    /// MirrorSync spec id.
+    MIRROR_SYNC,
+    /// Bruno spec id.
+    BRUNO,
+    /// Euler spec id.
+    EULER,
+    /// Nano spec id.
+    NANO,
+    /// Moran spec id.
+    MORAN,
+    /// Gibbs spec id.
+    GIBBS,
+    /// Planck spec id.
+    PLANCK,
+    /// Luban spec id.
+    LUBAN,
+    /// Plato spec id.
+    PLATO,
+    /// Hertz spec id, enables Berlin and London.
+    HERTZ,
+    /// HertzFix spec id.
+    HERTZ_FIX,
+    /// Kepler spec id, enables Shanghai.
+    KEPLER,
+    /// Feynman spec id.
+    FEYNMAN,
+    /// FeynmanFix spec id.
+    FEYNMAN_FIX,
+    /// Cancun spec id.
+    CANCUN,
+    /// Haber spec id.
+    HABER,
+    /// HaberFix spec id.
+    HABER_FIX,
+    /// Bohr spec id.
+    BOHR,
+    /// Pascal spec id, enables Prague.
+    PASCAL,
+    /// Lorentz spec id.
+    LORENTZ,
+    /// Maxwell spec id.
+    #[default]
+    MAXWELL,
+    /// Fermi spec id.
+    FERMI,
+}
+
+impl BscSpecId {
+    /// Converts the [`BscSpecId`] into a [`SpecId`].
+    pub const fn into_eth_spec(self) -> SpecId {
+        match self {
+            Self::RAMANUJAN
+            | Self::NIELS
+            | Self::MIRROR_SYNC
+            | Self::BRUNO
+            | Self::EULER
+            | Self::NANO
+            | Self::MORAN
+            | Self::GIBBS
+            | Self::PLANCK
+            | Self::LUBAN
+            | Self::PLATO => SpecId::MUIR_GLACIER,
+            Self::HERTZ | Self::HERTZ_FIX => SpecId::LONDON,
+            Self::KEPLER | Self::FEYNMAN | Self::FEYNMAN_FIX => SpecId::SHANGHAI,
+            Self::CANCUN | Self::HABER | Self::HABER_FIX | Self::BOHR => SpecId::CANCUN,
+            Self::PASCAL | Self::LORENTZ | Self::MAXWELL | Self::FERMI => SpecId::PRAGUE,
+        }
+    }
+
+    /// Checks if the [`BscSpecId`] is enabled in the other [`BscSpecId`].
+    pub const fn is_enabled_in(self, other: BscSpecId) -> bool {
+        other as u8 <= self as u8
+    }
+}
+
+impl From<BscSpecId> for SpecId {
+    fn from(spec: BscSpecId) -> Self {
+        spec.into_eth_spec()
+    }
+}
+
+impl FromStr for BscSpecId {
+    type Err = UnknownHardfork;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            name::RAMANUJAN => Ok(BscSpecId::RAMANUJAN),
+            name::NIELS => Ok(BscSpecId::NIELS),
+            name::MIRROR_SYNC => Ok(BscSpecId::MIRROR_SYNC),
+            name::BRUNO => Ok(BscSpecId::BRUNO),
+            name::EULER => Ok(BscSpecId::EULER),
+            name::NANO => Ok(BscSpecId::NANO),
+            name::MORAN => Ok(BscSpecId::MORAN),
+            name::GIBBS => Ok(BscSpecId::GIBBS),
+            name::PLANCK => Ok(BscSpecId::PLANCK),
+            name::LUBAN => Ok(BscSpecId::LUBAN),
+            name::PLATO => Ok(BscSpecId::PLATO),
+            name::HERTZ => Ok(BscSpecId::HERTZ),
+            name::HERTZ_FIX => Ok(BscSpecId::HERTZ_FIX),
+            name::KEPLER => Ok(BscSpecId::KEPLER),
+            name::FEYNMAN => Ok(BscSpecId::FEYNMAN),
+            name::FEYNMAN_FIX => Ok(BscSpecId::FEYNMAN_FIX),
+            eth_name::CANCUN => Ok(BscSpecId::CANCUN),
+            name::HABER => Ok(BscSpecId::HABER),
+            name::HABER_FIX => Ok(BscSpecId::HABER_FIX),
+            name::BOHR => Ok(BscSpecId::BOHR),
+            name::PASCAL => Ok(BscSpecId::PASCAL),
+            name::LORENTZ => Ok(BscSpecId::LORENTZ),
+            name::MAXWELL => Ok(BscSpecId::MAXWELL),
+            name::FERMI => Ok(BscSpecId::FERMI),
+            _ => Err(UnknownHardfork),
+        }
+    }
+}
+
+impl From<BscSpecId> for &'static str {
+    fn from(spec_id: BscSpecId) -> Self {
+        match spec_id {
+            BscSpecId::RAMANUJAN => name::RAMANUJAN,
+            BscSpecId::NIELS => name::NIELS,
+            BscSpecId::MIRROR_SYNC => name::MIRROR_SYNC,
+            BscSpecId::BRUNO => name::BRUNO,
+            BscSpecId::EULER => name::EULER,
+            BscSpecId::NANO => name::NANO,
+            BscSpecId::MORAN => name::MORAN,
+            BscSpecId::GIBBS => name::GIBBS,
+            BscSpecId::PLANCK => name::PLANCK,
+            BscSpecId::LUBAN => name::LUBAN,
+            BscSpecId::PLATO => name::PLATO,
+            BscSpecId::HERTZ => name::HERTZ,
+            BscSpecId::HERTZ_FIX => name::HERTZ_FIX,
+            BscSpecId::KEPLER => name::KEPLER,
+            BscSpecId::FEYNMAN => name::FEYNMAN,
+            BscSpecId::FEYNMAN_FIX => name::FEYNMAN_FIX,
+            BscSpecId::CANCUN => eth_name::CANCUN,
+            BscSpecId::HABER => name::HABER,
+            BscSpecId::HABER_FIX => name::HABER_FIX,
+            BscSpecId::BOHR => name::BOHR,
+            BscSpecId::PASCAL => name::PASCAL,
+            BscSpecId::LORENTZ => name::LORENTZ,
+            BscSpecId::MAXWELL => name::MAXWELL,
+            BscSpecId::FERMI => name::FERMI,
+        }
+    }
+}
+
+/// String identifiers for BNB Smart Chain hardforks
+pub mod name {
+    /// Ramanujan spec name.
+    pub const RAMANUJAN: &str = "Ramanujan";
+    /// Niels spec name.
+    pub const NIELS: &str = "Niels";
+    /// MirrorSync spec name.
+    pub const MIRROR_SYNC: &str = "MirrorSync";
+    /// Bruno spec name.
+    pub const BRUNO: &str = "Bruno";
+    /// Euler spec name.
+    pub const EULER: &str = "Euler";
+    /// Nano spec name.
+    pub const NANO: &str = "Nano";
+    /// Moran spec name.
+    pub const MORAN: &str = "Moran";
+    /// Gibbs spec name.
+    pub const GIBBS: &str = "Gibbs";
+    /// Planck spec name.
+    pub const PLANCK: &str = "Planck";
+    /// Luban spec name.
+    pub const LUBAN: &str = "Luban";
+    /// Plato spec name.
+    pub const PLATO: &str = "Plato";
+    /// Hertz spec name.
+    pub const HERTZ: &str = "Hertz";
+    /// HertzFix spec name.
+    pub const HERTZ_FIX: &str = "HertzFix";
+    /// Kepler spec name.
+    pub const KEPLER: &str = "Kepler";
+    /// Feynman spec name.
+    pub const FEYNMAN: &str = "Feynman";
+    /// FeynmanFix spec name.
+    pub const FEYNMAN_FIX: &str = "FeynmanFix";
+    /// Haber spec name.
+    pub const HABER: &str = "Haber";
+    /// HaberFix spec name.
+    pub const HABER_FIX: &str = "HaberFix";
+    /// Bohr spec name.
+    pub const BOHR: &str = "Bohr";
+    /// Pascal spec name.
+    pub const PASCAL: &str = "Pascal";
+    /// Lorentz spec name.
+    pub const LORENTZ: &str = "Lorentz";
+    /// Maxwell spec name.
+    pub const MAXWELL: &str = "Maxwell";
+    /// Fermi spec name.
+    pub const FERMI: &str = "Fermi";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bsc_spec_id_eth_spec_compatibility() {
+        // (BscSpecId, enabled ETH spec, first disabled ETH spec)
+        let test_cases = [
+            (BscSpecId::RAMANUJAN, SpecId::MUIR_GLACIER, SpecId::BERLIN),
+            (BscSpecId::PLATO, SpecId::MUIR_GLACIER, SpecId::BERLIN),
+            (BscSpecId::HERTZ, SpecId::LONDON, SpecId::MERGE),
+            (BscSpecId::KEPLER, SpecId::SHANGHAI, SpecId::CANCUN),
+            (BscSpecId::FEYNMAN_FIX, SpecId::SHANGHAI, SpecId::CANCUN),
+            (BscSpecId::HABER, SpecId::CANCUN, SpecId::PRAGUE),
+            (BscSpecId::BOHR, SpecId::CANCUN, SpecId::PRAGUE),
+            (BscSpecId::PASCAL, SpecId::PRAGUE, SpecId::OSAKA),
+            (BscSpecId::MAXWELL, SpecId::PRAGUE, SpecId::OSAKA),
+        ];
+
+        for (bsc_spec, enabled, disabled) in test_cases {
+            assert!(
+                bsc_spec.into_eth_spec().is_enabled_in(enabled),
+                "{bsc_spec:?} should be enabled in ETH {enabled:?}"
+            );
+            assert!(
+                !bsc_spec.into_eth_spec().is_enabled_in(disabled),
+                "{bsc_spec:?} should not be enabled in ETH {disabled:?}"
+            );
+        }
+
+        assert!(BscSpecId::LORENTZ.is_enabled_in(BscSpecId::HERTZ));
+        assert!(BscSpecId::LORENTZ.is_enabled_in(BscSpecId::LORENTZ));
+        assert!(!BscSpecId::LORENTZ.is_enabled_in(BscSpecId::MAXWELL));
+    }
+
+    #[test]
+    fn test_bsc_spec_id_names() {
+        let specs = [
+            BscSpecId::RAMANUJAN,
+            BscSpecId::NIELS,
+            BscSpecId::MIRROR_SYNC,
+            BscSpecId::BRUNO,
+            BscSpecId::EULER,
+            BscSpecId::NANO,
+            BscSpecId::MORAN,
+            BscSpecId::GIBBS,
+            BscSpecId::PLANCK,
+            BscSpecId::LUBAN,
+            BscSpecId::PLATO,
+            BscSpecId::HERTZ,
+            BscSpecId::HERTZ_FIX,
+            BscSpecId::KEPLER,
+            BscSpecId::FEYNMAN,
+            BscSpecId::FEYNMAN_FIX,
+            BscSpecId::CANCUN,
+            BscSpecId::HABER,
+            BscSpecId::HABER_FIX,
+            BscSpecId::BOHR,
+            BscSpecId::PASCAL,
+            BscSpecId::LORENTZ,
+            BscSpecId::MAXWELL,
+            BscSpecId::FERMI,
+        ];
+        for (i, spec) in specs.into_iter().enumerate() {
+            assert_eq!(spec as usize, BscSpecId::RAMANUJAN as usize + i);
+            let name: &'static str = spec.into();
+            assert_eq!(name.parse(), Ok(spec));
+        }
+        assert_eq!("Unknown".parse::<BscSpecId>(), Err(UnknownHardfork));
+    }
+
+    #[test]
+    fn default_bsc_spec_id() {
+        assert_eq!(BscSpecId::default(), BscSpecId::MAXWELL);
+    }
+}
@@ -7,12 +7,14 @@
 //! [`InvalidHeader`] is the error that is returned when the header is invalid.
 //!
 //! [`SuccessReason`] is the reason that the transaction successfully completed.
+//!
+//! [`RevertReason`] is the decoded output of a reverted execution.
 use crate::{context::ContextError, transaction::TransactionError};
 use core::fmt::{self, Debug};
 use database_interface::DBErrorMarker;
-use primitives::{Address, Bytes, Log, U256};
+use primitives::{keccak256, Address, Bytes, FixedBytes, HashMap, Log, U256};
 use state::EvmState;
-use std::{boxed::Box, string::String, vec::Vec};
+use std::{boxed::Box, format, string::String, vec::Vec};
 
 /// Trait for the halt reason.
 pub trait HaltReasonTr: Clone + Debug + PartialEq + Eq + From<HaltReason> {}
@@ -174,6 +176,24 @@ impl<HaltReasonTy> ExecutionResult<HaltReasonTy> {
             | Self::Halt { gas_used, .. } => gas_used,
         }
     }
+
+    /// Decodes the output of a reverted execution.
+    ///
+    /// Returns [`None`] if the execution was not reverted. Custom errors are returned as
+    /// [`RevertReason::Raw`], use [`ExecutionResult::revert_reason_with`] to resolve them.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.revert_reason_with(&ErrorRegistry::default())
+    }
+
+    /// Decodes the output of a reverted execution, resolving custom errors with the registry.
+    ///
+    /// Returns [`None`] if the execution was not reverted.
+    pub fn revert_reason_with(&self, registry: &ErrorRegistry) -> Option<RevertReason> {
+        match self {
+            Self::Revert { output, .. } => Some(RevertReason::decode_with(output, registry)),
+            _ => None,
+        }
+    }
 }
 
 /// Output of a transaction execution
@@ -212,6 +232,170 @@ impl Output {
     }
 }
 
+/// Decoded output of a reverted execution.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RevertReason {
+    /// Solidity `Error(string)`, emitted by `require` and `revert` with a message.
+    Error(String),
+    /// Solidity `Panic(uint256)`, emitted by failing assertions and checked arithmetic.
+    Panic(U256),
+    /// Custom error found in the [`ErrorRegistry`].
+    Custom {
+        /// Selector of the error.
+        selector: FixedBytes<4>,
+        /// Signature of the error, e.g. `InsufficientBalance(uint256,uint256)`.
+        signature: String,
+        /// ABI encoded arguments of the error.
+        args: Bytes,
+    },
+    /// Output that could not be decoded, empty if the execution reverted without data.
+    Raw(Bytes),
+}
+
+impl RevertReason {
+    /// Selector of `Error(string)`.
+    pub const ERROR_SELECTOR: FixedBytes<4> = FixedBytes([0x08, 0xc3, 0x79, 0xa0]);
+    /// Selector of `Panic(uint256)`.
+    pub const PANIC_SELECTOR: FixedBytes<4> = FixedBytes([0x4e, 0x48, 0x7b, 0x71]);
+
+    /// Decodes `Error(string)` and `Panic(uint256)` revert output.
+    pub fn decode(output: &[u8]) -> Self {
+        Self::decode_with(output, &ErrorRegistry::default())
+    }
+
+    /// Decodes `Error(string)`, `Panic(uint256)` and the custom errors of the registry.
+    pub fn decode_with(output: &[u8], registry: &ErrorRegistry) -> Self {
+        let raw = || Self::Raw(Bytes::copy_from_slice(output));
+        let Some((selector, data)) = output.split_first_chunk::<4>() else {
+            return raw();
+        };
+        let selector = FixedBytes(*selector);
+        if selector == Self::ERROR_SELECTOR {
+            return decode_abi_string(data).map_or_else(raw, Self::Error);
+        }
+        if selector == Self::PANIC_SELECTOR {
+            return match data.len() {
+                32 => Self::Panic(U256::from_be_slice(data)),
+                _ => raw(),
+            };
+        }
+        match registry.get(&selector) {
+            Some(signature) => Self::Custom {
+                selector,
+                signature: signature.into(),
+                args: Bytes::copy_from_slice(data),
+            },
+            None => raw(),
+        }
+    }
+
+    /// Returns the description of a Solidity panic code, using the same wording as geth.
+    pub fn panic_description(code: U256) -> Option<&'static str> {
+        let description = match u8::try_from(code).ok()? {
+            0x00 => "generic panic",
+            0x01 => "assert(false)",
+            0x11 => "arithmetic underflow or overflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "enum overflow",
+            0x22 => "invalid encoded storage byte array accessed",
+            0x31 => "out-of-bounds array access; popping on an empty array",
+            0x32 => "out-of-bounds access of an array or bytesN",
+            0x41 => "out of memory",
+            0x51 => "uninitialized function",
+            _ => return None,
+        };
+        Some(description)
+    }
+
+    /// Returns the reason of a Solidity panic like geth, the description of known codes and
+    /// `unknown panic code: 0x..` for the others.
+    pub fn panic_reason(code: U256) -> String {
+        match Self::panic_description(code) {
+            Some(description) => description.into(),
+            None => format!("unknown panic code: {code:#x}"),
+        }
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error(reason) => f.write_str(reason),
+            Self::Panic(code) => match Self::panic_description(*code) {
+                Some(description) => write!(f, "panic: {description} ({code:#x})"),
+                None => write!(f, "panic: unknown panic code {code:#x}"),
+            },
+            Self::Custom {
+                signature, args, ..
+            } => {
+                f.write_str(signature)?;
+                if !args.is_empty() {
+                    write!(f, " {args}")?;
+                }
+                Ok(())
+            }
+            Self::Raw(output) if output.is_empty() => f.write_str("empty revert data"),
+            Self::Raw(output) => write!(f, "{output}"),
+        }
+    }
+}
+
+/// Decodes an ABI encoded `string`.
+fn decode_abi_string(data: &[u8]) -> Option<String> {
+    let word = |start: usize| {
+        let word = U256::from_be_slice(data.get(start..start.checked_add(32)?)?);
+        usize::try_from(word).ok()
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = data.get(start..start.checked_add(len)?)?;
+    core::str::from_utf8(bytes).ok().map(Into::into)
+}
+
+/// Registry of custom error signatures used to decode [`RevertReason::Custom`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorRegistry {
+    errors: HashMap<FixedBytes<4>, String>,
+}
+
+impl ErrorRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the error signature, e.g. `InsufficientBalance(uint256,uint256)`, and returns its
+    /// selector.
+    pub fn insert(&mut self, signature: impl Into<String>) -> FixedBytes<4> {
+        let signature = signature.into();
+        let selector = FixedBytes::from_slice(&keccak256(signature.as_bytes())[..4]);
+        self.errors.insert(selector, signature);
+        selector
+    }
+
+    /// Adds the signature with an explicit selector.
+    pub fn insert_selector(&mut self, selector: FixedBytes<4>, signature: impl Into<String>) {
+        self.errors.insert(selector, signature.into());
+    }
+
+    /// Returns the signature of the selector.
+    pub fn get(&self, selector: &FixedBytes<4>) -> Option<&str> {
+        self.errors.get(selector).map(String::as_str)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for ErrorRegistry {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut registry = Self::new();
+        for signature in iter {
+            registry.insert(signature);
+        }
+        registry
+    }
+}
+
 /// Main EVM error
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -631,3 +815,105 @@ pub enum OutOfGasError {
     /// When performing SSTORE the gasleft is less than or equal to 2300
     ReentrancySentry,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::hex;
+
+    fn revert(output: Bytes) -> ExecutionResult {
+        ExecutionResult::Revert {
+            gas_used: 0,
+            output,
+        }
+    }
+
+    #[test]
+    fn test_revert_reason_error() {
+        let output = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000004"
+            "6f6f707300000000000000000000000000000000000000000000000000000000"
+        );
+        let reason = revert(output.into()).revert_reason().unwrap();
+        assert_eq!(reason, RevertReason::Error("oops".into()));
+        assert_eq!(reason.to_string(), "oops");
+
+        // Truncated string is not decoded.
+        let truncated = Bytes::copy_from_slice(&output[..output.len() - 32]);
+        assert_eq!(
+            RevertReason::decode(&truncated),
+            RevertReason::Raw(truncated)
+        );
+    }
+
+    #[test]
+    fn test_revert_reason_panic() {
+        let output = hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000011"
+        );
+        let reason = RevertReason::decode(&output);
+        assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+        assert_eq!(
+            reason.to_string(),
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+        assert_eq!(
+            RevertReason::Panic(U256::from(0x99)).to_string(),
+            "panic: unknown panic code 0x99"
+        );
+        assert_eq!(
+            RevertReason::panic_reason(U256::from(0x11)),
+            "arithmetic underflow or overflow"
+        );
+        assert_eq!(
+            RevertReason::panic_reason(U256::from(0x99)),
+            "unknown panic code: 0x99"
+        );
+    }
+
+    #[test]
+    fn test_revert_reason_custom() {
+        let registry: ErrorRegistry = ["InsufficientBalance(uint256,uint256)"]
+            .into_iter()
+            .collect();
+        let output = hex!(
+            "cf479181"
+            "0000000000000000000000000000000000000000000000000000000000000001"
+            "0000000000000000000000000000000000000000000000000000000000000002"
+        );
+
+        let result = revert(output.into());
+        assert_eq!(
+            result.revert_reason(),
+            Some(RevertReason::Raw(output.into()))
+        );
+        let reason = result.revert_reason_with(&registry).unwrap();
+        assert_eq!(
+            reason,
+            RevertReason::Custom {
+                selector: FixedBytes(hex!("cf479181")),
+                signature: "InsufficientBalance(uint256,uint256)".into(),
+                args: Bytes::copy_from_slice(&output[4..]),
+            }
+        );
+        assert!(reason
+            .to_string()
+            .starts_with("InsufficientBalance(uint256,uint256) 0x00"));
+    }
+
+    #[test]
+    fn test_revert_reason_not_reverted() {
+        let result: ExecutionResult = ExecutionResult::Halt {
+            reason: HaltReason::OpcodeNotFound,
+            gas_used: 0,
+        };
+        assert_eq!(result.revert_reason(), None);
+        assert_eq!(
+            revert(Bytes::new()).revert_reason().unwrap().to_string(),
+            "empty revert data"
+        );
+    }
+}
@@ -1,6 +1,6 @@
 //! This module contains [`BlockEnv`] and it implements [`Block`] trait.
 use context_interface::block::{BlobExcessGasAndPrice, Block};
-use primitives::{eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE, Address, B256, U256};
+use primitives::{eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE, Address, HashMap, B256, U256};
 
 /// The block environment
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -116,3 +116,111 @@ impl Default for BlockEnv {
         }
     }
 }
+
+/// Block overrides of `eth_call`.
+///
+/// Unset fields keep the value of the [`BlockEnv`]. Block hashes are served by the database,
+/// pass [`BlockOverrides::block_hash`] to the `StateOverride` database layer of `revm-database`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockOverrides {
+    /// Overrides the block number.
+    pub number: Option<U256>,
+    /// Overrides the difficulty.
+    pub difficulty: Option<U256>,
+    /// Overrides the timestamp.
+    pub time: Option<u64>,
+    /// Overrides the gas limit.
+    pub gas_limit: Option<u64>,
+    /// Overrides the beneficiary (coinbase).
+    pub coinbase: Option<Address>,
+    /// Overrides the prevrandao.
+    pub random: Option<B256>,
+    /// Overrides the base fee.
+    pub base_fee: Option<u64>,
+    /// Overrides the blob gas price, the excess blob gas is kept.
+    pub blob_base_fee: Option<u128>,
+    /// Overrides the hashes of the given block numbers.
+    pub block_hash: HashMap<u64, B256>,
+}
+
+impl BlockOverrides {
+    /// Applies the overrides to the block environment.
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = number;
+        }
+        if let Some(difficulty) = self.difficulty {
+            block.difficulty = difficulty;
+        }
+        if let Some(time) = self.time {
+            block.timestamp = U256::from(time);
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(coinbase) = self.coinbase {
+            block.beneficiary = coinbase;
+        }
+        if let Some(random) = self.random {
+            block.prevrandao = Some(random);
+        }
+        if let Some(base_fee) = self.base_fee {
+            block.basefee = base_fee;
+        }
+        if let Some(blob_gasprice) = self.blob_base_fee {
+            let excess_blob_gas = block
+                .blob_excess_gas_and_price
+                .map(|blob| blob.excess_blob_gas)
+                .unwrap_or_default();
+            block.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                excess_blob_gas,
+                blob_gasprice,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_block_overrides() {
+        let mut block = BlockEnv {
+            blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(
+                1_000_000,
+                BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
+            )),
+            ..Default::default()
+        };
+        let original = block.clone();
+
+        BlockOverrides::default().apply(&mut block);
+        assert_eq!(block, original);
+
+        let overrides = BlockOverrides {
+            number: Some(U256::from(10)),
+            time: Some(20),
+            coinbase: Some(Address::with_last_byte(1)),
+            random: Some(B256::with_last_byte(2)),
+            base_fee: Some(30),
+            blob_base_fee: Some(40),
+            ..Default::default()
+        };
+        overrides.apply(&mut block);
+        assert_eq!(block.number, U256::from(10));
+        assert_eq!(block.timestamp, U256::from(20));
+        assert_eq!(block.beneficiary, Address::with_last_byte(1));
+        assert_eq!(block.prevrandao, Some(B256::with_last_byte(2)));
+        assert_eq!(block.basefee, 30);
+        assert_eq!(block.gas_limit, original.gas_limit);
+        assert_eq!(
+            block.blob_excess_gas_and_price,
+            Some(BlobExcessGasAndPrice {
+                excess_blob_gas: 1_000_000,
+                blob_gasprice: 40,
+            })
+        );
+    }
+}
@@ -15,7 +15,7 @@ pub mod journal;
 pub mod local;
 pub mod tx;
 
-pub use block::BlockEnv;
+pub use block::{BlockEnv, BlockOverrides};
 pub use cfg::{Cfg, CfgEnv};
 pub use context::*;
 pub use evm::Evm;
@@ -1,8 +1,10 @@
 //! Async database interface.
 use crate::{DBErrorMarker, Database, DatabaseRef};
 use core::{error::Error, future::Future};
+use futures_util::{stream, StreamExt, TryStreamExt};
 use primitives::{Address, StorageKey, StorageValue, B256};
 use state::{AccountInfo, Bytecode};
+use std::vec::Vec;
 use tokio::runtime::{Handle, Runtime};
 
 /// The async EVM database interface
@@ -38,8 +40,49 @@ pub trait DatabaseAsync {
         &mut self,
         number: u64,
     ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+
+    /// Gets basic account information of multiple accounts.
+    ///
+    /// Default implementation requests the accounts one after another.
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> impl Future<Output = Result<Vec<Option<AccountInfo>>, Self::Error>> + Send
+    where
+        Self: Send,
+    {
+        async move {
+            let mut accounts = Vec::with_capacity(addresses.len());
+            for address in addresses {
+                accounts.push(self.basic_async(*address).await?);
+            }
+            Ok(accounts)
+        }
+    }
+
+    /// Gets storage values of multiple address and index pairs.
+    ///
+    /// Default implementation requests the slots one after another.
+    fn storage_many(
+        &mut self,
+        slots: &[(Address, StorageKey)],
+    ) -> impl Future<Output = Result<Vec<StorageValue>, Self::Error>> + Send
+    where
+        Self: Send,
+    {
+        async move {
+            let mut values = Vec::with_capacity(slots.len());
+            for (address, index) in slots {
+                values.push(self.storage_async(*address, *index).await?);
+            }
+            Ok(values)
+        }
+    }
 }
 
+/// Default of [`DatabaseAsyncRef::max_concurrent_requests`].
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 16;
+
 /// The async EVM database interface
 ///
 /// Contains the same methods as [DatabaseRef], but it returns [Future] type instead.
@@ -73,6 +116,54 @@ pub trait DatabaseAsyncRef {
         &self,
         number: u64,
     ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+
+    /// Maximum number of requests that [`basic_many`][Self::basic_many] and
+    /// [`storage_many`][Self::storage_many] run concurrently.
+    ///
+    /// Defaults to [`DEFAULT_MAX_CONCURRENT_REQUESTS`].
+    fn max_concurrent_requests(&self) -> usize {
+        DEFAULT_MAX_CONCURRENT_REQUESTS
+    }
+
+    /// Gets basic account information of multiple accounts.
+    ///
+    /// Default implementation runs up to [`max_concurrent_requests`][Self::max_concurrent_requests]
+    /// requests concurrently.
+    fn basic_many(
+        &self,
+        addresses: &[Address],
+    ) -> impl Future<Output = Result<Vec<Option<AccountInfo>>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        stream::iter(
+            addresses
+                .iter()
+                .map(|address| self.basic_async_ref(*address)),
+        )
+        .buffered(self.max_concurrent_requests().max(1))
+        .try_collect()
+    }
+
+    /// Gets storage values of multiple address and index pairs.
+    ///
+    /// Default implementation runs up to [`max_concurrent_requests`][Self::max_concurrent_requests]
+    /// requests concurrently.
+    fn storage_many(
+        &self,
+        slots: &[(Address, StorageKey)],
+    ) -> impl Future<Output = Result<Vec<StorageValue>, Self::Error>> + Send
+    where
+        Self: Sync,
+    {
+        stream::iter(
+            slots
+                .iter()
+                .map(|(address, index)| self.storage_async_ref(*address, *index)),
+        )
+        .buffered(self.max_concurrent_requests().max(1))
+        .try_collect()
+    }
 }
 
 /// Wraps a [DatabaseAsync] or [DatabaseAsyncRef] to provide a [`Database`] implementation.
@@ -117,6 +208,21 @@ impl<T> WrapDatabaseAsync<T> {
         let rt = HandleOrRuntime::Handle(handle);
         Self { db, rt }
     }
+
+    /// Returns a reference to the wrapped database.
+    pub fn inner(&self) -> &T {
+        &self.db
+    }
+
+    /// Returns a mutable reference to the wrapped database.
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.db
+    }
+
+    /// Consumes the wrapper and returns the wrapped database.
+    pub fn into_inner(self) -> T {
+        self.db
+    }
 }
 
 impl<T: DatabaseAsync> Database for WrapDatabaseAsync<T> {
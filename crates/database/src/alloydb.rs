@@ -7,7 +7,10 @@ use alloy_provider::{
 };
 use alloy_transport::TransportError;
 use core::error::Error;
-use database_interface::{async_db::DatabaseAsyncRef, DBErrorMarker};
+use database_interface::{
+    async_db::{DatabaseAsyncRef, DEFAULT_MAX_CONCURRENT_REQUESTS},
+    DBErrorMarker,
+};
 use primitives::{Address, StorageKey, StorageValue, B256};
 use state::{AccountInfo, Bytecode};
 use std::fmt::Display;
@@ -41,6 +44,8 @@ pub struct AlloyDB<N: Network, P: Provider<N>> {
     provider: P,
     /// The block number on which the queries will be based on.
     block_number: BlockId,
+    /// Maximum number of concurrent requests of the batched methods.
+    max_concurrent_requests: usize,
     _marker: core::marker::PhantomData<fn() -> N>,
 }
 
@@ -50,6 +55,7 @@ impl<N: Network, P: Provider<N>> AlloyDB<N, P> {
         Self {
             provider,
             block_number,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
             _marker: core::marker::PhantomData,
         }
     }
@@ -58,11 +64,21 @@ impl<N: Network, P: Provider<N>> AlloyDB<N, P> {
     pub fn set_block_number(&mut self, block_number: BlockId) {
         self.block_number = block_number;
     }
+
+    /// Sets the maximum number of requests sent concurrently by
+    /// [`DatabaseAsyncRef::basic_many`] and [`DatabaseAsyncRef::storage_many`].
+    pub fn set_max_concurrent_requests(&mut self, max_concurrent_requests: usize) {
+        self.max_concurrent_requests = max_concurrent_requests;
+    }
 }
 
 impl<N: Network, P: Provider<N>> DatabaseAsyncRef for AlloyDB<N, P> {
     type Error = DBTransportError;
 
+    fn max_concurrent_requests(&self) -> usize {
+        self.max_concurrent_requests
+    }
+
     async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         let nonce = self
             .provider
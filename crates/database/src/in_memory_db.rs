@@ -1,3 +1,4 @@
+use crate::snapshot::{restore_entries, save_entry, save_slot, SnapshotId, Snapshots};
 use core::convert::Infallible;
 use database_interface::{
     Database, DatabaseCommit, DatabaseRef, EmptyDB, BENCH_CALLER, BENCH_CALLER_BALANCE,
@@ -58,6 +59,22 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: This is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// Undo layers of the snapshots taken with [`CacheDB::snapshot`].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    snapshots: Snapshots<CacheSnapshotLayer>,
+}
+
+/// Values saved by a [`CacheDB`] snapshot before they were modified.
+///
+/// Accounts are saved without their storage, storage slots are saved one by one.
+/// `None` means that the entry was not present in the cache.
+#[derive(Debug, Clone, Default)]
+pub struct CacheSnapshotLayer {
+    accounts: HashMap<Address, Option<(AccountInfo, AccountState)>>,
+    storage: HashMap<(Address, StorageKey), Option<StorageValue>>,
+    contracts: HashMap<B256, Option<Bytecode>>,
+    block_hashes: HashMap<U256, Option<B256>>,
+    logs_len: usize,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -74,6 +91,8 @@ impl<ExtDb> CacheDB<CacheDB<ExtDb>> {
     /// - Contracts are overridden with outer contracts
     /// - Logs are appended
     /// - Block hashes are overridden with outer block hashes
+    ///
+    /// Snapshots of the outer cache are discarded, snapshots of the inner cache are kept.
     pub fn flatten(self) -> CacheDB<ExtDb> {
         let CacheDB {
             cache:
@@ -84,8 +103,23 @@ impl<ExtDb> CacheDB<CacheDB<ExtDb>> {
                     block_hashes,
                 },
             db: mut inner,
+            snapshots: _,
         } = self;
 
+        for (address, account) in &accounts {
+            inner.save_account(*address);
+            // Outer accounts replace the inner storage.
+            inner.save_storage(*address);
+            inner.save_slots(*address, account.storage.keys().copied());
+        }
+        if let Some(layer) = inner.snapshots.top_mut() {
+            for code_hash in contracts.keys() {
+                save_entry(&mut layer.contracts, &inner.cache.contracts, code_hash);
+            }
+            for number in block_hashes.keys() {
+                save_entry(&mut layer.block_hashes, &inner.cache.block_hashes, number);
+            }
+        }
         inner.cache.accounts.extend(accounts);
         inner.cache.contracts.extend(contracts);
         inner.cache.logs.extend(logs);
@@ -105,9 +139,108 @@ impl<ExtDB> CacheDB<ExtDB> {
         Self {
             cache: Cache::default(),
             db,
+            snapshots: Snapshots::default(),
         }
     }
 
+    /// Returns the undo layers of the valid snapshots.
+    pub fn snapshots(&self) -> &Snapshots<CacheSnapshotLayer> {
+        &self.snapshots
+    }
+
+    /// Takes a snapshot of the cache and returns its id.
+    ///
+    /// The snapshot is cheap to take, values are saved only when they are modified for the
+    /// first time after the snapshot through the [`CacheDB`] methods or [`DatabaseCommit`].
+    /// Direct modifications of the [`Cache`] fields are not tracked.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        self.snapshots.push(CacheSnapshotLayer {
+            logs_len: self.cache.logs.len(),
+            ..Default::default()
+        })
+    }
+
+    /// Reverts the accounts, storage, contracts, block hashes and logs to the snapshot.
+    ///
+    /// The snapshot and all snapshots taken after it are invalidated.
+    /// Returns `false` if the snapshot is not valid.
+    pub fn revert_to(&mut self, id: SnapshotId) -> bool {
+        let Some(layers) = self.snapshots.pop_to(id) else {
+            return false;
+        };
+        for layer in layers {
+            for (address, saved) in layer.accounts {
+                match saved {
+                    Some((info, account_state)) => {
+                        let account = self.cache.accounts.entry(address).or_default();
+                        account.info = info;
+                        account.account_state = account_state;
+                    }
+                    None => {
+                        self.cache.accounts.remove(&address);
+                    }
+                }
+            }
+            for ((address, key), value) in layer.storage {
+                let Some(account) = self.cache.accounts.get_mut(&address) else {
+                    continue;
+                };
+                match value {
+                    Some(value) => account.storage.insert(key, value),
+                    None => account.storage.remove(&key),
+                };
+            }
+            restore_entries(layer.contracts, &mut self.cache.contracts);
+            restore_entries(layer.block_hashes, &mut self.cache.block_hashes);
+            self.cache.logs.truncate(layer.logs_len);
+        }
+        true
+    }
+
+    /// Saves the account info and state in the latest snapshot before they are modified.
+    #[inline]
+    fn save_account(&mut self, address: Address) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            layer.accounts.entry(address).or_insert_with(|| {
+                self.cache
+                    .accounts
+                    .get(&address)
+                    .map(|account| (account.info.clone(), account.account_state.clone()))
+            });
+        }
+    }
+
+    /// Saves the storage slots in the latest snapshot before they are modified.
+    #[inline]
+    fn save_slots(&mut self, address: Address, keys: impl IntoIterator<Item = StorageKey>) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            let storage = self.cache.accounts.get(&address).map(|acc| &acc.storage);
+            for key in keys {
+                save_slot(&mut layer.storage, storage, address, key);
+            }
+        }
+    }
+
+    /// Saves all cached storage slots of the account in the latest snapshot before the storage
+    /// is cleared.
+    fn save_storage(&mut self, address: Address) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            if let Some(account) = self.cache.accounts.get(&address) {
+                for key in account.storage.keys() {
+                    save_slot(&mut layer.storage, Some(&account.storage), address, *key);
+                }
+            }
+        }
+    }
+
+    /// Inserts the block hash into the cache.
+    pub fn insert_block_hash(&mut self, number: U256, hash: B256) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            save_entry(&mut layer.block_hashes, &self.cache.block_hashes, &number);
+        }
+        self.cache.block_hashes.insert(number, hash);
+    }
+
     /// Inserts the account's code into the cache.
     ///
     /// Accounts objects and code are stored separately in the cache, this will take the code from the account and instead map it to the code hash.
@@ -119,6 +252,13 @@ impl<ExtDB> CacheDB<ExtDB> {
                 if account.code_hash == KECCAK_EMPTY {
                     account.code_hash = code.hash_slow();
                 }
+                if let Some(layer) = self.snapshots.top_mut() {
+                    save_entry(
+                        &mut layer.contracts,
+                        &self.cache.contracts,
+                        &account.code_hash,
+                    );
+                }
                 self.cache
                     .contracts
                     .entry(account.code_hash)
@@ -133,6 +273,7 @@ impl<ExtDB> CacheDB<ExtDB> {
     /// Inserts account info but not override storage
     pub fn insert_account_info(&mut self, address: Address, mut info: AccountInfo) {
         self.insert_contract(&mut info);
+        self.save_account(address);
         let account_entry = self.cache.accounts.entry(address).or_default();
         account_entry.update_info(info);
         if account_entry.account_state == AccountState::NotExisting {
@@ -150,6 +291,8 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
     /// Returns the account for the given address.
     ///
     /// If the account was not found in the cache, it will be loaded from the underlying database.
+    ///
+    /// Modifications through the returned reference are not tracked by snapshots.
     pub fn load_account(&mut self, address: Address) -> Result<&mut DbAccount, ExtDB::Error> {
         let db = &self.db;
         match self.cache.accounts.entry(address) {
@@ -172,6 +315,8 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         slot: StorageKey,
         value: StorageValue,
     ) -> Result<(), ExtDB::Error> {
+        self.load_account(address)?;
+        self.save_slots(address, [slot]);
         let account = self.load_account(address)?;
         account.storage.insert(slot, value);
         Ok(())
@@ -183,6 +328,10 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         address: Address,
         storage: HashMap<StorageKey, StorageValue>,
     ) -> Result<(), ExtDB::Error> {
+        self.load_account(address)?;
+        self.save_account(address);
+        self.save_storage(address);
+        self.save_slots(address, storage.keys().copied());
         let account = self.load_account(address)?;
         account.account_state = AccountState::StorageCleared;
         account.storage = storage.into_iter().collect();
@@ -196,6 +345,11 @@ impl<ExtDB> DatabaseCommit for CacheDB<ExtDB> {
             if !account.is_touched() {
                 continue;
             }
+            self.save_account(address);
+            if account.is_selfdestructed() || account.is_created() {
+                self.save_storage(address);
+            }
+            self.save_slots(address, account.storage.keys().copied());
             if account.is_selfdestructed() {
                 let db_account = self.cache.accounts.entry(address).or_default();
                 db_account.storage.clear();
@@ -362,7 +516,7 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
 }
 
 /// Database account representation.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DbAccount {
     /// Basic account information.
@@ -503,10 +657,10 @@ impl Database for BenchmarkDB {
 
 #[cfg(test)]
 mod tests {
-    use super::{CacheDB, EmptyDB};
-    use database_interface::Database;
-    use primitives::{Address, HashMap, StorageKey, StorageValue};
-    use state::AccountInfo;
+    use super::{AccountState, CacheDB, EmptyDB};
+    use database_interface::{Database, DatabaseCommit};
+    use primitives::{Address, HashMap, StorageKey, StorageValue, B256};
+    use state::{Account, AccountInfo, Bytecode, EvmStorageSlot};
 
     #[test]
     fn test_insert_account_storage() {
@@ -560,6 +714,127 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    fn committed_account(balance: u64, slot: u64, value: u64) -> Account {
+        Account::from(AccountInfo {
+            balance: StorageValue::from(balance),
+            ..Default::default()
+        })
+        .with_storage(
+            [(
+                StorageKey::from(slot),
+                EvmStorageSlot::new_changed(StorageValue::ZERO, StorageValue::from(value), 0),
+            )]
+            .into_iter(),
+        )
+        .with_touched_mark()
+    }
+
+    #[test]
+    fn test_snapshot_revert() {
+        let account = Address::with_last_byte(1);
+        let created = Address::with_last_byte(2);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.commit(HashMap::from_iter([(account, committed_account(1, 0, 1))]));
+
+        let snapshot = db.snapshot();
+        db.commit(HashMap::from_iter([
+            (account, committed_account(2, 1, 2)),
+            (created, committed_account(3, 0, 3)),
+        ]));
+        db.insert_account_storage(account, StorageKey::from(0), StorageValue::from(5))
+            .unwrap();
+        db.insert_block_hash(StorageValue::from(1), B256::with_last_byte(1));
+        db.insert_account_info(
+            created,
+            AccountInfo {
+                code: Some(Bytecode::new_legacy([0x00].into())),
+                ..Default::default()
+            },
+        );
+        assert_eq!(db.cache.contracts.len(), 3);
+
+        assert!(db.revert_to(snapshot));
+        let reverted = &db.cache.accounts[&account];
+        assert_eq!(reverted.info.balance, StorageValue::from(1));
+        assert_eq!(
+            reverted.storage,
+            HashMap::from_iter([(StorageKey::from(0), StorageValue::from(1))])
+        );
+        assert!(!db.cache.accounts.contains_key(&created));
+        assert!(db.cache.block_hashes.is_empty());
+        assert_eq!(db.cache.contracts.len(), 2);
+
+        // Snapshot is consumed by the revert.
+        assert!(!db.revert_to(snapshot));
+    }
+
+    #[test]
+    fn test_snapshot_saves_only_written_slots() {
+        let account = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.commit(HashMap::from_iter([(account, committed_account(1, 0, 1))]));
+        for slot in 1..4 {
+            db.insert_account_storage(account, StorageKey::from(slot), StorageValue::from(slot))
+                .unwrap();
+        }
+        let storage_before = db.cache.accounts[&account].storage.clone();
+
+        let snapshot = db.snapshot();
+        db.basic(account).unwrap();
+        db.storage(account, StorageKey::from(1)).unwrap();
+        db.storage(Address::with_last_byte(2), StorageKey::from(1))
+            .unwrap();
+        let layer = db.snapshots.top_mut().unwrap();
+        assert!(layer.accounts.is_empty() && layer.storage.is_empty());
+
+        db.insert_account_storage(account, StorageKey::from(2), StorageValue::from(7))
+            .unwrap();
+        let layer = db.snapshots.top_mut().unwrap();
+        assert!(layer.accounts.is_empty());
+        assert_eq!(
+            layer.storage,
+            HashMap::from_iter([((account, StorageKey::from(2)), Some(StorageValue::from(2)))])
+        );
+
+        db.replace_account_storage(
+            account,
+            HashMap::from_iter([(StorageKey::from(9), StorageValue::from(9))]),
+        )
+        .unwrap();
+        assert!(db.revert_to(snapshot));
+        let reverted = &db.cache.accounts[&account];
+        assert_eq!(reverted.storage, storage_before);
+        assert_eq!(reverted.account_state, AccountState::Touched);
+    }
+
+    #[test]
+    fn test_revert_invalidates_newer_snapshots() {
+        let account = Address::with_last_byte(1);
+        let mut db = CacheDB::new(EmptyDB::default());
+        let balance = |db: &CacheDB<EmptyDB>| db.cache.accounts[&account].info.balance;
+
+        let first = db.snapshot();
+        db.commit(HashMap::from_iter([(account, committed_account(1, 0, 1))]));
+        let second = db.snapshot();
+        db.commit(HashMap::from_iter([(account, committed_account(2, 0, 2))]));
+        let third = db.snapshot();
+        db.commit(HashMap::from_iter([(account, committed_account(3, 0, 3))]));
+
+        assert!(db.revert_to(second));
+        assert_eq!(balance(&db), StorageValue::from(1));
+        assert!(!db.revert_to(third));
+        assert!(!db.snapshots.contains(second));
+
+        let fourth = db.snapshot();
+        db.commit(HashMap::from_iter([(account, committed_account(4, 0, 4))]));
+        assert!(db.revert_to(fourth));
+        assert_eq!(balance(&db), StorageValue::from(1));
+
+        assert!(db.revert_to(first));
+        assert!(db.cache.accounts.is_empty());
+        assert!(db.snapshots.is_empty());
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
@@ -11,15 +11,39 @@ pub use database_interface::*;
 
 /// In-memory database implementations.
 pub mod in_memory_db;
+/// Concurrent prefetching of remote state.
+#[cfg(feature = "asyncdb")]
+pub mod prefetch;
+/// Snapshot ids and copy-on-write undo layers.
+pub mod snapshot;
+/// Binary state file format.
+#[cfg(feature = "std")]
+pub mod state_file;
+/// `eth_call` state and block hash overrides.
+pub mod state_override;
+/// State root computation.
+#[cfg(feature = "state-root")]
+pub mod state_root;
 /// State management and tracking.
 pub mod states;
+/// Execution witness recording and stateless execution.
+pub mod witness;
 
 #[cfg(feature = "alloydb")]
 pub use alloydb::{AlloyDB, BlockId, DBTransportError};
 
 pub use in_memory_db::*;
+#[cfg(feature = "asyncdb")]
+pub use prefetch::Prefetcher;
+pub use snapshot::SnapshotId;
+#[cfg(feature = "std")]
+pub use state_file::{StateFileKind, StateFileReader, StateFileRecord, StateFileWriter};
+pub use state_override::{AccountOverride, StateOverride, StateOverrideError};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
     OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
+#[cfg(feature = "asyncdb")]
+pub use witness::AsyncWitnessRecorder;
+pub use witness::{ExecutionWitness, MissingWitness, WitnessDB, WitnessRecorder};
@@ -0,0 +1,383 @@
+//! Concurrent prefetching of remote state into a [`CacheDB`].
+//!
+//! [`WrapDatabaseAsync`] waits for every account and storage slot one request at a time. When
+//! the accessed keys are known in advance, from an access list or from the
+//! [`ExecutionWitness`] of a previous run, [`Prefetcher`] loads them with the batched
+//! [`DatabaseAsyncRef::basic_many`] and [`DatabaseAsyncRef::storage_many`] before execution,
+//! together with the contract codes and block hashes of a witness. At most
+//! [`DatabaseAsyncRef::max_concurrent_requests`] requests are in flight at a time.
+use crate::{in_memory_db::Cache, AccountState, CacheDB, DbAccount, ExecutionWitness};
+use alloy_eip2930::AccessList;
+use database_interface::{async_db::DatabaseAsyncRef, WrapDatabaseAsync};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use primitives::{Address, HashSet, StorageKey, B256, U256};
+use std::vec::Vec;
+
+/// Accounts and storage slots to load into a [`CacheDB`] before execution.
+///
+/// Entries that are already cached are not requested again, so local modifications of the
+/// cache are never overridden.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Prefetcher {
+    /// Accounts to load.
+    pub accounts: HashSet<Address>,
+    /// Storage slots to load, their accounts are loaded too.
+    pub storage: HashSet<(Address, StorageKey)>,
+    /// Contract codes to load by their hash.
+    pub codes: HashSet<B256>,
+    /// Block hashes to load by their block number.
+    pub block_hashes: HashSet<u64>,
+}
+
+impl Prefetcher {
+    /// Creates an empty prefetcher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an account.
+    pub fn with_account(mut self, address: Address) -> Self {
+        self.accounts.insert(address);
+        self
+    }
+
+    /// Adds a storage slot.
+    pub fn with_storage(mut self, address: Address, index: StorageKey) -> Self {
+        self.storage.insert((address, index));
+        self
+    }
+
+    /// Adds a contract code.
+    pub fn with_code(mut self, code_hash: B256) -> Self {
+        self.codes.insert(code_hash);
+        self
+    }
+
+    /// Adds a block hash.
+    pub fn with_block_hash(mut self, number: u64) -> Self {
+        self.block_hashes.insert(number);
+        self
+    }
+
+    /// Adds the accounts and storage slots of the access list.
+    pub fn with_access_list(mut self, access_list: &AccessList) -> Self {
+        for item in access_list.iter() {
+            self.accounts.insert(item.address);
+            self.storage.extend(
+                item.storage_keys
+                    .iter()
+                    .map(|key| (item.address, StorageKey::from_be_bytes(key.0))),
+            );
+        }
+        self
+    }
+
+    /// Adds the accounts, storage slots, contract codes and block hashes read in a previous
+    /// execution.
+    ///
+    /// Values are loaded again from the database, only the keys of the witness are used.
+    pub fn with_witness(mut self, witness: &ExecutionWitness) -> Self {
+        self.accounts.extend(witness.accounts.keys().copied());
+        for (address, storage) in &witness.storage {
+            self.storage
+                .extend(storage.keys().map(|index| (*address, *index)));
+        }
+        self.codes.extend(witness.codes.keys().copied());
+        self.block_hashes
+            .extend(witness.block_hashes.keys().copied());
+        self
+    }
+
+    /// Returns `true` if there is nothing to load.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.storage.is_empty()
+            && self.codes.is_empty()
+            && self.block_hashes.is_empty()
+    }
+
+    /// Concurrently loads the entries that are not cached yet from `db` into the `cache`.
+    ///
+    /// Accounts, storage slots, codes and block hashes are requested one kind after another,
+    /// each with at most [`DatabaseAsyncRef::max_concurrent_requests`] requests in flight.
+    ///
+    /// Returns the number of requested entries.
+    pub async fn warm<DB: DatabaseAsyncRef + Sync>(
+        &self,
+        cache: &mut Cache,
+        db: &DB,
+    ) -> Result<usize, DB::Error> {
+        let accounts = self
+            .accounts
+            .iter()
+            .chain(self.storage.iter().map(|(address, _)| address))
+            .filter(|address| !cache.accounts.contains_key(*address))
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let storage = self
+            .storage
+            .iter()
+            .filter(|(address, index)| match cache.accounts.get(address) {
+                Some(account) => {
+                    !account.storage.contains_key(index)
+                        && !matches!(
+                            account.account_state,
+                            AccountState::StorageCleared | AccountState::NotExisting
+                        )
+                }
+                None => true,
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        let codes = self
+            .codes
+            .iter()
+            .filter(|code_hash| !cache.contracts.contains_key(*code_hash))
+            .copied()
+            .collect::<Vec<_>>();
+        let block_hashes = self
+            .block_hashes
+            .iter()
+            .filter(|number| !cache.block_hashes.contains_key(&U256::from(**number)))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let limit = db.max_concurrent_requests().max(1);
+        let infos = db.basic_many(&accounts).await?;
+        let values = db.storage_many(&storage).await?;
+        let bytecodes: Vec<_> =
+            stream::iter(codes.iter().map(|hash| db.code_by_hash_async_ref(*hash)))
+                .buffered(limit)
+                .try_collect()
+                .await?;
+        let hashes: Vec<_> = stream::iter(
+            block_hashes
+                .iter()
+                .map(|number| db.block_hash_async_ref(*number)),
+        )
+        .buffered(limit)
+        .try_collect()
+        .await?;
+
+        let loaded = accounts.len() + storage.len() + codes.len() + block_hashes.len();
+        for (address, info) in accounts.into_iter().zip(infos) {
+            let account = info
+                .map(|info| DbAccount {
+                    info,
+                    ..Default::default()
+                })
+                .unwrap_or_else(DbAccount::new_not_existing);
+            cache.accounts.insert(address, account);
+        }
+        for ((address, index), value) in storage.into_iter().zip(values) {
+            if let Some(account) = cache.accounts.get_mut(&address) {
+                // Storage of a non-existing account is always empty.
+                if account.account_state != AccountState::NotExisting {
+                    account.storage.insert(index, value);
+                }
+            }
+        }
+        cache.contracts.extend(codes.into_iter().zip(bytecodes));
+        cache
+            .block_hashes
+            .extend(block_hashes.into_iter().map(U256::from).zip(hashes));
+        Ok(loaded)
+    }
+}
+
+impl From<&AccessList> for Prefetcher {
+    fn from(access_list: &AccessList) -> Self {
+        Self::new().with_access_list(access_list)
+    }
+}
+
+impl From<&ExecutionWitness> for Prefetcher {
+    fn from(witness: &ExecutionWitness) -> Self {
+        Self::new().with_witness(witness)
+    }
+}
+
+impl<T: DatabaseAsyncRef + Sync> CacheDB<WrapDatabaseAsync<T>> {
+    /// Concurrently loads the prefetcher entries from the wrapped async database.
+    ///
+    /// Returns the number of requested entries.
+    pub async fn prefetch(&mut self, prefetcher: &Prefetcher) -> Result<usize, T::Error> {
+        prefetcher.warm(&mut self.cache, self.db.inner()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eip2930::AccessListItem;
+    use core::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use database_interface::Database;
+    use primitives::{StorageValue, KECCAK_EMPTY};
+    use state::{AccountInfo, Bytecode};
+
+    const ACCOUNT: Address = Address::with_last_byte(1);
+    const MISSING: Address = Address::with_last_byte(2);
+    const CODE_HASH: B256 = B256::with_last_byte(3);
+
+    /// In-process provider that tracks the number of concurrent requests.
+    #[derive(Debug, Default)]
+    struct MockProvider {
+        max_concurrent_requests: usize,
+        requests: AtomicUsize,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl MockProvider {
+        async fn request(&self) {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl DatabaseAsyncRef for MockProvider {
+        type Error = Infallible;
+
+        fn max_concurrent_requests(&self) -> usize {
+            self.max_concurrent_requests
+        }
+
+        async fn basic_async_ref(
+            &self,
+            address: Address,
+        ) -> Result<Option<AccountInfo>, Self::Error> {
+            self.request().await;
+            Ok((address != MISSING).then(|| AccountInfo::from_balance(U256::from(10))))
+        }
+
+        async fn code_by_hash_async_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.request().await;
+            Ok(Bytecode::default())
+        }
+
+        async fn storage_async_ref(
+            &self,
+            _address: Address,
+            index: StorageKey,
+        ) -> Result<StorageValue, Self::Error> {
+            self.request().await;
+            Ok(index + U256::from(1))
+        }
+
+        async fn block_hash_async_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            self.request().await;
+            Ok(B256::with_last_byte(number as u8))
+        }
+    }
+
+    fn cache_db() -> CacheDB<WrapDatabaseAsync<MockProvider>> {
+        cache_db_with_limit(16)
+    }
+
+    fn cache_db_with_limit(
+        max_concurrent_requests: usize,
+    ) -> CacheDB<WrapDatabaseAsync<MockProvider>> {
+        let provider = MockProvider {
+            max_concurrent_requests,
+            ..Default::default()
+        };
+        CacheDB::new(WrapDatabaseAsync::new(provider).unwrap())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prefetch_access_list_concurrently() {
+        let access_list = AccessList(vec![
+            AccessListItem {
+                address: ACCOUNT,
+                storage_keys: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+            },
+            AccessListItem {
+                address: MISSING,
+                storage_keys: vec![],
+            },
+        ]);
+        let mut db = cache_db();
+        let loaded = db.prefetch(&Prefetcher::from(&access_list)).await.unwrap();
+        assert_eq!(loaded, 4);
+
+        // The accounts and the storage slots are requested in two concurrent batches.
+        let provider = db.db.inner();
+        assert_eq!(provider.requests.load(Ordering::SeqCst), 4);
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 2);
+
+        // Reads are served from the cache.
+        assert_eq!(
+            db.basic(ACCOUNT).unwrap(),
+            Some(AccountInfo::from_balance(U256::from(10)))
+        );
+        assert_eq!(db.basic(MISSING).unwrap(), None);
+        assert_eq!(db.storage(ACCOUNT, U256::from(2)).unwrap(), U256::from(3));
+        assert_eq!(db.db.inner().requests.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prefetch_bounded_concurrency() {
+        let mut db = cache_db_with_limit(2);
+        let prefetcher = (0..10).fold(Prefetcher::new(), |prefetcher, index| {
+            prefetcher.with_storage(ACCOUNT, U256::from(index))
+        });
+        assert_eq!(db.prefetch(&prefetcher).await.unwrap(), 11);
+
+        let provider = db.db.inner();
+        assert_eq!(provider.requests.load(Ordering::SeqCst), 11);
+        assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), 2);
+        assert_eq!(db.storage(ACCOUNT, U256::from(9)).unwrap(), U256::from(10));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn prefetch_skips_cached_entries() {
+        let mut db = cache_db();
+        db.insert_account_info(ACCOUNT, AccountInfo::from_balance(U256::from(1)));
+        db.cache
+            .accounts
+            .get_mut(&ACCOUNT)
+            .unwrap()
+            .storage
+            .insert(U256::from(1), U256::from(100));
+
+        let mut witness = ExecutionWitness::default();
+        witness.accounts.insert(ACCOUNT, None);
+        witness.storage.insert(
+            ACCOUNT,
+            [(U256::from(1), U256::ZERO), (U256::from(5), U256::ZERO)]
+                .into_iter()
+                .collect(),
+        );
+        // The empty code is always cached.
+        witness.codes.insert(KECCAK_EMPTY, Bytecode::default());
+        witness.codes.insert(CODE_HASH, Bytecode::default());
+        witness.block_hashes.insert(7, B256::ZERO);
+        let prefetcher = Prefetcher::from(&witness).with_storage(MISSING, U256::from(1));
+        let loaded = db.prefetch(&prefetcher).await.unwrap();
+        // Account of the missing slot, slot 5, the missing slot, the code and the block hash.
+        assert_eq!(loaded, 5);
+
+        assert_eq!(
+            db.basic(ACCOUNT).unwrap(),
+            Some(AccountInfo::from_balance(U256::from(1)))
+        );
+        assert_eq!(db.storage(ACCOUNT, U256::from(1)).unwrap(), U256::from(100));
+        assert_eq!(db.storage(ACCOUNT, U256::from(5)).unwrap(), U256::from(6));
+        assert_eq!(db.storage(MISSING, U256::from(1)).unwrap(), U256::ZERO);
+        assert_eq!(db.code_by_hash(CODE_HASH).unwrap(), Bytecode::default());
+        assert_eq!(db.block_hash(7).unwrap(), B256::with_last_byte(7));
+        assert_eq!(db.db.inner().requests.load(Ordering::SeqCst), 5);
+
+        // Everything is cached now.
+        assert_eq!(db.prefetch(&prefetcher).await.unwrap(), 0);
+    }
+}
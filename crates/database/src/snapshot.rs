@@ -0,0 +1,160 @@
+//! Snapshot ids with copy-on-write undo layers.
+//!
+//! Taking a snapshot pushes an empty layer. Before an entry is modified for the first time
+//! after the snapshot, its previous value is saved in the top layer. Accounts and their storage
+//! slots are saved separately, so writing a slot does not copy the whole account storage.
+//! Reverting to a snapshot restores the saved values of all layers down to and including the
+//! snapshot layer.
+use core::{fmt, hash::Hash};
+use primitives::{Address, HashMap, StorageKey, StorageValue};
+use std::vec::Vec;
+
+/// Identifier of a snapshot returned by `snapshot()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotId(pub u64);
+
+impl fmt::Display for SnapshotId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Stack of snapshot layers.
+///
+/// Ids are increasing and never reused, reverting to a snapshot invalidates it and all newer
+/// snapshots.
+#[derive(Clone, Debug, Default)]
+pub struct Snapshots<L> {
+    next_id: u64,
+    layers: Vec<(SnapshotId, L)>,
+}
+
+impl<L> Snapshots<L> {
+    /// Pushes a new layer and returns its id.
+    pub fn push(&mut self, layer: L) -> SnapshotId {
+        let id = SnapshotId(self.next_id);
+        self.next_id += 1;
+        self.layers.push((id, layer));
+        id
+    }
+
+    /// Returns the layer of the latest snapshot that records modifications.
+    #[inline]
+    pub fn top_mut(&mut self) -> Option<&mut L> {
+        self.layers.last_mut().map(|(_, layer)| layer)
+    }
+
+    /// Returns `true` if the snapshot is still valid.
+    pub fn contains(&self, id: SnapshotId) -> bool {
+        self.layers.iter().any(|(layer_id, _)| *layer_id == id)
+    }
+
+    /// Returns the number of valid snapshots.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if there are no valid snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Removes the layers of the snapshot and all newer snapshots.
+    ///
+    /// Returns the removed layers ordered from newest to oldest, or `None` if the
+    /// snapshot is not valid.
+    pub fn pop_to(&mut self, id: SnapshotId) -> Option<impl Iterator<Item = L> + '_> {
+        let index = self
+            .layers
+            .iter()
+            .position(|(layer_id, _)| *layer_id == id)?;
+        Some(self.layers.drain(index..).rev().map(|(_, layer)| layer))
+    }
+
+    /// Invalidates all snapshots.
+    pub fn clear(&mut self) {
+        self.layers.clear();
+    }
+}
+
+/// Saves the current value of `key` in `saved` if it was not saved before.
+///
+/// `None` is saved if the key is not present in `map`.
+#[inline]
+pub(crate) fn save_entry<K: Hash + Eq + Clone, V: Clone>(
+    saved: &mut HashMap<K, Option<V>>,
+    map: &HashMap<K, V>,
+    key: &K,
+) {
+    if !saved.contains_key(key) {
+        saved.insert(key.clone(), map.get(key).cloned());
+    }
+}
+
+/// Saves the current value of the storage slot in `saved` if it was not saved before.
+///
+/// `None` is saved if the slot is not present in `storage`.
+#[inline]
+pub(crate) fn save_slot(
+    saved: &mut HashMap<(Address, StorageKey), Option<StorageValue>>,
+    storage: Option<&HashMap<StorageKey, StorageValue>>,
+    address: Address,
+    key: StorageKey,
+) {
+    saved
+        .entry((address, key))
+        .or_insert_with(|| storage.and_then(|storage| storage.get(&key).copied()));
+}
+
+/// Restores the saved values into `map`, removing the keys that were not present.
+pub(crate) fn restore_entries<K: Hash + Eq, V>(
+    saved: HashMap<K, Option<V>>,
+    map: &mut HashMap<K, V>,
+) {
+    for (key, value) in saved {
+        match value {
+            Some(value) => map.insert(key, value),
+            None => map.remove(&key),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revert_invalidates_newer_snapshots() {
+        let mut snapshots = Snapshots::default();
+        let first = snapshots.push(1);
+        let second = snapshots.push(2);
+        let third = snapshots.push(3);
+        assert_eq!(snapshots.len(), 3);
+
+        let popped: Vec<_> = snapshots.pop_to(second).unwrap().collect();
+        assert_eq!(popped, [3, 2]);
+        assert!(snapshots.contains(first));
+        assert!(!snapshots.contains(second));
+        assert!(!snapshots.contains(third));
+        assert!(snapshots.pop_to(third).is_none());
+
+        // Ids are not reused.
+        assert_eq!(snapshots.push(4), SnapshotId(3));
+    }
+
+    #[test]
+    fn save_and_restore_entries() {
+        let mut map = HashMap::from_iter([(1, "a"), (2, "b")]);
+        let mut saved = HashMap::default();
+        save_entry(&mut saved, &map, &1);
+        map.insert(1, "c");
+        save_entry(&mut saved, &map, &1);
+        save_entry(&mut saved, &map, &3);
+        map.insert(3, "d");
+        map.insert(2, "e");
+
+        restore_entries(saved, &mut map);
+        assert_eq!(map, HashMap::from_iter([(1, "a"), (2, "e")]));
+    }
+}
@@ -0,0 +1,839 @@
+//! Compact binary file format for [`CacheDB`], [`BundleState`] and [`ExecutionWitness`].
+//!
+//! A file starts with the [`STATE_FILE_MAGIC`] bytes, the format version and the kind of the
+//! stored state, followed by a stream of tagged records terminated by an end tag. Records are
+//! read one at a time with [`StateFileReader`] so a file can be loaded without decoding it into
+//! memory first.
+//!
+//! Integers are stored as LEB128 varints and 256-bit values as a length byte followed by the
+//! minimal big-endian bytes. Entries are written sorted so the same state always produces the
+//! same file.
+use crate::{
+    states::{
+        reverts::AccountInfoRevert, AccountRevert, AccountStatus, BundleAccount, BundleState,
+        RevertToSlot, StorageSlot,
+    },
+    AccountState, CacheDB, DbAccount, ExecutionWitness,
+};
+use bytecode::Bytecode;
+use primitives::{Address, Bytes, HashMap, StorageKey, StorageValue, B256, KECCAK_EMPTY, U256};
+use state::AccountInfo;
+use std::{
+    io::{self, Read, Write},
+    vec::Vec,
+};
+
+/// Magic bytes at the start of a state file.
+pub const STATE_FILE_MAGIC: [u8; 8] = *b"REVMSTAT";
+/// Current version of the state file format.
+pub const STATE_FILE_VERSION: u8 = 1;
+
+const TAG_END: u8 = 0;
+const TAG_ACCOUNT: u8 = 1;
+const TAG_CONTRACT: u8 = 2;
+const TAG_BLOCK_HASH: u8 = 3;
+const TAG_BUNDLE_ACCOUNT: u8 = 4;
+const TAG_REVERTS: u8 = 5;
+const TAG_WITNESS_ACCOUNT: u8 = 6;
+const TAG_STORAGE: u8 = 7;
+
+/// Kind of state stored in the file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StateFileKind {
+    /// Accounts, storage, contracts and block hashes of a [`CacheDB`].
+    CacheDB = 0,
+    /// State, contracts and reverts of a [`BundleState`].
+    BundleState = 1,
+    /// Accounts, storage, codes and block hashes of an [`ExecutionWitness`].
+    ExecutionWitness = 2,
+}
+
+impl TryFrom<u8> for StateFileKind {
+    type Error = io::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::CacheDB),
+            1 => Ok(Self::BundleState),
+            2 => Ok(Self::ExecutionWitness),
+            _ => Err(invalid_data("unknown state file kind")),
+        }
+    }
+}
+
+/// Record of a state file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateFileRecord {
+    /// [`CacheDB`] account with its storage.
+    Account(Address, DbAccount),
+    /// Contract bytecode by code hash.
+    Contract(B256, Bytecode),
+    /// Block hash by block number.
+    BlockHash(U256, B256),
+    /// [`BundleState`] account.
+    BundleAccount(Address, BundleAccount),
+    /// [`BundleState`] reverts of one block.
+    Reverts(Vec<(Address, AccountRevert)>),
+    /// [`ExecutionWitness`] account, `None` if the account does not exist.
+    WitnessAccount(Address, Option<AccountInfo>),
+    /// [`ExecutionWitness`] storage slots of an account.
+    Storage(Address, HashMap<StorageKey, StorageValue>),
+}
+
+impl<ExtDB> CacheDB<ExtDB> {
+    /// Writes the accounts, storage, contracts and block hashes of the cache to a state file.
+    ///
+    /// Logs and snapshots are not written.
+    pub fn write_state_file<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = StateFileWriter::new(writer, StateFileKind::CacheDB)?;
+        for (code_hash, code) in sorted(&self.cache.contracts) {
+            writer.write_record(&StateFileRecord::Contract(*code_hash, code.clone()))?;
+        }
+        for (address, account) in sorted(&self.cache.accounts) {
+            writer.write_account(address, account)?;
+        }
+        for (number, hash) in sorted(&self.cache.block_hashes) {
+            writer.write_record(&StateFileRecord::BlockHash(*number, *hash))?;
+        }
+        writer.finish()
+    }
+
+    /// Loads a state file written by [`CacheDB::write_state_file`] into the cache.
+    ///
+    /// Records are inserted as they are read, overriding the cached entries.
+    pub fn load_state_file<R: Read>(&mut self, reader: R) -> io::Result<()> {
+        let mut reader = StateFileReader::new(reader)?;
+        if reader.kind() != StateFileKind::CacheDB {
+            return Err(invalid_data("state file does not contain a cache"));
+        }
+        while let Some(record) = reader.read_record()? {
+            match record {
+                StateFileRecord::Account(address, mut account) => {
+                    // Contracts are written before accounts.
+                    if account.info.code_hash != KECCAK_EMPTY {
+                        account.info.code =
+                            self.cache.contracts.get(&account.info.code_hash).cloned();
+                    }
+                    self.cache.accounts.insert(address, account);
+                }
+                StateFileRecord::Contract(code_hash, code) => {
+                    self.cache.contracts.insert(code_hash, code);
+                }
+                StateFileRecord::BlockHash(number, hash) => {
+                    self.cache.block_hashes.insert(number, hash);
+                }
+                _ => return Err(invalid_data("unexpected record in cache state file")),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BundleState {
+    /// Writes the state, contracts and reverts of the bundle to a state file.
+    pub fn write_state_file<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = StateFileWriter::new(writer, StateFileKind::BundleState)?;
+        for (code_hash, code) in sorted(&self.contracts) {
+            writer.write_record(&StateFileRecord::Contract(*code_hash, code.clone()))?;
+        }
+        for (address, account) in sorted(&self.state) {
+            writer.write_bundle_account(address, account)?;
+        }
+        for reverts in self.reverts.iter() {
+            let mut reverts = reverts.iter().collect::<Vec<_>>();
+            reverts.sort_unstable_by_key(|(address, _)| *address);
+            writer.write_reverts(&reverts)?;
+        }
+        writer.finish()
+    }
+
+    /// Reads a bundle from a state file written by [`BundleState::write_state_file`].
+    ///
+    /// The bundle is built while records are read, it can be passed to
+    /// [`StateBuilder::with_bundle_prestate`][crate::StateBuilder::with_bundle_prestate].
+    pub fn read_state_file<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = StateFileReader::new(reader)?;
+        if reader.kind() != StateFileKind::BundleState {
+            return Err(invalid_data("state file does not contain a bundle"));
+        }
+        let mut bundle = BundleState::default();
+        while let Some(record) = reader.read_record()? {
+            match record {
+                StateFileRecord::Contract(code_hash, code) => {
+                    bundle.contracts.insert(code_hash, code);
+                }
+                StateFileRecord::BundleAccount(address, mut account) => {
+                    // Code is stored once in contracts.
+                    for info in [&mut account.info, &mut account.original_info]
+                        .into_iter()
+                        .flatten()
+                    {
+                        info.code = bundle.contracts.get(&info.code_hash).cloned();
+                    }
+                    bundle.state_size += account.size_hint();
+                    bundle.state.insert(address, account);
+                }
+                StateFileRecord::Reverts(reverts) => {
+                    bundle.reverts_size += reverts
+                        .iter()
+                        .map(|(_, revert)| revert.size_hint())
+                        .sum::<usize>();
+                    bundle.reverts.push(reverts);
+                }
+                _ => return Err(invalid_data("unexpected record in bundle state file")),
+            }
+        }
+        Ok(bundle)
+    }
+}
+
+impl ExecutionWitness {
+    /// Writes the witness to a state file.
+    pub fn write_state_file<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = StateFileWriter::new(writer, StateFileKind::ExecutionWitness)?;
+        for (code_hash, code) in sorted(&self.codes) {
+            writer.write_record(&StateFileRecord::Contract(*code_hash, code.clone()))?;
+        }
+        for (address, info) in sorted(&self.accounts) {
+            writer.write_record(&StateFileRecord::WitnessAccount(*address, info.clone()))?;
+        }
+        for (address, storage) in sorted(&self.storage) {
+            writer.write_storage(address, storage)?;
+        }
+        for (number, hash) in sorted(&self.block_hashes) {
+            writer.write_record(&StateFileRecord::BlockHash(U256::from(*number), *hash))?;
+        }
+        writer.finish()
+    }
+
+    /// Reads a witness from a state file written by [`ExecutionWitness::write_state_file`].
+    pub fn read_state_file<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = StateFileReader::new(reader)?;
+        if reader.kind() != StateFileKind::ExecutionWitness {
+            return Err(invalid_data("state file does not contain a witness"));
+        }
+        let mut witness = ExecutionWitness::default();
+        while let Some(record) = reader.read_record()? {
+            match record {
+                StateFileRecord::Contract(code_hash, code) => {
+                    witness.codes.insert(code_hash, code);
+                }
+                StateFileRecord::WitnessAccount(address, info) => {
+                    witness.accounts.insert(address, info);
+                }
+                StateFileRecord::Storage(address, storage) => {
+                    witness.storage.insert(address, storage);
+                }
+                StateFileRecord::BlockHash(number, hash) => {
+                    let number = number
+                        .try_into()
+                        .map_err(|_| invalid_data("block number overflow"))?;
+                    witness.block_hashes.insert(number, hash);
+                }
+                _ => return Err(invalid_data("unexpected record in witness state file")),
+            }
+        }
+        Ok(witness)
+    }
+}
+
+/// Writer of a state file.
+///
+/// [`StateFileWriter::finish`] must be called to write the end tag.
+#[derive(Debug)]
+pub struct StateFileWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> StateFileWriter<W> {
+    /// Writes the file header and returns the writer.
+    pub fn new(mut writer: W, kind: StateFileKind) -> io::Result<Self> {
+        writer.write_all(&STATE_FILE_MAGIC)?;
+        writer.write_all(&[STATE_FILE_VERSION, kind as u8])?;
+        Ok(Self { writer })
+    }
+
+    /// Writes a single record.
+    pub fn write_record(&mut self, record: &StateFileRecord) -> io::Result<()> {
+        match record {
+            StateFileRecord::Account(address, account) => self.write_account(address, account),
+            StateFileRecord::Contract(code_hash, code) => {
+                self.write_u8(TAG_CONTRACT)?;
+                self.writer.write_all(code_hash.as_slice())?;
+                self.write_bytes(&code.original_bytes())
+            }
+            StateFileRecord::BlockHash(number, hash) => {
+                self.write_u8(TAG_BLOCK_HASH)?;
+                self.write_u256(*number)?;
+                self.writer.write_all(hash.as_slice())
+            }
+            StateFileRecord::BundleAccount(address, account) => {
+                self.write_bundle_account(address, account)
+            }
+            StateFileRecord::Reverts(reverts) => {
+                self.write_reverts(&reverts.iter().collect::<Vec<_>>())
+            }
+            StateFileRecord::WitnessAccount(address, info) => {
+                self.write_u8(TAG_WITNESS_ACCOUNT)?;
+                self.writer.write_all(address.as_slice())?;
+                self.write_optional_info(info.as_ref())
+            }
+            StateFileRecord::Storage(address, storage) => self.write_storage(address, storage),
+        }
+    }
+
+    /// Writes the end tag and flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.write_u8(TAG_END)?;
+        self.writer.flush()
+    }
+
+    fn write_account(&mut self, address: &Address, account: &DbAccount) -> io::Result<()> {
+        self.write_u8(TAG_ACCOUNT)?;
+        self.writer.write_all(address.as_slice())?;
+        self.write_u8(account_state_to_u8(&account.account_state))?;
+        self.write_info(&account.info)?;
+        self.write_varint(account.storage.len() as u64)?;
+        for (slot, value) in sorted(&account.storage) {
+            self.write_u256(*slot)?;
+            self.write_u256(*value)?;
+        }
+        Ok(())
+    }
+
+    fn write_storage(
+        &mut self,
+        address: &Address,
+        storage: &HashMap<StorageKey, StorageValue>,
+    ) -> io::Result<()> {
+        self.write_u8(TAG_STORAGE)?;
+        self.writer.write_all(address.as_slice())?;
+        self.write_varint(storage.len() as u64)?;
+        for (slot, value) in sorted(storage) {
+            self.write_u256(*slot)?;
+            self.write_u256(*value)?;
+        }
+        Ok(())
+    }
+
+    fn write_bundle_account(
+        &mut self,
+        address: &Address,
+        account: &BundleAccount,
+    ) -> io::Result<()> {
+        self.write_u8(TAG_BUNDLE_ACCOUNT)?;
+        self.writer.write_all(address.as_slice())?;
+        self.write_u8(account_status_to_u8(account.status))?;
+        self.write_optional_info(account.info.as_ref())?;
+        self.write_optional_info(account.original_info.as_ref())?;
+        self.write_varint(account.storage.len() as u64)?;
+        for (slot, value) in sorted(&account.storage) {
+            self.write_u256(*slot)?;
+            self.write_u256(value.previous_or_original_value)?;
+            self.write_u256(value.present_value)?;
+        }
+        Ok(())
+    }
+
+    fn write_reverts(&mut self, reverts: &[&(Address, AccountRevert)]) -> io::Result<()> {
+        self.write_u8(TAG_REVERTS)?;
+        self.write_varint(reverts.len() as u64)?;
+        for (address, revert) in reverts {
+            self.writer.write_all(address.as_slice())?;
+            match &revert.account {
+                AccountInfoRevert::DoNothing => self.write_u8(0)?,
+                AccountInfoRevert::DeleteIt => self.write_u8(1)?,
+                AccountInfoRevert::RevertTo(info) => {
+                    self.write_u8(2)?;
+                    self.write_info(info)?;
+                }
+            }
+            self.write_u8(account_status_to_u8(revert.previous_status))?;
+            self.write_u8(revert.wipe_storage as u8)?;
+            self.write_varint(revert.storage.len() as u64)?;
+            for (slot, value) in sorted(&revert.storage) {
+                self.write_u256(*slot)?;
+                match value {
+                    RevertToSlot::Some(value) => {
+                        self.write_u8(0)?;
+                        self.write_u256(*value)?;
+                    }
+                    RevertToSlot::Destroyed => self.write_u8(1)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_info(&mut self, info: &AccountInfo) -> io::Result<()> {
+        self.write_u256(info.balance)?;
+        self.write_varint(info.nonce)?;
+        self.writer.write_all(info.code_hash.as_slice())
+    }
+
+    fn write_optional_info(&mut self, info: Option<&AccountInfo>) -> io::Result<()> {
+        match info {
+            Some(info) => {
+                self.write_u8(1)?;
+                self.write_info(info)
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) -> io::Result<()> {
+        self.writer.write_all(&[value])
+    }
+
+    fn write_varint(&mut self, mut value: u64) -> io::Result<()> {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                return self.write_u8(byte);
+            }
+            self.write_u8(byte | 0x80)?;
+        }
+    }
+
+    fn write_u256(&mut self, value: U256) -> io::Result<()> {
+        let bytes = value.to_be_bytes::<32>();
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(32);
+        self.write_u8((32 - start) as u8)?;
+        self.writer.write_all(&bytes[start..])
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.write_varint(bytes.len() as u64)?;
+        self.writer.write_all(bytes)
+    }
+}
+
+/// Streaming reader of a state file.
+///
+/// Reading a record only decodes that record, wrap the reader in a
+/// [`BufReader`][std::io::BufReader] when reading from a file.
+#[derive(Debug)]
+pub struct StateFileReader<R> {
+    reader: R,
+    kind: StateFileKind,
+    finished: bool,
+}
+
+impl<R: Read> StateFileReader<R> {
+    /// Reads and validates the file header.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != STATE_FILE_MAGIC {
+            return Err(invalid_data("not a state file"));
+        }
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header)?;
+        if header[0] != STATE_FILE_VERSION {
+            return Err(invalid_data("unsupported state file version"));
+        }
+        Ok(Self {
+            reader,
+            kind: StateFileKind::try_from(header[1])?,
+            finished: false,
+        })
+    }
+
+    /// Returns the kind of state stored in the file.
+    pub fn kind(&self) -> StateFileKind {
+        self.kind
+    }
+
+    /// Reads the next record, returns `None` after the end tag.
+    pub fn read_record(&mut self) -> io::Result<Option<StateFileRecord>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let record = match self.read_u8()? {
+            TAG_END => {
+                self.finished = true;
+                return Ok(None);
+            }
+            TAG_ACCOUNT => {
+                let address = self.read_address()?;
+                let account_state = account_state_from_u8(self.read_u8()?)?;
+                let info = self.read_info()?;
+                let len = self.read_varint()?;
+                let mut storage = HashMap::default();
+                for _ in 0..len {
+                    storage.insert(self.read_u256()?, self.read_u256()?);
+                }
+                StateFileRecord::Account(
+                    address,
+                    DbAccount {
+                        info,
+                        account_state,
+                        storage,
+                    },
+                )
+            }
+            TAG_CONTRACT => {
+                let code_hash = self.read_b256()?;
+                let bytes = self.read_bytes()?;
+                let code = Bytecode::new_raw_checked(bytes)
+                    .map_err(|_| invalid_data("invalid bytecode"))?;
+                StateFileRecord::Contract(code_hash, code)
+            }
+            TAG_BLOCK_HASH => StateFileRecord::BlockHash(self.read_u256()?, self.read_b256()?),
+            TAG_BUNDLE_ACCOUNT => {
+                let address = self.read_address()?;
+                let status = account_status_from_u8(self.read_u8()?)?;
+                let info = self.read_optional_info()?;
+                let original_info = self.read_optional_info()?;
+                let len = self.read_varint()?;
+                let mut storage = HashMap::default();
+                for _ in 0..len {
+                    let slot = self.read_u256()?;
+                    let slot_value = StorageSlot {
+                        previous_or_original_value: self.read_u256()?,
+                        present_value: self.read_u256()?,
+                    };
+                    storage.insert(slot, slot_value);
+                }
+                StateFileRecord::BundleAccount(
+                    address,
+                    BundleAccount::new(original_info, info, storage, status),
+                )
+            }
+            TAG_REVERTS => {
+                let len = self.read_varint()?;
+                let mut reverts = Vec::new();
+                for _ in 0..len {
+                    let address = self.read_address()?;
+                    let account = match self.read_u8()? {
+                        0 => AccountInfoRevert::DoNothing,
+                        1 => AccountInfoRevert::DeleteIt,
+                        2 => AccountInfoRevert::RevertTo(self.read_info()?),
+                        _ => return Err(invalid_data("unknown account revert")),
+                    };
+                    let previous_status = account_status_from_u8(self.read_u8()?)?;
+                    let wipe_storage = self.read_bool()?;
+                    let storage_len = self.read_varint()?;
+                    let mut storage = HashMap::default();
+                    for _ in 0..storage_len {
+                        let slot: StorageKey = self.read_u256()?;
+                        let value = match self.read_u8()? {
+                            0 => RevertToSlot::Some(self.read_u256()?),
+                            1 => RevertToSlot::Destroyed,
+                            _ => return Err(invalid_data("unknown storage revert")),
+                        };
+                        storage.insert(slot, value);
+                    }
+                    reverts.push((
+                        address,
+                        AccountRevert {
+                            account,
+                            storage,
+                            previous_status,
+                            wipe_storage,
+                        },
+                    ));
+                }
+                StateFileRecord::Reverts(reverts)
+            }
+            TAG_WITNESS_ACCOUNT => {
+                StateFileRecord::WitnessAccount(self.read_address()?, self.read_optional_info()?)
+            }
+            TAG_STORAGE => {
+                let address = self.read_address()?;
+                let len = self.read_varint()?;
+                let mut storage = HashMap::default();
+                for _ in 0..len {
+                    storage.insert(self.read_u256()?, self.read_u256()?);
+                }
+                StateFileRecord::Storage(address, storage)
+            }
+            _ => return Err(invalid_data("unknown state file record")),
+        };
+        Ok(Some(record))
+    }
+
+    fn read_info(&mut self) -> io::Result<AccountInfo> {
+        Ok(AccountInfo {
+            balance: self.read_u256()?,
+            nonce: self.read_varint()?,
+            code_hash: self.read_b256()?,
+            code: None,
+        })
+    }
+
+    fn read_optional_info(&mut self) -> io::Result<Option<AccountInfo>> {
+        if self.read_bool()? {
+            self.read_info().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8; 1];
+        self.reader.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_bool(&mut self) -> io::Result<bool> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid_data("invalid boolean")),
+        }
+    }
+
+    fn read_varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_data("varint overflow"))
+    }
+
+    fn read_u256(&mut self) -> io::Result<U256> {
+        let len = self.read_u8()? as usize;
+        if len > 32 {
+            return Err(invalid_data("invalid 256-bit value length"));
+        }
+        let mut bytes = [0u8; 32];
+        self.reader.read_exact(&mut bytes[32 - len..])?;
+        Ok(U256::from_be_bytes(bytes))
+    }
+
+    fn read_b256(&mut self) -> io::Result<B256> {
+        let mut bytes = B256::ZERO;
+        self.reader.read_exact(bytes.as_mut_slice())?;
+        Ok(bytes)
+    }
+
+    fn read_address(&mut self) -> io::Result<Address> {
+        let mut address = Address::ZERO;
+        self.reader.read_exact(address.as_mut_slice())?;
+        Ok(address)
+    }
+
+    fn read_bytes(&mut self) -> io::Result<Bytes> {
+        let len = self.read_varint()? as usize;
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(bytes.into())
+    }
+}
+
+impl<R: Read> Iterator for StateFileReader<R> {
+    type Item = io::Result<StateFileRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.read_record();
+        if record.is_err() {
+            // Stop after the first error.
+            self.finished = true;
+        }
+        record.transpose()
+    }
+}
+
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    entries.sort_unstable_by_key(|(key, _)| *key);
+    entries
+}
+
+fn invalid_data(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn account_state_to_u8(state: &AccountState) -> u8 {
+    match state {
+        AccountState::NotExisting => 0,
+        AccountState::Touched => 1,
+        AccountState::StorageCleared => 2,
+        AccountState::None => 3,
+    }
+}
+
+fn account_state_from_u8(value: u8) -> io::Result<AccountState> {
+    Ok(match value {
+        0 => AccountState::NotExisting,
+        1 => AccountState::Touched,
+        2 => AccountState::StorageCleared,
+        3 => AccountState::None,
+        _ => return Err(invalid_data("unknown account state")),
+    })
+}
+
+fn account_status_to_u8(status: AccountStatus) -> u8 {
+    match status {
+        AccountStatus::LoadedNotExisting => 0,
+        AccountStatus::Loaded => 1,
+        AccountStatus::LoadedEmptyEIP161 => 2,
+        AccountStatus::InMemoryChange => 3,
+        AccountStatus::Changed => 4,
+        AccountStatus::Destroyed => 5,
+        AccountStatus::DestroyedChanged => 6,
+        AccountStatus::DestroyedAgain => 7,
+    }
+}
+
+fn account_status_from_u8(value: u8) -> io::Result<AccountStatus> {
+    Ok(match value {
+        0 => AccountStatus::LoadedNotExisting,
+        1 => AccountStatus::Loaded,
+        2 => AccountStatus::LoadedEmptyEIP161,
+        3 => AccountStatus::InMemoryChange,
+        4 => AccountStatus::Changed,
+        5 => AccountStatus::Destroyed,
+        6 => AccountStatus::DestroyedChanged,
+        7 => AccountStatus::DestroyedAgain,
+        _ => return Err(invalid_data("unknown account status")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EmptyDB;
+    use primitives::{address, b256, bytes};
+
+    fn cache_db() -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = Bytecode::new_raw(bytes!("6001600055"));
+        db.insert_account_info(
+            address!("0x1000000000000000000000000000000000000001"),
+            AccountInfo::new(U256::from(1_000_000), 7, code.hash_slow(), code),
+        );
+        db.insert_account_storage(
+            address!("0x1000000000000000000000000000000000000001"),
+            U256::from(1),
+            U256::MAX,
+        )
+        .unwrap();
+        db.insert_account_info(
+            address!("0x2000000000000000000000000000000000000002"),
+            AccountInfo::from_balance(U256::from(5)),
+        );
+        db.cache.accounts.insert(
+            address!("0x3000000000000000000000000000000000000003"),
+            DbAccount::new_not_existing(),
+        );
+        db.insert_block_hash(
+            U256::from(42),
+            b256!("0x4200000000000000000000000000000000000000000000000000000000000042"),
+        );
+        db
+    }
+
+    #[test]
+    fn cache_db_roundtrip() {
+        let db = cache_db();
+        let mut file = Vec::new();
+        db.write_state_file(&mut file).unwrap();
+
+        let mut loaded = CacheDB::new(EmptyDB::default());
+        loaded.load_state_file(file.as_slice()).unwrap();
+        assert_eq!(loaded.cache.accounts, db.cache.accounts);
+        assert_eq!(loaded.cache.contracts, db.cache.contracts);
+        assert_eq!(loaded.cache.block_hashes, db.cache.block_hashes);
+        let info =
+            &loaded.cache.accounts[&address!("0x1000000000000000000000000000000000000001")].info;
+        assert_eq!(
+            info.code,
+            db.cache.accounts[&address!("0x1000000000000000000000000000000000000001")]
+                .info
+                .code
+        );
+
+        // Output is deterministic.
+        let mut second = Vec::new();
+        loaded.write_state_file(&mut second).unwrap();
+        assert_eq!(file, second);
+    }
+
+    #[test]
+    fn bundle_state_roundtrip() {
+        let address = address!("0x1000000000000000000000000000000000000001");
+        let code = Bytecode::new_raw(bytes!("6001600055"));
+        let bundle = BundleState::builder(0..=1)
+            .state_present_account_info(
+                address,
+                AccountInfo::new(U256::from(10), 1, code.hash_slow(), code.clone()),
+            )
+            .state_original_account_info(address, AccountInfo::from_balance(U256::from(3)))
+            .state_storage(
+                address,
+                HashMap::from_iter([(U256::from(1), (U256::ZERO, U256::from(2)))]),
+            )
+            .revert_address(0, address)
+            .revert_account_info(0, address, Some(None))
+            .revert_address(1, address)
+            .revert_account_info(1, address, Some(Some(AccountInfo::default())))
+            .revert_storage(1, address, vec![(U256::from(1), U256::from(5))])
+            .contract(code.hash_slow(), code)
+            .build();
+
+        let mut file = Vec::new();
+        bundle.write_state_file(&mut file).unwrap();
+        let loaded = BundleState::read_state_file(file.as_slice()).unwrap();
+        assert_eq!(loaded, bundle);
+        assert_eq!(loaded.state_size, bundle.state_size);
+        assert_eq!(loaded.reverts_size, bundle.reverts_size);
+        assert!(loaded.state[&address].info.as_ref().unwrap().code.is_some());
+    }
+
+    #[test]
+    fn reader_yields_records() {
+        let mut file = Vec::new();
+        cache_db().write_state_file(&mut file).unwrap();
+
+        let reader = StateFileReader::new(file.as_slice()).unwrap();
+        assert_eq!(reader.kind(), StateFileKind::CacheDB);
+        let records = reader.collect::<io::Result<Vec<_>>>().unwrap();
+        // Two default contracts, the inserted contract, three accounts and one block hash.
+        assert_eq!(records.len(), 7);
+        assert!(matches!(
+            records[0],
+            StateFileRecord::Contract(code_hash, _) if code_hash == B256::ZERO
+        ));
+        assert!(records
+            .iter()
+            .any(|record| matches!(record, StateFileRecord::Contract(code_hash, _) if *code_hash == KECCAK_EMPTY)));
+    }
+
+    #[test]
+    fn invalid_header() {
+        let mut file = Vec::new();
+        cache_db().write_state_file(&mut file).unwrap();
+
+        let mut bad_magic = file.clone();
+        bad_magic[0] = b'X';
+        let err = StateFileReader::new(bad_magic.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_version = file.clone();
+        bad_version[STATE_FILE_MAGIC.len()] = STATE_FILE_VERSION + 1;
+        let err = StateFileReader::new(bad_version.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Cache file can't be read as a bundle.
+        let err = BundleState::read_state_file(file.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Truncated file.
+        let mut db = CacheDB::new(EmptyDB::default());
+        let err = db.load_state_file(&file[..file.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,316 @@
+//! `eth_call` state and block hash overrides on top of a database.
+use core::{error::Error, fmt};
+use database_interface::{Database, DatabaseRef};
+use primitives::{Address, HashMap, StorageKey, StorageValue, B256, U256};
+use state::{AccountInfo, Bytecode};
+
+/// Override of a single account.
+///
+/// Follows the semantics of the `eth_call` state override set: unset fields keep the value of
+/// the database, `state` replaces the whole storage and `state_diff` only the listed slots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountOverride {
+    /// Overrides the balance.
+    pub balance: Option<U256>,
+    /// Overrides the nonce.
+    pub nonce: Option<u64>,
+    /// Overrides the code, the code hash is computed from it.
+    pub code: Option<Bytecode>,
+    /// Replaces the storage, slots that are not listed are zero.
+    pub state: Option<HashMap<StorageKey, StorageValue>>,
+    /// Overrides the listed storage slots, other slots are read from the database.
+    pub state_diff: Option<HashMap<StorageKey, StorageValue>>,
+}
+
+/// Error returned when an override is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StateOverrideError {
+    /// Both `state` and `state_diff` are set for the account.
+    StateAndStateDiff(Address),
+}
+
+impl fmt::Display for StateOverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StateAndStateDiff(address) => {
+                write!(
+                    f,
+                    "account {address} has both state and state diff overrides"
+                )
+            }
+        }
+    }
+}
+
+impl Error for StateOverrideError {}
+
+/// Database layer that applies [`AccountOverride`]s and block hash overrides.
+///
+/// Accounts that do not exist in the inner database are created when they are overridden.
+#[derive(Clone, Debug, Default)]
+pub struct StateOverride<DB> {
+    /// Inner database.
+    pub db: DB,
+    accounts: HashMap<Address, AccountOverride>,
+    codes: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+impl<DB> StateOverride<DB> {
+    /// Creates a new layer without overrides.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: HashMap::default(),
+            codes: HashMap::default(),
+            block_hashes: HashMap::default(),
+        }
+    }
+
+    /// Creates a new layer with the given account overrides.
+    pub fn with_accounts(
+        db: DB,
+        overrides: impl IntoIterator<Item = (Address, AccountOverride)>,
+    ) -> Result<Self, StateOverrideError> {
+        let mut this = Self::new(db);
+        for (address, account) in overrides {
+            this.insert_account(address, account)?;
+        }
+        Ok(this)
+    }
+
+    /// Sets the override of the account, replacing the previous one.
+    pub fn insert_account(
+        &mut self,
+        address: Address,
+        account: AccountOverride,
+    ) -> Result<(), StateOverrideError> {
+        if account.state.is_some() && account.state_diff.is_some() {
+            return Err(StateOverrideError::StateAndStateDiff(address));
+        }
+        if let Some(code) = &account.code {
+            self.codes.insert(code.hash_slow(), code.clone());
+        }
+        self.accounts.insert(address, account);
+        Ok(())
+    }
+
+    /// Returns the override of the account.
+    pub fn account(&self, address: &Address) -> Option<&AccountOverride> {
+        self.accounts.get(address)
+    }
+
+    /// Overrides the hashes of the given block numbers.
+    pub fn with_block_hashes(
+        mut self,
+        block_hashes: impl IntoIterator<Item = (u64, B256)>,
+    ) -> Self {
+        self.block_hashes.extend(block_hashes);
+        self
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for StateOverride<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        let Some(account) = self.accounts.get(&address) else {
+            return Ok(info);
+        };
+        let mut info = info.unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code) = &account.code {
+            info.code_hash = code.hash_slow();
+            info.code = Some(code.clone());
+        }
+        Ok(Some(info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.codes.get(&code_hash) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            if let Some(state) = &account.state {
+                return Ok(state.get(&index).copied().unwrap_or_default());
+            }
+            if let Some(value) = account
+                .state_diff
+                .as_ref()
+                .and_then(|state_diff| state_diff.get(&index))
+            {
+                return Ok(*value);
+            }
+        }
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        match self.block_hashes.get(&number) {
+            Some(hash) => Ok(*hash),
+            None => self.db.block_hash_ref(number),
+        }
+    }
+}
+
+impl<DB: DatabaseRef> Database for StateOverride<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheDB, EmptyDB};
+    use primitives::{Bytes, KECCAK_EMPTY};
+
+    const ACCOUNT: Address = Address::with_last_byte(1);
+    const NEW_ACCOUNT: Address = Address::with_last_byte(2);
+
+    fn db() -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            ACCOUNT,
+            AccountInfo {
+                balance: U256::from(10),
+                nonce: 3,
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(ACCOUNT, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(ACCOUNT, U256::from(2), U256::from(2))
+            .unwrap();
+        db
+    }
+
+    #[test]
+    fn account_overrides() {
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let db = StateOverride::with_accounts(
+            db(),
+            [
+                (
+                    ACCOUNT,
+                    AccountOverride {
+                        balance: Some(U256::from(20)),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    NEW_ACCOUNT,
+                    AccountOverride {
+                        nonce: Some(7),
+                        code: Some(code.clone()),
+                        ..Default::default()
+                    },
+                ),
+            ],
+        )
+        .unwrap();
+
+        let info = db.basic_ref(ACCOUNT).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::from(20), 3));
+        assert_eq!(info.code_hash, KECCAK_EMPTY);
+
+        let info = db.basic_ref(NEW_ACCOUNT).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::ZERO, 7));
+        assert_eq!(info.code_hash, code.hash_slow());
+        assert_eq!(db.code_by_hash_ref(code.hash_slow()).unwrap(), code);
+
+        assert_eq!(db.basic_ref(Address::with_last_byte(3)).unwrap(), None);
+    }
+
+    #[test]
+    fn storage_overrides() {
+        let mut db = StateOverride::new(db());
+        let slots = HashMap::from_iter([(U256::from(1), U256::from(100))]);
+        db.insert_account(
+            ACCOUNT,
+            AccountOverride {
+                state_diff: Some(slots.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            db.storage_ref(ACCOUNT, U256::from(1)).unwrap(),
+            U256::from(100)
+        );
+        assert_eq!(
+            db.storage_ref(ACCOUNT, U256::from(2)).unwrap(),
+            U256::from(2)
+        );
+
+        db.insert_account(
+            ACCOUNT,
+            AccountOverride {
+                state: Some(slots.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            db.storage_ref(ACCOUNT, U256::from(1)).unwrap(),
+            U256::from(100)
+        );
+        assert_eq!(db.storage_ref(ACCOUNT, U256::from(2)).unwrap(), U256::ZERO);
+
+        assert_eq!(
+            db.insert_account(
+                ACCOUNT,
+                AccountOverride {
+                    state: Some(slots.clone()),
+                    state_diff: Some(slots),
+                    ..Default::default()
+                },
+            ),
+            Err(StateOverrideError::StateAndStateDiff(ACCOUNT))
+        );
+    }
+
+    #[test]
+    fn block_hash_overrides() {
+        let hash = B256::with_last_byte(1);
+        let db = StateOverride::new(db()).with_block_hashes([(5, hash)]);
+        assert_eq!(db.block_hash_ref(5).unwrap(), hash);
+        assert_eq!(
+            db.block_hash_ref(6).unwrap(),
+            EmptyDB::default().block_hash_ref(6).unwrap()
+        );
+    }
+}
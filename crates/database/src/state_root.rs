@@ -0,0 +1,457 @@
+//! State root and storage root computation.
+//!
+//! [`StateTrie`] keeps the account trie and the storage tries of all accounts in memory.
+//! Changes from a [`BundleState`] or a [`StateChangeset`] are applied on top of it and only
+//! the tries of changed accounts are hashed again when the root is requested.
+mod trie;
+
+pub use trie::{MerkleTrie, EMPTY_ROOT_HASH};
+
+use crate::states::{BundleState, CacheState, PlainAccount, StateChangeset};
+use alloy_rlp::{Encodable, RlpEncodable};
+use primitives::{keccak256, Address, HashMap, HashSet, StorageKey, StorageValue, B256, U256};
+use state::AccountInfo;
+use std::vec::Vec;
+
+/// Computes the storage root of the given storage slots.
+///
+/// Slots with zero value are skipped.
+pub fn storage_root(storage: impl IntoIterator<Item = (StorageKey, StorageValue)>) -> B256 {
+    let mut trie = MerkleTrie::new();
+    for (slot, value) in storage {
+        if !value.is_zero() {
+            trie.insert(hashed_slot(slot), alloy_rlp::encode(value));
+        }
+    }
+    trie.root()
+}
+
+/// Computes the state root of the given accounts.
+pub fn state_root<'a>(accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>) -> B256 {
+    StateTrie::from_plain_accounts(accounts).root()
+}
+
+impl BundleState {
+    /// Computes the state root of the present state of the bundle.
+    ///
+    /// The root is only valid if the bundle contains the whole state, for incremental
+    /// computation on top of an existing state use [`StateTrie`].
+    pub fn state_root(&self) -> B256 {
+        let mut trie = StateTrie::new();
+        trie.apply_bundle_state(self);
+        trie.root()
+    }
+}
+
+impl CacheState {
+    /// Computes the state root of all existing accounts in the cache.
+    pub fn state_root(&self) -> B256 {
+        state_root(self.trie_account())
+    }
+}
+
+/// Account leaf of the state trie.
+#[derive(RlpEncodable)]
+struct TrieAccount {
+    nonce: u64,
+    balance: U256,
+    storage_root: B256,
+    code_hash: B256,
+}
+
+/// Account with its storage trie.
+#[derive(Clone, Debug, Default)]
+struct AccountTrie {
+    nonce: u64,
+    balance: U256,
+    code_hash: B256,
+    storage: MerkleTrie,
+}
+
+impl AccountTrie {
+    fn set_info(&mut self, info: &AccountInfo) {
+        self.nonce = info.nonce;
+        self.balance = info.balance;
+        self.code_hash = info.code_hash;
+    }
+
+    fn set_storage(&mut self, slot: StorageKey, value: StorageValue) {
+        if value.is_zero() {
+            self.storage.remove(hashed_slot(slot));
+        } else {
+            self.storage
+                .insert(hashed_slot(slot), alloy_rlp::encode(value));
+        }
+    }
+}
+
+/// In-memory state trie with cached storage tries.
+///
+/// Build it from the full pre-state once and apply the changes of every block on top of it,
+/// [`StateTrie::root`] then only rehashes the accounts and slots that changed.
+///
+/// Changes are applied lazily, leaves of the changed accounts are re-encoded on the next
+/// call to [`StateTrie::root`].
+#[derive(Clone, Debug, Default)]
+pub struct StateTrie {
+    /// Trie of accounts keyed by hashed address.
+    trie: MerkleTrie,
+    /// Existing accounts with their storage tries.
+    accounts: HashMap<Address, AccountTrie>,
+    /// Accounts changed since the last root computation.
+    changed: HashSet<Address>,
+}
+
+impl StateTrie {
+    /// Creates an empty state trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the state trie from the full list of accounts.
+    pub fn from_plain_accounts<'a>(
+        accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+    ) -> Self {
+        let mut trie = Self::new();
+        for (address, account) in accounts {
+            trie.insert_account(address, &account.info);
+            for (slot, value) in &account.storage {
+                trie.set_storage(address, *slot, *value);
+            }
+        }
+        trie
+    }
+
+    /// Returns the number of accounts in the trie.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns `true` if there are no accounts in the trie.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Inserts or updates the account info, keeping its storage.
+    pub fn insert_account(&mut self, address: Address, info: &AccountInfo) {
+        self.accounts.entry(address).or_default().set_info(info);
+        self.changed.insert(address);
+    }
+
+    /// Removes the account together with its storage.
+    pub fn remove_account(&mut self, address: Address) {
+        self.accounts.remove(&address);
+        self.changed.insert(address);
+    }
+
+    /// Sets the storage slot of the account, zero value removes the slot.
+    ///
+    /// The account is created with empty info if it does not exist.
+    pub fn set_storage(&mut self, address: Address, slot: StorageKey, value: StorageValue) {
+        self.accounts
+            .entry(address)
+            .or_default()
+            .set_storage(slot, value);
+        self.changed.insert(address);
+    }
+
+    /// Clears the storage of the account.
+    pub fn wipe_storage(&mut self, address: Address) {
+        if let Some(account) = self.accounts.get_mut(&address) {
+            account.storage = MerkleTrie::new();
+            self.changed.insert(address);
+        }
+    }
+
+    /// Applies the present state of all accounts in the bundle.
+    ///
+    /// Storage of destroyed accounts is wiped before the bundle storage is applied.
+    pub fn apply_bundle_state(&mut self, bundle: &BundleState) {
+        for (address, account) in &bundle.state {
+            let Some(info) = &account.info else {
+                self.remove_account(*address);
+                continue;
+            };
+            if account.was_destroyed() {
+                self.wipe_storage(*address);
+            }
+            self.insert_account(*address, info);
+            for (slot, value) in &account.storage {
+                self.set_storage(*address, *slot, value.present_value);
+            }
+        }
+    }
+
+    /// Applies the plain state changeset.
+    pub fn apply_changeset(&mut self, changeset: &StateChangeset) {
+        for storage in &changeset.storage {
+            if storage.wipe_storage {
+                self.wipe_storage(storage.address);
+            }
+            for (slot, value) in &storage.storage {
+                self.set_storage(storage.address, *slot, *value);
+            }
+        }
+        for (address, info) in &changeset.accounts {
+            match info {
+                Some(info) => self.insert_account(*address, info),
+                None => self.remove_account(*address),
+            }
+        }
+    }
+
+    /// Returns the storage root of the account or `None` if the account does not exist.
+    pub fn storage_root(&mut self, address: Address) -> Option<B256> {
+        self.accounts
+            .get_mut(&address)
+            .map(|account| account.storage.root())
+    }
+
+    /// Returns the storage roots of all accounts.
+    pub fn storage_roots(&mut self) -> HashMap<Address, B256> {
+        self.accounts
+            .iter_mut()
+            .map(|(address, account)| (*address, account.storage.root()))
+            .collect()
+    }
+
+    /// Returns the state root, rehashing only the accounts changed since the last call.
+    pub fn root(&mut self) -> B256 {
+        for address in core::mem::take(&mut self.changed) {
+            let key = keccak256(address);
+            match self.accounts.get_mut(&address) {
+                Some(account) => {
+                    let leaf = TrieAccount {
+                        nonce: account.nonce,
+                        balance: account.balance,
+                        storage_root: account.storage.root(),
+                        code_hash: account.code_hash,
+                    };
+                    let mut value = Vec::with_capacity(leaf.length());
+                    leaf.encode(&mut value);
+                    self.trie.insert(key, value);
+                }
+                None => {
+                    self.trie.remove(key);
+                }
+            }
+        }
+        self.trie.root()
+    }
+}
+
+fn hashed_slot(slot: StorageKey) -> B256 {
+    keccak256(slot.to_be_bytes::<32>())
+}
+
+/// Helpers shared by the tests of the state root modules.
+#[cfg(test)]
+mod test_utils {
+    use hash_db::Hasher;
+    use plain_hasher::PlainHasher;
+    use primitives::{keccak256, B256};
+
+    /// Keccak hasher of the reference `triehash` implementation.
+    #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+    pub(super) struct KeccakHasher;
+
+    impl Hasher for KeccakHasher {
+        type Out = B256;
+        type StdHasher = PlainHasher;
+        const LENGTH: usize = 32;
+
+        fn hash(x: &[u8]) -> Self::Out {
+            keccak256(x)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::KeccakHasher;
+    use super::*;
+    use crate::states::OriginalValuesKnown;
+    use primitives::KECCAK_EMPTY;
+
+    /// Full recomputation with `triehash`, same as the state test runner used to do.
+    fn reference_state_root(accounts: &HashMap<Address, PlainAccount>) -> B256 {
+        triehash::sec_trie_root::<KeccakHasher, _, _, _>(accounts.iter().map(
+            |(address, account)| {
+                let storage_root = triehash::sec_trie_root::<KeccakHasher, _, _, _>(
+                    account
+                        .storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(slot, value)| (slot.to_be_bytes::<32>(), alloy_rlp::encode(value))),
+                );
+                let leaf = TrieAccount {
+                    nonce: account.info.nonce,
+                    balance: account.info.balance,
+                    storage_root,
+                    code_hash: account.info.code_hash,
+                };
+                (address.into_array(), alloy_rlp::encode(leaf))
+            },
+        ))
+    }
+
+    fn address(i: u64) -> Address {
+        Address::from_word(keccak256(i.to_be_bytes()))
+    }
+
+    fn info(i: u64) -> AccountInfo {
+        AccountInfo {
+            balance: U256::from(i * 1_000),
+            nonce: i,
+            code_hash: if i.is_multiple_of(2) {
+                KECCAK_EMPTY
+            } else {
+                keccak256(i.to_le_bytes())
+            },
+            code: None,
+        }
+    }
+
+    fn pre_state() -> HashMap<Address, PlainAccount> {
+        (0..50)
+            .map(|i| {
+                let storage = (0..i % 7)
+                    .map(|slot| (U256::from(slot), U256::from(i * 100 + slot)))
+                    .collect();
+                (
+                    address(i),
+                    PlainAccount {
+                        info: info(i),
+                        storage,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_state_root() {
+        assert_eq!(StateTrie::new().root(), EMPTY_ROOT_HASH);
+        assert_eq!(storage_root([]), EMPTY_ROOT_HASH);
+        assert_eq!(BundleState::default().state_root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn state_root_matches_reference() {
+        let accounts = pre_state();
+        let expected = reference_state_root(&accounts);
+        assert_eq!(
+            state_root(accounts.iter().map(|(a, acc)| (*a, acc))),
+            expected
+        );
+
+        let mut cache = CacheState::new(false);
+        for (address, account) in &accounts {
+            cache.insert_account_with_storage(
+                *address,
+                account.info.clone(),
+                account.storage.clone(),
+            );
+        }
+        assert_eq!(cache.state_root(), expected);
+
+        let mut trie = StateTrie::from_plain_accounts(accounts.iter().map(|(a, acc)| (*a, acc)));
+        let account = &accounts[&address(6)];
+        assert_eq!(
+            trie.storage_root(address(6)),
+            Some(storage_root(account.storage.clone()))
+        );
+        assert_eq!(trie.storage_root(address(0)), Some(EMPTY_ROOT_HASH));
+        assert_eq!(trie.storage_root(address(100)), None);
+        assert_eq!(trie.storage_roots().len(), accounts.len());
+    }
+
+    /// Bundle that changes, removes and creates accounts on top of [`pre_state`].
+    fn bundle(
+        pre: &HashMap<Address, PlainAccount>,
+    ) -> (BundleState, HashMap<Address, PlainAccount>) {
+        let mut post = pre.clone();
+        let mut changes = Vec::new();
+
+        // Balance change.
+        let mut changed = info(1);
+        changed.balance = U256::from(42);
+        post.get_mut(&address(1)).unwrap().info = changed.clone();
+        changes.push((address(1), Some(info(1)), Some(changed), HashMap::default()));
+
+        // Storage change: update, clear and add slots.
+        let storage = HashMap::from_iter([
+            (U256::from(0), (U256::from(600), U256::from(7))),
+            (U256::from(1), (U256::from(601), U256::ZERO)),
+            (U256::from(10), (U256::ZERO, U256::from(1))),
+        ]);
+        let post_storage = &mut post.get_mut(&address(6)).unwrap().storage;
+        for (slot, (_, value)) in &storage {
+            post_storage.insert(*slot, *value);
+        }
+        changes.push((address(6), Some(info(6)), Some(info(6)), storage));
+
+        // Removed account.
+        post.remove(&address(13));
+        changes.push((address(13), Some(info(13)), None, HashMap::default()));
+
+        // New account with storage.
+        let storage = HashMap::from_iter([(U256::from(3), (U256::ZERO, U256::from(3)))]);
+        post.insert(
+            address(100),
+            PlainAccount {
+                info: info(100),
+                storage: HashMap::from_iter([(U256::from(3), U256::from(3))]),
+            },
+        );
+        changes.push((address(100), None, Some(info(100)), storage));
+
+        let reverts = [[(
+            address(13),
+            Some(Some(info(13))),
+            Vec::<(StorageKey, StorageValue)>::new(),
+        )]];
+        (BundleState::new(changes, reverts, []), post)
+    }
+
+    #[test]
+    fn incremental_bundle_matches_full_recomputation() {
+        let pre = pre_state();
+        let mut trie = StateTrie::from_plain_accounts(pre.iter().map(|(a, acc)| (*a, acc)));
+        assert_eq!(trie.root(), reference_state_root(&pre));
+
+        let (bundle, post) = bundle(&pre);
+        trie.apply_bundle_state(&bundle);
+        assert_eq!(trie.len(), post.len());
+        assert_eq!(trie.root(), reference_state_root(&post));
+        assert_eq!(
+            trie.storage_root(address(6)),
+            Some(storage_root(post[&address(6)].storage.clone()))
+        );
+    }
+
+    #[test]
+    fn changeset_matches_bundle() {
+        let pre = pre_state();
+        let (bundle, post) = bundle(&pre);
+
+        let mut trie = StateTrie::from_plain_accounts(pre.iter().map(|(a, acc)| (*a, acc)));
+        trie.apply_changeset(&bundle.to_plain_state(OriginalValuesKnown::Yes));
+        assert_eq!(trie.root(), reference_state_root(&post));
+
+        // Wiping the storage drops the storage root of the account.
+        let mut changeset = StateChangeset::default();
+        changeset
+            .storage
+            .push(crate::states::PlainStorageChangeset {
+                address: address(6),
+                wipe_storage: true,
+                storage: Vec::new(),
+            });
+        trie.apply_changeset(&changeset);
+        let mut post = post;
+        post.get_mut(&address(6)).unwrap().storage.clear();
+        assert_eq!(trie.root(), reference_state_root(&post));
+        assert_eq!(trie.storage_root(address(6)), Some(EMPTY_ROOT_HASH));
+    }
+}
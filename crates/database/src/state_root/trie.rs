@@ -0,0 +1,397 @@
+//! In-memory Merkle Patricia Trie with cached node hashes.
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use primitives::{b256, keccak256, B256};
+use std::{boxed::Box, vec::Vec};
+
+/// Root hash of an empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    b256!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// In-memory secure Merkle Patricia Trie.
+///
+/// Keys are 32 byte hashes and values are already RLP encoded. Every node caches its
+/// reference (inlined RLP or hash), inserting or removing a key only invalidates the
+/// nodes on its path so the root of a modified trie is recomputed incrementally.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleTrie {
+    root: Node,
+}
+
+impl MerkleTrie {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the trie has no entries.
+    pub fn is_empty(&self) -> bool {
+        matches!(self.root.kind, NodeKind::Empty)
+    }
+
+    /// Inserts the RLP encoded `value` under the hashed `key`.
+    pub fn insert(&mut self, key: B256, value: Vec<u8>) {
+        self.root.insert(&to_nibbles(&key), value);
+    }
+
+    /// Removes the value under the hashed `key`.
+    ///
+    /// Returns `true` if the key was present.
+    pub fn remove(&mut self, key: B256) -> bool {
+        self.root.remove(&to_nibbles(&key))
+    }
+
+    /// Returns the root hash of the trie.
+    ///
+    /// Only the nodes changed since the last call are hashed again.
+    pub fn root(&mut self) -> B256 {
+        if self.is_empty() {
+            return EMPTY_ROOT_HASH;
+        }
+        keccak256(self.root.rlp())
+    }
+}
+
+/// Trie node with cached RLP encoding.
+#[derive(Clone, Debug, Default)]
+struct Node {
+    kind: NodeKind,
+    /// RLP encoding of the node, `None` if the node was modified.
+    rlp: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, Default)]
+enum NodeKind {
+    #[default]
+    Empty,
+    Leaf {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extension {
+        key: Vec<u8>,
+        child: Box<Node>,
+    },
+    Branch {
+        children: Box<[Node; 16]>,
+    },
+}
+
+impl Node {
+    fn new(kind: NodeKind) -> Self {
+        Self { kind, rlp: None }
+    }
+
+    fn leaf(key: &[u8], value: Vec<u8>) -> Self {
+        Self::new(NodeKind::Leaf {
+            key: key.to_vec(),
+            value,
+        })
+    }
+
+    /// Wraps the node in an extension if `key` is not empty.
+    fn with_prefix(key: &[u8], node: Node) -> Self {
+        if key.is_empty() {
+            node
+        } else {
+            Self::new(NodeKind::Extension {
+                key: key.to_vec(),
+                child: Box::new(node),
+            })
+        }
+    }
+
+    fn empty_branch() -> Box<[Node; 16]> {
+        Box::new(core::array::from_fn(|_| Node::default()))
+    }
+
+    fn insert(&mut self, path: &[u8], value: Vec<u8>) {
+        self.rlp = None;
+        match &mut self.kind {
+            NodeKind::Empty => *self = Self::leaf(path, value),
+            NodeKind::Leaf {
+                key,
+                value: old_value,
+            } => {
+                if key.as_slice() == path {
+                    *old_value = value;
+                    return;
+                }
+                // Keys have the same length so they diverge before the end of the path.
+                let common = common_prefix(key, path);
+                let mut children = Self::empty_branch();
+                children[key[common] as usize] =
+                    Self::leaf(&key[common + 1..], core::mem::take(old_value));
+                children[path[common] as usize] = Self::leaf(&path[common + 1..], value);
+                *self =
+                    Self::with_prefix(&path[..common], Self::new(NodeKind::Branch { children }));
+            }
+            NodeKind::Extension { key, child } => {
+                let common = common_prefix(key, path);
+                if common == key.len() {
+                    child.insert(&path[common..], value);
+                    return;
+                }
+                let mut children = Self::empty_branch();
+                let child = core::mem::take(child.as_mut());
+                children[key[common] as usize] = Self::with_prefix(&key[common + 1..], child);
+                children[path[common] as usize] = Self::leaf(&path[common + 1..], value);
+                *self =
+                    Self::with_prefix(&path[..common], Self::new(NodeKind::Branch { children }));
+            }
+            NodeKind::Branch { children } => {
+                children[path[0] as usize].insert(&path[1..], value);
+            }
+        }
+    }
+
+    fn remove(&mut self, path: &[u8]) -> bool {
+        let removed = match &mut self.kind {
+            NodeKind::Empty => false,
+            NodeKind::Leaf { key, .. } => {
+                if key.as_slice() != path {
+                    return false;
+                }
+                self.kind = NodeKind::Empty;
+                true
+            }
+            NodeKind::Extension { key, child } => {
+                if !path.starts_with(key) || !child.remove(&path[key.len()..]) {
+                    return false;
+                }
+                // Merge the extension with the collapsed child.
+                let mut merged = core::mem::take(key);
+                match core::mem::take(&mut child.kind) {
+                    NodeKind::Empty => self.kind = NodeKind::Empty,
+                    NodeKind::Leaf { key, value } => {
+                        merged.extend_from_slice(&key);
+                        self.kind = NodeKind::Leaf { key: merged, value };
+                    }
+                    NodeKind::Extension { key, child } => {
+                        merged.extend_from_slice(&key);
+                        self.kind = NodeKind::Extension { key: merged, child };
+                    }
+                    kind @ NodeKind::Branch { .. } => {
+                        child.kind = kind;
+                        *key = merged;
+                    }
+                }
+                true
+            }
+            NodeKind::Branch { children } => {
+                if !children[path[0] as usize].remove(&path[1..]) {
+                    return false;
+                }
+                let mut remaining = children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, child)| !matches!(child.kind, NodeKind::Empty));
+                let (index, _) = remaining.next().expect("branch has at least two children");
+                if remaining.next().is_none() {
+                    // Only one child left, collapse the branch into it.
+                    let child = core::mem::take(&mut children[index]);
+                    let nibble = [index as u8];
+                    self.kind = match child.kind {
+                        NodeKind::Leaf { key, value } => NodeKind::Leaf {
+                            key: [&nibble[..], &key].concat(),
+                            value,
+                        },
+                        NodeKind::Extension { key, child } => NodeKind::Extension {
+                            key: [&nibble[..], &key].concat(),
+                            child,
+                        },
+                        kind => NodeKind::Extension {
+                            key: nibble.to_vec(),
+                            child: Box::new(Node {
+                                kind,
+                                rlp: child.rlp,
+                            }),
+                        },
+                    };
+                }
+                true
+            }
+        };
+        if removed {
+            self.rlp = None;
+        }
+        removed
+    }
+
+    /// Returns the RLP of the node, encoding it if it is not cached.
+    fn rlp(&mut self) -> &[u8] {
+        match &mut self.rlp {
+            Some(rlp) => rlp,
+            rlp @ None => rlp.insert(self.kind.encode()),
+        }
+    }
+
+    /// Returns the reference used by the parent node: the RLP itself if shorter
+    /// than 32 bytes, otherwise its hash.
+    fn reference(&mut self) -> Vec<u8> {
+        let rlp = self.rlp();
+        if rlp.len() < 32 {
+            rlp.to_vec()
+        } else {
+            alloy_rlp::encode(keccak256(rlp))
+        }
+    }
+}
+
+impl NodeKind {
+    fn encode(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            NodeKind::Empty => out.push(EMPTY_STRING_CODE),
+            NodeKind::Leaf { key, value } => {
+                let key = hex_prefix(key, true);
+                let value = value.as_slice();
+                Header {
+                    list: true,
+                    payload_length: key.as_slice().length() + value.length(),
+                }
+                .encode(&mut out);
+                key.as_slice().encode(&mut out);
+                value.encode(&mut out);
+            }
+            NodeKind::Extension { key, child } => {
+                let key = hex_prefix(key, false);
+                let child = child.reference();
+                Header {
+                    list: true,
+                    payload_length: key.as_slice().length() + child.len(),
+                }
+                .encode(&mut out);
+                key.as_slice().encode(&mut out);
+                out.extend_from_slice(&child);
+            }
+            NodeKind::Branch { children } => {
+                let children: Vec<Vec<u8>> = children
+                    .iter_mut()
+                    .map(|child| match child.kind {
+                        NodeKind::Empty => Vec::from([EMPTY_STRING_CODE]),
+                        _ => child.reference(),
+                    })
+                    .collect();
+                Header {
+                    list: true,
+                    // 16 children and an empty value.
+                    payload_length: children.iter().map(Vec::len).sum::<usize>() + 1,
+                }
+                .encode(&mut out);
+                for child in children {
+                    out.extend_from_slice(&child);
+                }
+                out.push(EMPTY_STRING_CODE);
+            }
+        }
+        out
+    }
+}
+
+fn to_nibbles(key: &B256) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+/// Hex-prefix encoding of a nibble path.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 0x20 } else { 0x00 };
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        out.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_root::test_utils::KeccakHasher;
+
+    fn reference_root(entries: &[(B256, Vec<u8>)]) -> B256 {
+        triehash::trie_root::<KeccakHasher, _, _, _>(entries.iter().cloned())
+    }
+
+    fn entry(i: u64) -> (B256, Vec<u8>) {
+        let key = keccak256(i.to_be_bytes());
+        // Mix short values that get inlined with long ones that get hashed.
+        let value = alloy_rlp::encode(&key[..(i % 33) as usize]);
+        (key, value)
+    }
+
+    #[test]
+    fn empty_trie() {
+        let mut trie = MerkleTrie::new();
+        assert!(trie.is_empty());
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+        assert_eq!(reference_root(&[]), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn insert_matches_reference() {
+        let mut trie = MerkleTrie::new();
+        let mut entries = Vec::new();
+        for i in 0..300 {
+            let (key, value) = entry(i);
+            trie.insert(key, value.clone());
+            entries.push((key, value));
+            if i % 37 == 0 {
+                assert_eq!(trie.root(), reference_root(&entries));
+            }
+        }
+        assert_eq!(trie.root(), reference_root(&entries));
+    }
+
+    #[test]
+    fn update_and_remove_match_reference() {
+        let mut trie = MerkleTrie::new();
+        let mut entries: Vec<_> = (0..300).map(entry).collect();
+        for (key, value) in &entries {
+            trie.insert(*key, value.clone());
+        }
+        assert_eq!(trie.root(), reference_root(&entries));
+
+        // Update every third value.
+        for (key, value) in entries.iter_mut().step_by(3) {
+            *value = alloy_rlp::encode(key.as_slice());
+            trie.insert(*key, value.clone());
+        }
+        assert_eq!(trie.root(), reference_root(&entries));
+
+        // Remove entries until the trie is empty.
+        while !entries.is_empty() {
+            let (key, _) = entries.swap_remove(entries.len() / 2);
+            assert!(trie.remove(key));
+            assert!(!trie.remove(key));
+            if entries.len() % 29 == 0 {
+                assert_eq!(trie.root(), reference_root(&entries));
+            }
+        }
+        assert!(trie.is_empty());
+        assert_eq!(trie.root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn hex_prefix_encoding() {
+        assert_eq!(hex_prefix(&[1, 2, 3, 4, 5], false), [0x11, 0x23, 0x45]);
+        assert_eq!(
+            hex_prefix(&[0, 1, 2, 3, 4, 5], false),
+            [0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            hex_prefix(&[0x0f, 1, 0x0c, 0x0b, 8], true),
+            [0x3f, 0x1c, 0xb8]
+        );
+        assert_eq!(hex_prefix(&[], true), [0x20]);
+    }
+}
@@ -1,10 +1,13 @@
 use super::{
-    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
-    CacheAccount, StateBuilder, TransitionAccount, TransitionState,
+    bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, AccountStatus,
+    BundleState, CacheAccount, StateBuilder, TransitionAccount, TransitionState,
 };
+use crate::snapshot::{restore_entries, save_entry, save_slot, SnapshotId, Snapshots};
 use bytecode::Bytecode;
 use database_interface::{Database, DatabaseCommit, DatabaseRef, EmptyDB};
-use primitives::{hash_map, Address, HashMap, StorageKey, StorageValue, B256, BLOCK_HASH_HISTORY};
+use primitives::{
+    hash_map, Address, HashMap, HashSet, StorageKey, StorageValue, B256, BLOCK_HASH_HISTORY,
+};
 use state::{Account, AccountInfo};
 use std::{
     boxed::Box,
@@ -63,6 +66,22 @@ pub struct State<DB> {
     ///
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// Undo layers of the snapshots taken with [`State::snapshot`].
+    pub(crate) snapshots: Snapshots<StateSnapshotLayer>,
+}
+
+/// Values saved by a [`State`] snapshot before they were modified.
+///
+/// Accounts are saved without their storage, storage slots are saved one by one.
+/// `None` means that the entry was not present.
+#[derive(Debug, Clone, Default)]
+pub struct StateSnapshotLayer {
+    accounts: HashMap<Address, Option<(Option<AccountInfo>, AccountStatus)>>,
+    storage: HashMap<(Address, StorageKey), Option<StorageValue>>,
+    /// Accounts whose storage was replaced, slots cached after that are dropped on revert.
+    wiped: HashSet<Address>,
+    contracts: HashMap<B256, Option<Bytecode>>,
+    transitions: HashMap<Address, Option<TransitionAccount>>,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -99,6 +118,7 @@ impl<DB: Database> State<DB> {
             if balance == 0 {
                 continue;
             }
+            self.save_account(address);
             let original_account = self.load_cache_account(address)?;
             transitions.push((
                 address,
@@ -108,9 +128,7 @@ impl<DB: Database> State<DB> {
             ))
         }
         // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(())
     }
 
@@ -125,15 +143,14 @@ impl<DB: Database> State<DB> {
         let mut transitions = Vec::new();
         let mut balances = Vec::new();
         for address in addresses {
+            self.save_account(address);
             let original_account = self.load_cache_account(address)?;
             let (balance, transition) = original_account.drain_balance();
             balances.push(balance);
             transitions.push((address, transition))
         }
         // Append transition
-        if let Some(s) = self.transition_state.as_mut() {
-            s.add_transitions(transitions)
-        }
+        self.apply_transition(transitions);
         Ok(balances)
     }
 
@@ -144,11 +161,15 @@ impl<DB: Database> State<DB> {
 
     /// Inserts a non-existing account into the state.
     pub fn insert_not_existing(&mut self, address: Address) {
+        self.save_account(address);
+        self.save_storage(address);
         self.cache.insert_not_existing(address)
     }
 
     /// Inserts an account into the state.
     pub fn insert_account(&mut self, address: Address, info: AccountInfo) {
+        self.save_account(address);
+        self.save_storage(address);
         self.cache.insert_account(address, info)
     }
 
@@ -159,6 +180,9 @@ impl<DB: Database> State<DB> {
         info: AccountInfo,
         storage: PlainStorage,
     ) {
+        self.save_account(address);
+        self.save_storage(address);
+        self.save_slots(address, storage.keys().copied());
         self.cache
             .insert_account_with_storage(address, info, storage)
     }
@@ -167,6 +191,11 @@ impl<DB: Database> State<DB> {
     pub fn apply_transition(&mut self, transitions: Vec<(Address, TransitionAccount)>) {
         // Add transition to transition state.
         if let Some(s) = self.transition_state.as_mut() {
+            if let Some(layer) = self.snapshots.top_mut() {
+                for (address, _) in &transitions {
+                    save_entry(&mut layer.transitions, &s.transitions, address);
+                }
+            }
             s.add_transitions(transitions)
         }
     }
@@ -176,7 +205,10 @@ impl<DB: Database> State<DB> {
     /// This action will create final post state and all reverts so that
     /// we at any time revert state of bundle to the state before transition
     /// is applied.
+    ///
+    /// Bundle state is not captured by snapshots, so all snapshots are invalidated.
     pub fn merge_transitions(&mut self, retention: BundleRetention) {
+        self.snapshots.clear();
         if let Some(transition_state) = self.transition_state.as_mut().map(TransitionState::take) {
             self.bundle_state
                 .apply_transitions_and_create_reverts(transition_state, retention);
@@ -187,6 +219,8 @@ impl<DB: Database> State<DB> {
     ///
     /// If the account is not found in the cache, it will be loaded from the
     /// database and inserted into the cache.
+    ///
+    /// Modifications through the returned reference are not tracked by snapshots.
     pub fn load_cache_account(&mut self, address: Address) -> Result<&mut CacheAccount, DB::Error> {
         match self.cache.accounts.entry(address) {
             hash_map::Entry::Vacant(entry) => {
@@ -227,6 +261,130 @@ impl<DB: Database> State<DB> {
     }
 }
 
+impl<DB> State<DB> {
+    /// Returns the undo layers of the valid snapshots.
+    pub fn snapshots(&self) -> &Snapshots<StateSnapshotLayer> {
+        &self.snapshots
+    }
+
+    /// Takes a snapshot of the cached accounts, storage, contracts and pending transitions
+    /// and returns its id.
+    ///
+    /// The snapshot is cheap to take, values are saved only when they are modified for the
+    /// first time after the snapshot. [`State::merge_transitions`] invalidates all snapshots
+    /// as the bundle state is not captured.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        self.snapshots.push(StateSnapshotLayer::default())
+    }
+
+    /// Reverts the state to the snapshot.
+    ///
+    /// The snapshot and all snapshots taken after it are invalidated.
+    /// Returns `false` if the snapshot is not valid.
+    pub fn revert_to(&mut self, id: SnapshotId) -> bool {
+        let Some(layers) = self.snapshots.pop_to(id) else {
+            return false;
+        };
+        for layer in layers {
+            for (address, saved) in layer.accounts {
+                let Some((info, status)) = saved else {
+                    self.cache.accounts.remove(&address);
+                    continue;
+                };
+                let account = self
+                    .cache
+                    .accounts
+                    .entry(address)
+                    .or_insert_with(CacheAccount::new_loaded_not_existing);
+                account.status = status;
+                match (info, account.account.as_mut()) {
+                    (Some(info), Some(account)) => account.info = info,
+                    (Some(info), None) => account.account = Some(info.into()),
+                    (None, _) => account.account = None,
+                }
+            }
+            for address in layer.wiped {
+                if let Some(account) = self
+                    .cache
+                    .accounts
+                    .get_mut(&address)
+                    .and_then(|account| account.account.as_mut())
+                {
+                    account.storage.clear();
+                }
+            }
+            for ((address, key), value) in layer.storage {
+                let Some(account) = self
+                    .cache
+                    .accounts
+                    .get_mut(&address)
+                    .and_then(|account| account.account.as_mut())
+                else {
+                    continue;
+                };
+                match value {
+                    Some(value) => account.storage.insert(key, value),
+                    None => account.storage.remove(&key),
+                };
+            }
+            restore_entries(layer.contracts, &mut self.cache.contracts);
+            if let Some(transition_state) = self.transition_state.as_mut() {
+                restore_entries(layer.transitions, &mut transition_state.transitions);
+            }
+        }
+        true
+    }
+
+    /// Saves the account info and status in the latest snapshot before they are modified.
+    #[inline]
+    fn save_account(&mut self, address: Address) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            layer.accounts.entry(address).or_insert_with(|| {
+                self.cache
+                    .accounts
+                    .get(&address)
+                    .map(|account| (account.account_info(), account.status))
+            });
+        }
+    }
+
+    /// Saves the storage slots in the latest snapshot before they are modified.
+    #[inline]
+    fn save_slots(&mut self, address: Address, keys: impl IntoIterator<Item = StorageKey>) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            let storage = self
+                .cache
+                .accounts
+                .get(&address)
+                .and_then(|account| account.account.as_ref())
+                .map(|account| &account.storage);
+            for key in keys {
+                save_slot(&mut layer.storage, storage, address, key);
+            }
+        }
+    }
+
+    /// Saves all cached storage slots of the account in the latest snapshot before the storage
+    /// is replaced.
+    fn save_storage(&mut self, address: Address) {
+        if let Some(layer) = self.snapshots.top_mut() {
+            if !layer.wiped.insert(address) {
+                return;
+            }
+            if let Some(account) = self
+                .cache
+                .accounts
+                .get(&address)
+                .and_then(|account| account.account.as_ref())
+            {
+                for key in account.storage.keys() {
+                    save_slot(&mut layer.storage, Some(&account.storage), address, *key);
+                }
+            }
+        }
+    }
+}
+
 impl<DB: Database> Database for State<DB> {
     type Error = DB::Error;
 
@@ -311,6 +469,30 @@ impl<DB: Database> Database for State<DB> {
 
 impl<DB: Database> DatabaseCommit for State<DB> {
     fn commit(&mut self, evm_state: HashMap<Address, Account>) {
+        for (address, account) in &evm_state {
+            if !account.is_touched() {
+                continue;
+            }
+            self.save_account(*address);
+            if account.is_selfdestructed() || account.is_created() {
+                self.save_storage(*address);
+            }
+            let changed = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.is_changed())
+                .map(|(key, _)| *key);
+            self.save_slots(*address, changed);
+            if let Some(layer) = self.snapshots.top_mut() {
+                if account.info.code.is_some() {
+                    save_entry(
+                        &mut layer.contracts,
+                        &self.cache.contracts,
+                        &account.info.code_hash,
+                    );
+                }
+            }
+        }
         let transitions = self.cache.apply_evm_state(evm_state);
         self.apply_transition(transitions);
     }
@@ -389,6 +571,101 @@ mod tests {
         AccountRevert, AccountStatus, BundleAccount, RevertToSlot,
     };
     use primitives::{keccak256, U256};
+    use state::EvmStorageSlot;
+
+    #[test]
+    fn snapshot_revert() {
+        let mut state = State::builder().with_bundle_update().build();
+        let existing = Address::with_last_byte(1);
+        let created = Address::with_last_byte(2);
+        state.insert_account(
+            existing,
+            AccountInfo {
+                balance: U256::from(1),
+                ..Default::default()
+            },
+        );
+        state.increment_balances([(existing, 1)]).unwrap();
+        let cache_before = state.cache.clone();
+        let transitions_before = state.transition_state.clone();
+
+        let snapshot = state.snapshot();
+        state
+            .increment_balances([(existing, 5), (created, 7)])
+            .unwrap();
+        let mut account = Account::from(state.basic(existing).unwrap().unwrap());
+        account.info.nonce = 1;
+        account.mark_touch();
+        state.commit(HashMap::from_iter([(existing, account)]));
+        assert_ne!(state.cache, cache_before);
+
+        assert!(state.revert_to(snapshot));
+        assert_eq!(state.cache, cache_before);
+        assert_eq!(state.transition_state, transitions_before);
+        assert!(!state.revert_to(snapshot));
+
+        // Merging transitions invalidates snapshots.
+        let snapshot = state.snapshot();
+        state.merge_transitions(BundleRetention::Reverts);
+        assert!(!state.revert_to(snapshot));
+    }
+
+    #[test]
+    fn snapshot_saves_only_writes() {
+        let mut state = State::builder().with_bundle_update().build();
+        let address = Address::with_last_byte(1);
+        state.insert_account_with_storage(
+            address,
+            AccountInfo {
+                balance: U256::from(1),
+                ..Default::default()
+            },
+            HashMap::from_iter([
+                (U256::from(1), U256::from(1)),
+                (U256::from(2), U256::from(2)),
+            ]),
+        );
+        let cache_before = state.cache.clone();
+
+        let snapshot = state.snapshot();
+        state.basic(address).unwrap();
+        state.storage(address, U256::from(1)).unwrap();
+        state.basic(Address::with_last_byte(2)).unwrap();
+        let layer = state.snapshots.top_mut().unwrap();
+        assert!(layer.accounts.is_empty() && layer.storage.is_empty());
+
+        let mut account = Account::from(state.basic(address).unwrap().unwrap());
+        account.storage.insert(
+            U256::from(2),
+            EvmStorageSlot::new_changed(U256::from(2), U256::from(5), 0),
+        );
+        account.mark_touch();
+        state.commit(HashMap::from_iter([(address, account)]));
+        let layer = state.snapshots.top_mut().unwrap();
+        assert_eq!(layer.accounts.len(), 1);
+        assert_eq!(
+            layer.storage,
+            HashMap::from_iter([((address, U256::from(2)), Some(U256::from(2)))])
+        );
+
+        // Slots read after the selfdestruct are cached as zero and dropped on revert.
+        let mut account = Account::from(state.basic(address).unwrap().unwrap());
+        account.mark_touch();
+        account.mark_selfdestruct();
+        state.commit(HashMap::from_iter([(address, account)]));
+        assert_eq!(state.storage(address, U256::from(3)).unwrap(), U256::ZERO);
+
+        assert!(state.revert_to(snapshot));
+        assert_eq!(
+            state.cache.accounts[&address],
+            cache_before.accounts[&address]
+        );
+        // Reads are not undone.
+        assert!(state
+            .cache
+            .accounts
+            .contains_key(&Address::with_last_byte(2)));
+    }
 
     #[test]
     fn block_hash_cache() {
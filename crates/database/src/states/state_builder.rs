@@ -1,4 +1,5 @@
 use super::{cache::CacheState, state::DBBox, BundleState, State, TransitionState};
+use crate::snapshot::Snapshots;
 use database_interface::{DBErrorMarker, Database, DatabaseRef, EmptyDB, WrapDatabaseRef};
 use primitives::B256;
 use std::collections::BTreeMap;
@@ -175,6 +176,7 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            snapshots: Snapshots::default(),
         }
     }
 }
@@ -0,0 +1,451 @@
+//! Execution witness recording and stateless execution.
+//!
+//! [`WitnessRecorder`] wraps a database and records every value read from it into an
+//! [`ExecutionWitness`]. [`WitnessDB`] executes using only the recorded witness and fails with
+//! [`MissingWitness`] on any read that is not part of it.
+//!
+//! With the `asyncdb` feature, [`AsyncWitnessRecorder`] records the responses of an async
+//! database so remote state can be saved to a fixture and replayed offline by a [`WitnessDB`].
+#[cfg(feature = "asyncdb")]
+mod async_recorder;
+
+#[cfg(feature = "asyncdb")]
+pub use async_recorder::AsyncWitnessRecorder;
+
+use core::{cell::RefCell, error::Error, fmt};
+use database_interface::{DBErrorMarker, Database, DatabaseCommit, DatabaseRef};
+use primitives::{Address, HashMap, StorageKey, StorageValue, B256};
+use state::{Account, AccountInfo, Bytecode};
+
+/// Pre-state read during execution.
+///
+/// Contains the first value returned by the database for every account, storage slot,
+/// bytecode and block hash that was accessed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionWitness {
+    /// Accounts info without code, `None` if the account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots of accounts.
+    pub storage: HashMap<Address, HashMap<StorageKey, StorageValue>>,
+    /// Bytecodes by code hash.
+    pub codes: HashMap<B256, Bytecode>,
+    /// Block hashes by block number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+impl ExecutionWitness {
+    /// Returns `true` if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.storage.is_empty()
+            && self.codes.is_empty()
+            && self.block_hashes.is_empty()
+    }
+
+    /// Merges the other witness into this one, keeping already present values.
+    pub fn extend(&mut self, other: ExecutionWitness) {
+        for (address, info) in other.accounts {
+            self.accounts.entry(address).or_insert(info);
+        }
+        for (address, storage) in other.storage {
+            let slots = self.storage.entry(address).or_default();
+            for (slot, value) in storage {
+                slots.entry(slot).or_insert(value);
+            }
+        }
+        for (hash, code) in other.codes {
+            self.codes.entry(hash).or_insert(code);
+        }
+        for (number, hash) in other.block_hashes {
+            self.block_hashes.entry(number).or_insert(hash);
+        }
+    }
+
+    fn record_account(&mut self, address: Address, info: &Option<AccountInfo>) {
+        let info = info.as_ref().map(|info| {
+            if let Some(code) = &info.code {
+                self.codes
+                    .entry(info.code_hash)
+                    .or_insert_with(|| code.clone());
+            }
+            info.copy_without_code()
+        });
+        self.accounts.entry(address).or_insert(info);
+    }
+
+    fn record_storage(&mut self, address: Address, index: StorageKey, value: StorageValue) {
+        self.storage
+            .entry(address)
+            .or_default()
+            .entry(index)
+            .or_insert(value);
+    }
+
+    fn record_code(&mut self, code_hash: B256, code: &Bytecode) {
+        self.codes.entry(code_hash).or_insert_with(|| code.clone());
+    }
+
+    fn record_block_hash(&mut self, number: u64, hash: B256) {
+        self.block_hashes.entry(number).or_insert(hash);
+    }
+}
+
+/// Database wrapper that records all reads into an [`ExecutionWitness`].
+///
+/// Implements [`Database`] if the inner database does, and [`DatabaseRef`] if the inner
+/// database does. Only the first value read for each key is recorded, so commits to the
+/// inner database do not change the recorded pre-state.
+#[derive(Debug, Default)]
+pub struct WitnessRecorder<DB> {
+    /// Inner database.
+    pub db: DB,
+    witness: RefCell<ExecutionWitness>,
+}
+
+impl<DB> WitnessRecorder<DB> {
+    /// Creates a new recorder wrapping the given database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: RefCell::new(ExecutionWitness::default()),
+        }
+    }
+
+    /// Returns a copy of the witness recorded so far.
+    pub fn witness(&self) -> ExecutionWitness {
+        self.witness.borrow().clone()
+    }
+
+    /// Takes the recorded witness, leaving an empty one in its place.
+    pub fn take_witness(&mut self) -> ExecutionWitness {
+        core::mem::take(self.witness.get_mut())
+    }
+
+    /// Consumes the recorder and returns the inner database and the recorded witness.
+    pub fn into_parts(self) -> (DB, ExecutionWitness) {
+        (self.db, self.witness.into_inner())
+    }
+}
+
+impl<DB: Database> Database for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic(address)?;
+        self.witness.get_mut().record_account(address, &info);
+        Ok(info)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash(code_hash)?;
+        self.witness.get_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        let value = self.db.storage(address, index)?;
+        self.witness.get_mut().record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash(number)?;
+        self.witness.get_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for WitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        self.witness.borrow_mut().record_account(address, &info);
+        Ok(info)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.witness.borrow_mut().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        let value = self.db.storage_ref(address, index)?;
+        self.witness
+            .borrow_mut()
+            .record_storage(address, index, value);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash_ref(number)?;
+        self.witness.borrow_mut().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+impl<DB: DatabaseCommit> DatabaseCommit for WitnessRecorder<DB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        self.db.commit(changes)
+    }
+}
+
+/// Read that is not covered by the [`ExecutionWitness`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MissingWitness {
+    /// Account is not in the witness.
+    Account(Address),
+    /// Storage slot is not in the witness.
+    Storage(Address, StorageKey),
+    /// Bytecode is not in the witness.
+    Code(B256),
+    /// Block hash is not in the witness.
+    BlockHash(u64),
+}
+
+impl DBErrorMarker for MissingWitness {}
+
+impl fmt::Display for MissingWitness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(address) => write!(f, "missing witness for account {address}"),
+            Self::Storage(address, index) => {
+                write!(f, "missing witness for storage slot {index} of {address}")
+            }
+            Self::Code(code_hash) => write!(f, "missing witness for code {code_hash}"),
+            Self::BlockHash(number) => write!(f, "missing witness for block hash {number}"),
+        }
+    }
+}
+
+impl Error for MissingWitness {}
+
+/// Stateless database backed only by an [`ExecutionWitness`].
+///
+/// Any read outside of the witness fails with [`MissingWitness`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WitnessDB {
+    /// Witness used to serve the reads.
+    pub witness: ExecutionWitness,
+}
+
+impl WitnessDB {
+    /// Creates a new database from the witness.
+    pub fn new(witness: ExecutionWitness) -> Self {
+        Self { witness }
+    }
+}
+
+impl From<ExecutionWitness> for WitnessDB {
+    fn from(witness: ExecutionWitness) -> Self {
+        Self::new(witness)
+    }
+}
+
+impl DatabaseRef for WitnessDB {
+    type Error = MissingWitness;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self
+            .witness
+            .accounts
+            .get(&address)
+            .ok_or(MissingWitness::Account(address))?;
+        Ok(info.clone().map(|mut info| {
+            info.code = self.witness.codes.get(&info.code_hash).cloned();
+            info
+        }))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.witness
+            .codes
+            .get(&code_hash)
+            .cloned()
+            .ok_or(MissingWitness::Code(code_hash))
+    }
+
+    fn storage_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.witness
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&index))
+            .copied()
+            .ok_or(MissingWitness::Storage(address, index))
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.witness
+            .block_hashes
+            .get(&number)
+            .copied()
+            .ok_or(MissingWitness::BlockHash(number))
+    }
+}
+
+impl Database for WitnessDB {
+    type Error = MissingWitness;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    fn storage(
+        &mut self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheDB, EmptyDB};
+    use primitives::{keccak256, Bytes, U256};
+
+    const ACCOUNT: Address = Address::with_last_byte(1);
+    const MISSING: Address = Address::with_last_byte(2);
+
+    fn pre_state() -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        db.insert_account_info(
+            ACCOUNT,
+            AccountInfo {
+                balance: U256::from(10),
+                nonce: 1,
+                code_hash: code.hash_slow(),
+                code: Some(code),
+            },
+        );
+        db.insert_account_storage(ACCOUNT, U256::from(1), U256::from(2))
+            .unwrap();
+        db
+    }
+
+    fn record() -> (CacheDB<EmptyDB>, ExecutionWitness) {
+        let mut recorder = WitnessRecorder::new(pre_state());
+        let info = recorder.basic(ACCOUNT).unwrap().unwrap();
+        recorder.code_by_hash(info.code_hash).unwrap();
+        recorder.storage(ACCOUNT, U256::from(1)).unwrap();
+        recorder.storage(ACCOUNT, U256::from(3)).unwrap();
+        assert_eq!(recorder.basic_ref(MISSING).unwrap(), None);
+        recorder.block_hash_ref(5).unwrap();
+        recorder.into_parts()
+    }
+
+    #[test]
+    fn records_reads() {
+        let (_, witness) = record();
+        let code_hash = witness.accounts[&ACCOUNT].as_ref().unwrap().code_hash;
+        assert_eq!(witness.accounts.len(), 2);
+        assert_eq!(witness.accounts[&MISSING], None);
+        assert!(witness.accounts[&ACCOUNT].as_ref().unwrap().code.is_none());
+        assert!(witness.codes.contains_key(&code_hash));
+        assert_eq!(witness.storage[&ACCOUNT].len(), 2);
+        assert_eq!(witness.storage[&ACCOUNT][&U256::from(3)], U256::ZERO);
+        assert_eq!(witness.block_hashes[&5], keccak256(5u64.to_string()));
+    }
+
+    #[test]
+    fn first_read_is_kept() {
+        let mut recorder = WitnessRecorder::new(pre_state());
+        recorder.storage(ACCOUNT, U256::from(1)).unwrap();
+        recorder
+            .db
+            .insert_account_storage(ACCOUNT, U256::from(1), U256::from(7))
+            .unwrap();
+        assert_eq!(
+            recorder.storage(ACCOUNT, U256::from(1)).unwrap(),
+            U256::from(7)
+        );
+        assert_eq!(
+            recorder.take_witness().storage[&ACCOUNT][&U256::from(1)],
+            U256::from(2)
+        );
+        assert!(recorder.witness().is_empty());
+    }
+
+    #[test]
+    fn witness_db_serves_recorded_reads() {
+        let (mut db, witness) = record();
+        let mut witness_db = WitnessDB::new(witness);
+
+        assert_eq!(
+            witness_db.basic(ACCOUNT).unwrap(),
+            db.basic(ACCOUNT).unwrap()
+        );
+        assert_eq!(witness_db.basic(MISSING), Ok(None));
+        assert_eq!(
+            witness_db.storage(ACCOUNT, U256::from(1)),
+            Ok(U256::from(2))
+        );
+        assert_eq!(witness_db.block_hash(5).unwrap(), db.block_hash(5).unwrap());
+    }
+
+    #[test]
+    fn witness_db_reports_missing_reads() {
+        let (_, witness) = record();
+        let mut db = WitnessDB::from(witness);
+        let other = Address::with_last_byte(3);
+
+        assert_eq!(db.basic(other), Err(MissingWitness::Account(other)));
+        assert_eq!(
+            db.storage(ACCOUNT, U256::from(4)),
+            Err(MissingWitness::Storage(ACCOUNT, U256::from(4)))
+        );
+        assert_eq!(
+            db.code_by_hash(B256::ZERO),
+            Err(MissingWitness::Code(B256::ZERO))
+        );
+        assert_eq!(db.block_hash(6), Err(MissingWitness::BlockHash(6)));
+        assert_eq!(
+            MissingWitness::BlockHash(6).to_string(),
+            "missing witness for block hash 6"
+        );
+    }
+
+    #[test]
+    fn extend_keeps_existing_values() {
+        let (_, mut witness) = record();
+        let mut other = ExecutionWitness::default();
+        other.record_storage(ACCOUNT, U256::from(1), U256::from(9));
+        other.record_block_hash(6, B256::ZERO);
+        witness.extend(other);
+        assert_eq!(witness.storage[&ACCOUNT][&U256::from(1)], U256::from(2));
+        assert_eq!(witness.block_hashes[&6], B256::ZERO);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let (_, witness) = record();
+        let json = serde_json::to_string(&witness).unwrap();
+        let decoded: ExecutionWitness = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, witness);
+    }
+}
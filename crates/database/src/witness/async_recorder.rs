@@ -0,0 +1,213 @@
+//! Recording and replay of async databases.
+use super::{ExecutionWitness, MissingWitness, WitnessDB};
+use database_interface::{async_db::DatabaseAsyncRef, DatabaseRef};
+use primitives::{Address, StorageKey, StorageValue, B256};
+use state::{AccountInfo, Bytecode};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Async database wrapper that records all responses into an [`ExecutionWitness`].
+///
+/// Wrap a remote database like `AlloyDB` with it, run the simulation and save the witness with
+/// [`ExecutionWitness::write_state_file`]. The saved fixture can be replayed without a provider
+/// by a [`WitnessDB`], which fails with [`MissingWitness`] on any key that was not recorded.
+///
+/// Only the first response for each key is recorded.
+#[derive(Debug, Default)]
+pub struct AsyncWitnessRecorder<DB> {
+    /// Inner database.
+    pub db: DB,
+    witness: Mutex<ExecutionWitness>,
+}
+
+impl<DB> AsyncWitnessRecorder<DB> {
+    /// Creates a new recorder wrapping the given database.
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            witness: Mutex::new(ExecutionWitness::default()),
+        }
+    }
+
+    /// Returns a copy of the witness recorded so far.
+    pub fn witness(&self) -> ExecutionWitness {
+        self.lock().clone()
+    }
+
+    /// Takes the recorded witness, leaving an empty one in its place.
+    pub fn take_witness(&mut self) -> ExecutionWitness {
+        core::mem::take(
+            self.witness
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
+    /// Consumes the recorder and returns the inner database and the recorded witness.
+    pub fn into_parts(self) -> (DB, ExecutionWitness) {
+        let witness = self
+            .witness
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        (self.db, witness)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ExecutionWitness> {
+        // Records are inserted atomically, the witness is valid even if a holder panicked.
+        self.witness.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<DB: DatabaseAsyncRef + Sync> DatabaseAsyncRef for AsyncWitnessRecorder<DB> {
+    type Error = DB::Error;
+
+    fn max_concurrent_requests(&self) -> usize {
+        self.db.max_concurrent_requests()
+    }
+
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_async_ref(address).await?;
+        self.lock().record_account(address, &info);
+        Ok(info)
+    }
+
+    async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        let code = self.db.code_by_hash_async_ref(code_hash).await?;
+        self.lock().record_code(code_hash, &code);
+        Ok(code)
+    }
+
+    async fn storage_async_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        let value = self.db.storage_async_ref(address, index).await?;
+        self.lock().record_storage(address, index, value);
+        Ok(value)
+    }
+
+    async fn block_hash_async_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let hash = self.db.block_hash_async_ref(number).await?;
+        self.lock().record_block_hash(number, hash);
+        Ok(hash)
+    }
+}
+
+/// Serves the recorded responses, so a [`WitnessDB`] can replace the recorded async database.
+impl DatabaseAsyncRef for WitnessDB {
+    type Error = MissingWitness;
+
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        DatabaseRef::basic_ref(self, address)
+    }
+
+    async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        DatabaseRef::code_by_hash_ref(self, code_hash)
+    }
+
+    async fn storage_async_ref(
+        &self,
+        address: Address,
+        index: StorageKey,
+    ) -> Result<StorageValue, Self::Error> {
+        DatabaseRef::storage_ref(self, address, index)
+    }
+
+    async fn block_hash_async_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        DatabaseRef::block_hash_ref(self, number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheDB, EmptyDB};
+    use database_interface::WrapDatabaseAsync;
+    use primitives::{Bytes, U256};
+
+    const ACCOUNT: Address = Address::with_last_byte(1);
+    const MISSING: Address = Address::with_last_byte(2);
+
+    /// In-process provider serving a [`CacheDB`].
+    struct MockProvider(CacheDB<EmptyDB>);
+
+    impl DatabaseAsyncRef for MockProvider {
+        type Error = core::convert::Infallible;
+
+        async fn basic_async_ref(
+            &self,
+            address: Address,
+        ) -> Result<Option<AccountInfo>, Self::Error> {
+            self.0.basic_ref(address)
+        }
+
+        async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.0.code_by_hash_ref(code_hash)
+        }
+
+        async fn storage_async_ref(
+            &self,
+            address: Address,
+            index: StorageKey,
+        ) -> Result<StorageValue, Self::Error> {
+            self.0.storage_ref(address, index)
+        }
+
+        async fn block_hash_async_ref(&self, number: u64) -> Result<B256, Self::Error> {
+            self.0.block_hash_ref(number)
+        }
+    }
+
+    fn provider() -> MockProvider {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        db.insert_account_info(
+            ACCOUNT,
+            AccountInfo {
+                balance: U256::from(10),
+                nonce: 1,
+                code_hash: code.hash_slow(),
+                code: Some(code),
+            },
+        );
+        db.insert_account_storage(ACCOUNT, U256::from(1), U256::from(2))
+            .unwrap();
+        MockProvider(db)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn record_and_replay_fixture() {
+        let recorder = WrapDatabaseAsync::new(AsyncWitnessRecorder::new(provider())).unwrap();
+        let info = recorder.basic_ref(ACCOUNT).unwrap().unwrap();
+        let code = recorder.code_by_hash_ref(info.code_hash).unwrap();
+        assert_eq!(
+            recorder.storage_ref(ACCOUNT, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(recorder.basic_ref(MISSING).unwrap(), None);
+        let block_hash = recorder.block_hash_ref(7).unwrap();
+
+        let mut fixture = Vec::new();
+        let (_, witness) = recorder.into_inner().into_parts();
+        witness.write_state_file(&mut fixture).unwrap();
+
+        // Replay without the provider.
+        let witness = ExecutionWitness::read_state_file(fixture.as_slice()).unwrap();
+        let replay = WrapDatabaseAsync::new(WitnessDB::new(witness)).unwrap();
+        assert_eq!(replay.basic_ref(ACCOUNT).unwrap(), Some(info.clone()));
+        assert_eq!(replay.code_by_hash_ref(info.code_hash).unwrap(), code);
+        assert_eq!(
+            replay.storage_ref(ACCOUNT, U256::from(1)).unwrap(),
+            U256::from(2)
+        );
+        assert_eq!(replay.basic_ref(MISSING).unwrap(), None);
+        assert_eq!(replay.block_hash_ref(7).unwrap(), block_hash);
+
+        // Unrecorded keys fail.
+        assert_eq!(
+            replay.storage_ref(ACCOUNT, U256::from(2)),
+            Err(MissingWitness::Storage(ACCOUNT, U256::from(2)))
+        );
+        assert_eq!(replay.block_hash_ref(8), Err(MissingWitness::BlockHash(8)));
+    }
+}
@@ -283,3 +283,136 @@ fn test_disable_balance_check() {
     let expected_balance = U256::ZERO;
     assert_eq!(returned_balance, expected_balance);
 }
+
+const WITNESS_BYTECODE: &[u8] = &[
+    // sstore(1, sload(0))
+    opcode::PUSH1,
+    0x00,
+    opcode::SLOAD,
+    opcode::PUSH1,
+    0x01,
+    opcode::SSTORE,
+    // pop(blockhash(9))
+    opcode::PUSH1,
+    0x09,
+    opcode::BLOCKHASH,
+    opcode::POP,
+    opcode::STOP,
+];
+
+#[test]
+fn test_execution_witness_replay() {
+    use revm::database::{MissingWitness, WitnessDB, WitnessRecorder};
+
+    let db = WitnessRecorder::new(BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+        WITNESS_BYTECODE.into(),
+    )));
+    let mut evm = Context::mainnet()
+        .modify_block_chained(|block| block.number = U256::from(10))
+        .with_db(db)
+        .build_mainnet();
+    let recorded = evm
+        .transact(TxEnv::builder_for_bench().build_fill())
+        .unwrap();
+
+    let (_, witness) = evm.ctx.journaled_state.database.into_parts();
+    assert!(witness.block_hashes.contains_key(&9));
+    assert!(witness.storage[&BENCH_TARGET].contains_key(&U256::ZERO));
+
+    // Replay with the witness only.
+    let mut evm = Context::mainnet()
+        .modify_block_chained(|block| block.number = U256::from(10))
+        .with_db(WitnessDB::new(witness))
+        .build_mainnet();
+    let replayed = evm
+        .transact(TxEnv::builder_for_bench().build_fill())
+        .unwrap();
+    assert_eq!(replayed, recorded);
+
+    // Any read outside of the witness fails.
+    let other = address!("0x0000000000000000000000000000000000001234");
+    let result = evm.transact(TxEnv::builder_for_bench().caller(other).build_fill());
+    assert!(matches!(
+        result,
+        Err(revm::context::result::EVMError::Database(MissingWitness::Account(address))) if address == other
+    ));
+}
+
+const OVERRIDE_BYTECODE: &[u8] = &[
+    // mstore(0, sload(0))
+    opcode::PUSH1,
+    0x00,
+    opcode::SLOAD,
+    opcode::PUSH0,
+    opcode::MSTORE,
+    // mstore(0x20, blockhash(9))
+    opcode::PUSH1,
+    0x09,
+    opcode::BLOCKHASH,
+    opcode::PUSH1,
+    0x20,
+    opcode::MSTORE,
+    // mstore(0x40, number())
+    opcode::NUMBER,
+    opcode::PUSH1,
+    0x40,
+    opcode::MSTORE,
+    // return(0, 0x60)
+    opcode::PUSH1,
+    0x60,
+    opcode::PUSH0,
+    opcode::RETURN,
+];
+
+#[test]
+fn test_state_and_block_overrides() {
+    use revm::{
+        context::BlockOverrides,
+        database::{AccountOverride, EmptyDB, StateOverride},
+        primitives::HashMap,
+    };
+
+    let block_hash = b256!("0x0000000000000000000000000000000000000000000000000000000000000009");
+    let block_overrides = BlockOverrides {
+        number: Some(U256::from(10)),
+        block_hash: HashMap::from_iter([(9, block_hash)]),
+        ..Default::default()
+    };
+    let db = StateOverride::with_accounts(
+        EmptyDB::default(),
+        [
+            (
+                BENCH_CALLER,
+                AccountOverride {
+                    balance: Some(U256::from(u64::MAX)),
+                    ..Default::default()
+                },
+            ),
+            (
+                BENCH_TARGET,
+                AccountOverride {
+                    code: Some(Bytecode::new_legacy(OVERRIDE_BYTECODE.into())),
+                    state_diff: Some(HashMap::from_iter([(U256::ZERO, U256::from(42))])),
+                    ..Default::default()
+                },
+            ),
+        ],
+    )
+    .unwrap()
+    .with_block_hashes(block_overrides.block_hash.clone());
+
+    let mut evm = Context::mainnet()
+        .modify_block_chained(|block| block_overrides.apply(block))
+        .with_db(db)
+        .build_mainnet();
+    let result = evm
+        .transact(TxEnv::builder_for_bench().build_fill())
+        .unwrap()
+        .result;
+    assert!(result.is_success());
+
+    let output = result.output().unwrap();
+    assert_eq!(U256::from_be_slice(&output[..0x20]), U256::from(42));
+    assert_eq!(&output[0x20..0x40], block_hash.as_slice());
+    assert_eq!(U256::from_be_slice(&output[0x40..]), U256::from(10));
+}
@@ -0,0 +1,234 @@
+//! Chain-agnostic fee hooks for L2 chains.
+//!
+//! Rollups usually charge fees on top of the regular gas fees (for example the L1 data fee),
+//! refund part of them and distribute them to fee vaults. [`L2FeeModel`] captures these hooks
+//! and [`L2Handler`] plugs them into the mainnet execution flow.
+use crate::{evm::FrameTr, post_execution, pre_execution, EvmTr, EvmTrError, FrameResult, Handler};
+use auto_impl::auto_impl;
+use context_interface::{result::HaltReason, ContextTr, Database, JournalTr};
+use interpreter::{interpreter_action::FrameInit, Gas};
+use primitives::U256;
+use state::EvmState;
+
+/// Fee hooks of an L2 chain.
+///
+/// All hooks are called for every transaction, implementations are responsible for skipping
+/// transactions that are not charged (e.g. deposits).
+#[auto_impl(&, Box)]
+pub trait L2FeeModel<CTX: ContextTr> {
+    /// Returns the cost charged upfront in addition to the gas fees.
+    ///
+    /// The cost is included in the caller balance check and deducted from the caller before
+    /// execution.
+    fn upfront_cost(&self, context: &mut CTX) -> Result<U256, <CTX::Db as Database>::Error>;
+
+    /// Returns the part of the upfront cost that is refunded to the caller after execution.
+    fn refund(&self, context: &mut CTX, gas: &Gas) -> U256 {
+        let _ = (context, gas);
+        U256::ZERO
+    }
+
+    /// Distributes the collected fees to the fee recipients.
+    ///
+    /// Called after the block beneficiary has been rewarded.
+    fn distribute_fees(
+        &self,
+        context: &mut CTX,
+        gas: &Gas,
+    ) -> Result<(), <CTX::Db as Database>::Error>;
+}
+
+/// Fee model that charges nothing on top of the mainnet fees.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoL2Fees;
+
+impl<CTX: ContextTr> L2FeeModel<CTX> for NoL2Fees {
+    fn upfront_cost(&self, _context: &mut CTX) -> Result<U256, <CTX::Db as Database>::Error> {
+        Ok(U256::ZERO)
+    }
+
+    fn distribute_fees(
+        &self,
+        _context: &mut CTX,
+        _gas: &Gas,
+    ) -> Result<(), <CTX::Db as Database>::Error> {
+        Ok(())
+    }
+}
+
+/// Mainnet handler extended with an [`L2FeeModel`].
+///
+/// Runs the mainnet execution flow and applies the fee model hooks when deducting the caller,
+/// reimbursing the caller and rewarding the beneficiary.
+#[derive(Debug, Clone)]
+pub struct L2Handler<EVM, ERROR, FRAME, FEE> {
+    /// Fee model of the chain.
+    pub fee_model: FEE,
+    /// Phantom data to hold the generic type parameters.
+    pub _phantom: core::marker::PhantomData<(EVM, ERROR, FRAME)>,
+}
+
+impl<EVM, ERROR, FRAME, FEE> L2Handler<EVM, ERROR, FRAME, FEE> {
+    /// Creates a new handler with the given fee model.
+    pub fn new(fee_model: FEE) -> Self {
+        Self {
+            fee_model,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<EVM, ERROR, FRAME, FEE: Default> Default for L2Handler<EVM, ERROR, FRAME, FEE> {
+    fn default() -> Self {
+        Self::new(FEE::default())
+    }
+}
+
+impl<EVM, ERROR, FRAME, FEE> Handler for L2Handler<EVM, ERROR, FRAME, FEE>
+where
+    EVM: EvmTr<Context: ContextTr<Journal: JournalTr<State = EvmState>>, Frame = FRAME>,
+    ERROR: EvmTrError<EVM>,
+    FRAME: FrameTr<FrameResult = FrameResult, FrameInit = FrameInit>,
+    FEE: L2FeeModel<EVM::Context>,
+{
+    type Evm = EVM;
+    type Error = ERROR;
+    type HaltReason = HaltReason;
+
+    fn validate_against_state_and_deduct_caller(
+        &self,
+        evm: &mut Self::Evm,
+    ) -> Result<(), Self::Error> {
+        let context = evm.ctx();
+        let additional_cost = self.fee_model.upfront_cost(context)?;
+        pre_execution::validate_against_state_and_deduct_caller_with_cost(context, additional_cost)
+    }
+
+    fn reimburse_caller(
+        &self,
+        evm: &mut Self::Evm,
+        exec_result: &mut FrameResult,
+    ) -> Result<(), Self::Error> {
+        let context = evm.ctx();
+        let additional_refund = self.fee_model.refund(context, exec_result.gas());
+        post_execution::reimburse_caller(context, exec_result.gas(), additional_refund)
+            .map_err(From::from)
+    }
+
+    fn reward_beneficiary(
+        &self,
+        evm: &mut Self::Evm,
+        exec_result: &mut FrameResult,
+    ) -> Result<(), Self::Error> {
+        let context = evm.ctx();
+        post_execution::reward_beneficiary(context, exec_result.gas())?;
+        self.fee_model
+            .distribute_fees(context, exec_result.gas())
+            .map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExecuteEvm, MainBuilder, MainContext, MainnetEvm};
+    use context::{result::EVMError, Context, ContextSetters, TxEnv};
+    use database::{CacheDB, EmptyDB};
+    use primitives::{address, Address, TxKind};
+    use state::AccountInfo;
+
+    const CALLER: Address = address!("0x0000000000000000000000000000000000001000");
+    const VAULT: Address = address!("0x0000000000000000000000000000000000002000");
+    const UPFRONT: u64 = 1_000;
+    const REFUND: u64 = 400;
+
+    #[derive(Debug, Default)]
+    struct FixedFee;
+
+    impl<CTX: ContextTr> L2FeeModel<CTX> for FixedFee {
+        fn upfront_cost(&self, _context: &mut CTX) -> Result<U256, <CTX::Db as Database>::Error> {
+            Ok(U256::from(UPFRONT))
+        }
+
+        fn refund(&self, _context: &mut CTX, _gas: &Gas) -> U256 {
+            U256::from(REFUND)
+        }
+
+        fn distribute_fees(
+            &self,
+            context: &mut CTX,
+            _gas: &Gas,
+        ) -> Result<(), <CTX::Db as Database>::Error> {
+            context
+                .journal_mut()
+                .balance_incr(VAULT, U256::from(UPFRONT - REFUND))
+        }
+    }
+
+    type TestEvm = MainnetEvm<crate::MainnetContext<CacheDB<EmptyDB>>>;
+    type TestHandler<FEE> =
+        L2Handler<TestEvm, EVMError<core::convert::Infallible>, crate::EthFrame, FEE>;
+
+    fn evm_with_caller(balance: u64) -> TestEvm {
+        let mut db = CacheDB::<EmptyDB>::default();
+        db.insert_account_info(
+            CALLER,
+            AccountInfo {
+                balance: U256::from(balance),
+                ..Default::default()
+            },
+        );
+        Context::mainnet()
+            .modify_block_chained(|b| b.basefee = 0)
+            .with_db(db)
+            .build_mainnet()
+    }
+
+    fn transfer() -> TxEnv {
+        TxEnv::builder()
+            .caller(CALLER)
+            .kind(TxKind::Call(Address::ZERO))
+            .gas_limit(21_000)
+            .gas_price(0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn fee_model_hooks_are_applied() {
+        let mut evm = evm_with_caller(10_000);
+        evm.ctx.set_tx(transfer());
+        let result = TestHandler::<FixedFee>::default().run(&mut evm).unwrap();
+        assert!(result.is_success());
+
+        let state = evm.finalize();
+        assert_eq!(
+            state[&CALLER].info.balance,
+            U256::from(10_000 - UPFRONT + REFUND)
+        );
+        assert_eq!(state[&VAULT].info.balance, U256::from(UPFRONT - REFUND));
+    }
+
+    #[test]
+    fn upfront_cost_is_part_of_balance_check() {
+        let mut evm = evm_with_caller(UPFRONT - 1);
+        evm.ctx.set_tx(transfer());
+        let result = TestHandler::<FixedFee>::default().run(&mut evm);
+        assert!(matches!(
+            result,
+            Err(EVMError::Transaction(
+                context::result::InvalidTransaction::LackOfFundForMaxFee { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn no_l2_fees_matches_mainnet() {
+        let mut evm = evm_with_caller(10_000);
+        evm.ctx.set_tx(transfer());
+        let result = TestHandler::<NoL2Fees>::default().run(&mut evm).unwrap();
+        assert!(result.is_success());
+        let state = evm.finalize();
+        assert_eq!(state[&CALLER].info.balance, U256::from(10_000));
+    }
+}
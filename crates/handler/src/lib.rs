@@ -13,6 +13,8 @@ pub mod api;
 pub mod evm;
 /// EVM execution logic and utilities.
 pub mod execution;
+/// Fee hooks for L2 chains built on top of the mainnet handler.
+pub mod fee_model;
 mod frame;
 mod frame_data;
 /// Handler implementation for orchestrating EVM execution.
@@ -34,6 +36,7 @@ pub mod validation;
 // Public exports
 pub use api::{ExecuteCommitEvm, ExecuteEvm};
 pub use evm::{EvmTr, FrameTr};
+pub use fee_model::{L2FeeModel, L2Handler, NoL2Fees};
 pub use frame::{return_create, ContextTrDbError, EthFrame};
 pub use frame_data::{CallFrame, CreateFrame, FrameData, FrameResult};
 pub use handler::{EvmTrError, Handler};
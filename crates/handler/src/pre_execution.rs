@@ -113,6 +113,23 @@ pub fn validate_against_state_and_deduct_caller<
     ERROR: From<InvalidTransaction> + From<<CTX::Db as Database>::Error>,
 >(
     context: &mut CTX,
+) -> Result<(), ERROR> {
+    validate_against_state_and_deduct_caller_with_cost(context, U256::ZERO)
+}
+
+/// Validates caller state and deducts transaction costs from the caller's balance,
+/// charging `additional_cost` on top of the gas and blob fees.
+///
+/// The additional cost is included in the max balance check and deducted together
+/// with the gas fees. It is used by chains that charge extra upfront fees,
+/// see [`L2FeeModel`][crate::L2FeeModel].
+#[inline]
+pub fn validate_against_state_and_deduct_caller_with_cost<
+    CTX: ContextTr,
+    ERROR: From<InvalidTransaction> + From<<CTX::Db as Database>::Error>,
+>(
+    context: &mut CTX,
+    additional_cost: U256,
 ) -> Result<(), ERROR> {
     let basefee = context.block().basefee() as u128;
     let blob_price = context.block().blob_gasprice().unwrap_or_default();
@@ -132,7 +149,7 @@ pub fn validate_against_state_and_deduct_caller<
         is_nonce_check_disabled,
     )?;
 
-    let max_balance_spending = tx.max_balance_spending()?;
+    let max_balance_spending = tx.max_balance_spending()?.saturating_add(additional_cost);
 
     // Check if account has enough balance for `gas_limit * max_fee`` and value transfer.
     // Transfer will be done inside `*_inner` functions.
@@ -149,7 +166,8 @@ pub fn validate_against_state_and_deduct_caller<
         .expect("effective balance is always smaller than max balance so it can't overflow");
 
     // subtracting max balance spending with value that is going to be deducted later in the call.
-    let gas_balance_spending = effective_balance_spending - tx.value();
+    let gas_balance_spending =
+        (effective_balance_spending - tx.value()).saturating_add(additional_cost);
 
     let mut new_balance = caller_account
         .info
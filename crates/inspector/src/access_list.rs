@@ -0,0 +1,371 @@
+//! AccessListInspector - Inspector that generates an EIP-2930 access list for a transaction.
+use crate::{InspectEvm, Inspector, InspectorEvmTr, JournalExt};
+use context::{
+    result::ExecutionResult,
+    transaction::{AccessList, AccessListItem},
+    Block, ContextTr, JournalTr, Transaction, TransactionType, TxEnv,
+};
+use interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, InterpreterTypes};
+use primitives::{Address, HashSet, TxKind, B256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Inspector that records every address and storage key touched by a transaction.
+///
+/// When the top-level call ends, the accounts and storage slots loaded by the transaction
+/// are read from [`JournalExt::evm_state`], so accounts and slots touched in reverted calls
+/// are included. Like geth, the sender, the recipient, the beneficiary and precompiles are excluded
+/// together with their storage keys, as well as contracts created by the transaction.
+///
+/// The result can be used as [`TxEnv::access_list`], see [`create_access_list`] to run the
+/// transaction until the access list stops changing.
+#[derive(Clone, Debug, Default)]
+pub struct AccessListInspector {
+    /// Additional addresses that are not included in the access list.
+    excluded: HashSet<Address>,
+    /// Touched addresses with touched storage keys.
+    touched: BTreeMap<Address, BTreeSet<B256>>,
+    /// Depth of the current frame.
+    depth: usize,
+}
+
+impl AccessListInspector {
+    /// Creates a new access list inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new access list inspector that additionally excludes the given addresses.
+    pub fn with_excluded(excluded: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            excluded: excluded.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the recorded access list.
+    pub fn access_list(&self) -> AccessList {
+        AccessList(
+            self.touched
+                .iter()
+                .map(|(address, keys)| AccessListItem {
+                    address: *address,
+                    storage_keys: keys.iter().copied().collect(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Clears the recorded access list.
+    pub fn clear(&mut self) {
+        self.touched.clear();
+        self.depth = 0;
+    }
+
+    fn frame_start(&mut self) {
+        if self.depth == 0 {
+            self.touched.clear();
+        }
+        self.depth += 1;
+    }
+
+    fn frame_end<CTX: ContextTr<Journal: JournalExt>>(&mut self, context: &mut CTX) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth != 0 {
+            return;
+        }
+
+        let caller = context.tx().caller();
+        let recipient = match context.tx().kind() {
+            TxKind::Call(address) => Some(address),
+            TxKind::Create => None,
+        };
+        let beneficiary = context.block().beneficiary();
+        let journal = context.journal_ref();
+        let precompiles = journal.precompile_addresses();
+        let state = journal.evm_state();
+
+        // Accounts touched by the current transaction have the latest transaction id.
+        let Some(transaction_id) = state.values().map(|acc| acc.transaction_id).max() else {
+            return;
+        };
+        for (address, account) in state {
+            if account.transaction_id != transaction_id
+                || account.is_created()
+                || self.excluded.contains(address)
+                || *address == caller
+                || Some(*address) == recipient
+                || *address == beneficiary
+                || precompiles.contains(address)
+            {
+                continue;
+            }
+            let keys = account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.transaction_id == transaction_id)
+                .map(|(key, _)| B256::from(*key));
+            self.touched.entry(*address).or_default().extend(keys);
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for AccessListInspector
+where
+    CTX: ContextTr<Journal: JournalExt>,
+    INTR: InterpreterTypes,
+{
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.frame_end(context);
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(context);
+    }
+}
+
+/// Result of [`create_access_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessListResult<HaltReasonTy> {
+    /// Generated access list.
+    pub access_list: AccessList,
+    /// Gas used by the transaction with the generated access list.
+    pub gas_used: u64,
+    /// Gas used by the transaction without an access list.
+    pub gas_used_without_access_list: u64,
+    /// Result of the transaction with the generated access list.
+    pub result: ExecutionResult<HaltReasonTy>,
+}
+
+/// Maximum number of runs with an access list in [`create_access_list`].
+pub const MAX_ACCESS_LIST_ITERATIONS: usize = 16;
+
+/// Error of [`create_access_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CreateAccessListError<E> {
+    /// Error of a transaction run.
+    Evm(E),
+    /// The access list still changed after [`MAX_ACCESS_LIST_ITERATIONS`] runs.
+    MaxIterationsReached,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for CreateAccessListError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Evm(error) => error.fmt(f),
+            Self::MaxIterationsReached => write!(
+                f,
+                "access list did not converge after {MAX_ACCESS_LIST_ITERATIONS} iterations"
+            ),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for CreateAccessListError<E> {}
+
+/// Generates the access list of the transaction, similar to `eth_createAccessList`.
+///
+/// The transaction is first run without an access list and then rerun with the access list
+/// recorded by the previous run until the list stops changing, at most
+/// [`MAX_ACCESS_LIST_ITERATIONS`] times. State changes of the runs are discarded. Legacy
+/// transactions are run as EIP-2930 transactions when the access list is set.
+pub fn create_access_list<EVM, HaltReasonTy>(
+    evm: &mut EVM,
+    mut tx: TxEnv,
+) -> Result<AccessListResult<HaltReasonTy>, CreateAccessListError<EVM::Error>>
+where
+    EVM: InspectEvm<Tx = TxEnv, ExecutionResult = ExecutionResult<HaltReasonTy>>
+        + InspectorEvmTr<Inspector = AccessListInspector>,
+{
+    tx.access_list = AccessList::default();
+    let result = run(evm, tx.clone()).map_err(CreateAccessListError::Evm)?;
+    let gas_used_without_access_list = result.gas_used();
+
+    if tx.tx_type == TransactionType::Legacy as u8 {
+        tx.tx_type = TransactionType::Eip2930 as u8;
+    }
+    let mut access_list = evm.inspector().access_list();
+    for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+        tx.access_list = access_list;
+        let result = run(evm, tx.clone()).map_err(CreateAccessListError::Evm)?;
+        let new_access_list = evm.inspector().access_list();
+        if new_access_list == tx.access_list {
+            return Ok(AccessListResult {
+                access_list: new_access_list,
+                gas_used: result.gas_used(),
+                gas_used_without_access_list,
+                result,
+            });
+        }
+        access_list = new_access_list;
+    }
+    Err(CreateAccessListError::MaxIterationsReached)
+}
+
+/// Runs the transaction with a cleared [`AccessListInspector`] and discards the state.
+fn run<EVM, HaltReasonTy>(
+    evm: &mut EVM,
+    tx: TxEnv,
+) -> Result<ExecutionResult<HaltReasonTy>, EVM::Error>
+where
+    EVM: InspectEvm<Tx = TxEnv, ExecutionResult = ExecutionResult<HaltReasonTy>>
+        + InspectorEvmTr<Inspector = AccessListInspector>,
+{
+    evm.inspector().clear();
+    let result = evm.inspect_one_tx(tx);
+    let _ = evm.finalize();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context::Context;
+    use database::{InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, U256};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLEE: Address = address!("0x1000000000000000000000000000000000000001");
+    const OTHER: Address = address!("0x2000000000000000000000000000000000000002");
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+
+    fn insert_code(db: &mut InMemoryDB, address: Address, code: Vec<u8>) {
+        let bytecode = Bytecode::new_raw(code.into());
+        db.insert_account_info(
+            address,
+            AccountInfo::default()
+                .with_code_hash(bytecode.hash_slow())
+                .with_code(bytecode),
+        );
+    }
+
+    fn db() -> InMemoryDB {
+        // Loads its own slot, the balance of `OTHER`, calls the identity precompile and `CALLEE`.
+        let mut code = vec![opcode::PUSH1, 0x02, opcode::SLOAD, opcode::POP];
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(OTHER.as_slice());
+        code.extend([opcode::BALANCE, opcode::POP]);
+        for address in [IDENTITY, CALLEE] {
+            code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+            code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+            code.push(opcode::PUSH20);
+            code.extend_from_slice(address.as_slice());
+            code.extend([opcode::GAS, opcode::STATICCALL, opcode::POP]);
+        }
+
+        // Loads slot one and reverts.
+        let callee = vec![
+            opcode::PUSH1,
+            0x01,
+            opcode::SLOAD,
+            opcode::POP,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::REVERT,
+        ];
+
+        let mut db = InMemoryDB::default();
+        insert_code(&mut db, BENCH_TARGET, code);
+        insert_code(&mut db, CALLEE, callee);
+        db
+    }
+
+    fn tx() -> TxEnv {
+        TxEnv::builder()
+            .caller(BENCH_CALLER)
+            .kind(TxKind::Call(BENCH_TARGET))
+            .gas_limit(100_000)
+            .build()
+            .unwrap()
+    }
+
+    fn expected() -> AccessList {
+        AccessList(vec![
+            AccessListItem {
+                address: CALLEE,
+                storage_keys: vec![B256::from(U256::from(1))],
+            },
+            AccessListItem {
+                address: OTHER,
+                storage_keys: vec![],
+            },
+        ])
+    }
+
+    #[test]
+    fn test_access_list_inspector() {
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(AccessListInspector::new());
+        let result = evm.inspect_one_tx(tx()).unwrap();
+        assert!(result.is_success());
+        assert_eq!(evm.inspector.access_list(), expected());
+    }
+
+    #[test]
+    fn test_access_list_inspector_excluded() {
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(AccessListInspector::with_excluded([OTHER]));
+        evm.inspect_one_tx(tx()).unwrap();
+        let mut expected = expected();
+        expected.0.retain(|item| item.address != OTHER);
+        assert_eq!(evm.inspector.access_list(), expected);
+    }
+
+    #[test]
+    fn test_create_access_list() {
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(AccessListInspector::new());
+        let result = create_access_list(&mut evm, tx()).unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(result.access_list, expected());
+        assert_eq!(result.gas_used, result.result.gas_used());
+        // The list never makes the transaction more expensive, `OTHER` and `CALLEE` save 300 gas.
+        assert!(result.gas_used <= result.gas_used_without_access_list);
+        assert_eq!(result.gas_used, result.gas_used_without_access_list - 300);
+    }
+
+    #[test]
+    fn test_create_access_list_max_iterations() {
+        // `CALLEE` reads the slot of the gas left, every access list changes the slot that is read.
+        let mut code = vec![opcode::PUSH1, 0x00, opcode::PUSH1, 0x00];
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend([opcode::GAS, opcode::STATICCALL, opcode::POP]);
+        let mut db = InMemoryDB::default();
+        insert_code(&mut db, BENCH_TARGET, code);
+        insert_code(
+            &mut db,
+            CALLEE,
+            vec![opcode::GAS, opcode::SLOAD, opcode::POP],
+        );
+
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(AccessListInspector::new());
+        assert_eq!(
+            create_access_list(&mut evm, tx()),
+            Err(CreateAccessListError::MaxIterationsReached)
+        );
+    }
+}
@@ -0,0 +1,658 @@
+//! Geth compatible `callTracer` [Inspector].
+//!
+//! Builds the nested call frame tree that geth's built-in `callTracer` emits.
+use crate::Inspector;
+use context::{result::RevertReason, ContextTr, CreateScheme, Transaction};
+use interpreter::{
+    CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, InstructionResult,
+    Interpreter, InterpreterTypes,
+};
+use primitives::{Address, Bytes, Log, B256, U256};
+use std::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Configuration of the [`CallTracer`], mirrors geth's `callTracer` config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct CallTracerConfig {
+    /// Only record the top-level call and skip all sub calls.
+    pub only_top_call: bool,
+    /// Record logs emitted in each call frame.
+    pub with_log: bool,
+}
+
+/// Type of the call frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum CallKind {
+    /// `CALL` opcode or call transaction.
+    Call,
+    /// `CALLCODE` opcode.
+    CallCode,
+    /// `DELEGATECALL` opcode.
+    DelegateCall,
+    /// `STATICCALL` opcode.
+    StaticCall,
+    /// `CREATE` opcode or create transaction.
+    Create,
+    /// `CREATE2` opcode.
+    Create2,
+    /// `SELFDESTRUCT` opcode.
+    SelfDestruct,
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall => Self::StaticCall,
+        }
+    }
+}
+
+impl From<CreateScheme> for CallKind {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create2 { .. } => Self::Create2,
+            CreateScheme::Create | CreateScheme::Custom { .. } => Self::Create,
+        }
+    }
+}
+
+impl CallKind {
+    /// Returns `true` if the frame is a `CREATE` or `CREATE2`.
+    #[inline]
+    pub const fn is_create(&self) -> bool {
+        matches!(self, Self::Create | Self::Create2)
+    }
+}
+
+/// Log emitted inside of a [`CallFrame`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallLog {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+    /// Number of sub calls made by the frame before this log was emitted.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub position: u64,
+}
+
+/// Single call frame of the geth `callTracer` output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallFrame {
+    /// Type of the call.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub kind: CallKind,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Gas available to the frame.
+    ///
+    /// For the top-level frame this is the gas limit of the transaction.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas: u64,
+    /// Gas used by the frame.
+    ///
+    /// For the top-level frame this includes the intrinsic gas but not the gas refund,
+    /// set it to [`ExecutionResult::gas_used`][context::result::ExecutionResult::gas_used]
+    /// to match the geth receipt value.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas_used: u64,
+    /// Target of the frame. Missing for failed creates.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub to: Option<Address>,
+    /// Call data or init code.
+    pub input: Bytes,
+    /// Return data, deployed code or revert data.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output: Option<Bytes>,
+    /// Error of the frame if it did not succeed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Decoded revert reason if the frame reverted with `Error(string)`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub revert_reason: Option<String>,
+    /// Sub calls made by the frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub calls: Vec<CallFrame>,
+    /// Logs emitted by the frame. Only recorded if [`CallTracerConfig::with_log`] is set.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub logs: Vec<CallLog>,
+    /// Value transferred. Missing for `DELEGATECALL` and `STATICCALL`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<U256>,
+}
+
+impl CallFrame {
+    /// Creates a new empty frame of the given kind.
+    pub fn new(kind: CallKind, from: Address, to: Option<Address>, gas: u64) -> Self {
+        Self {
+            kind,
+            from,
+            gas,
+            gas_used: 0,
+            to,
+            input: Bytes::new(),
+            output: None,
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+            logs: Vec::new(),
+            value: None,
+        }
+    }
+
+    /// Returns `true` if the frame did not succeed.
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Sets the output and error of the frame from its instruction result.
+    fn process_output(&mut self, result: InstructionResult, output: &Bytes) {
+        if result.is_ok() {
+            self.output = (!output.is_empty()).then(|| output.clone());
+            return;
+        }
+        self.error = Some(error_string(result));
+        if self.kind.is_create() {
+            self.to = None;
+        }
+        if !result.is_revert() || output.is_empty() {
+            return;
+        }
+        self.output = Some(output.clone());
+        self.revert_reason = decode_revert_reason(output);
+    }
+
+    /// Removes logs of this frame and all of its sub calls.
+    fn clear_logs(&mut self) {
+        self.logs.clear();
+        for call in &mut self.calls {
+            call.clear_logs();
+        }
+    }
+}
+
+/// Geth compatible `callTracer` [Inspector].
+///
+/// Records every call, create and selfdestruct made during the transaction as a
+/// tree of [`CallFrame`]s. The finished tree is available with [`CallTracer::frame`]
+/// once the top-level call has ended.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// Frames that have started but not yet ended.
+    stack: Vec<CallFrame>,
+    /// Number of nested frames that are not recorded because of [`CallTracerConfig::only_top_call`].
+    skipped_depth: usize,
+    /// Finished top-level frame.
+    frame: Option<CallFrame>,
+}
+
+impl CallTracer {
+    /// Creates a new call tracer with the default config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new call tracer with the given config.
+    pub fn with_config(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &CallTracerConfig {
+        &self.config
+    }
+
+    /// Returns the finished top-level frame.
+    pub fn frame(&self) -> Option<&CallFrame> {
+        self.frame.as_ref()
+    }
+
+    /// Takes the finished top-level frame, making the tracer ready for the next transaction.
+    pub fn take_frame(&mut self) -> Option<CallFrame> {
+        self.clear();
+        self.frame.take()
+    }
+
+    /// Consumes the tracer and returns the finished top-level frame.
+    pub fn into_frame(self) -> Option<CallFrame> {
+        self.frame
+    }
+
+    /// Serializes the finished top-level frame into a geth `callTracer` JSON value.
+    #[cfg(feature = "tracer")]
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.frame).unwrap_or_default()
+    }
+
+    /// Resets the in progress state of the tracer.
+    pub fn clear(&mut self) {
+        self.stack.clear();
+        self.skipped_depth = 0;
+    }
+
+    /// Returns `true` if a new frame should not be recorded.
+    fn skip_frame(&self) -> bool {
+        self.skipped_depth > 0 || (self.config.only_top_call && !self.stack.is_empty())
+    }
+
+    fn start_frame(&mut self, frame: CallFrame) {
+        if self.skip_frame() {
+            self.skipped_depth += 1;
+            return;
+        }
+        if self.stack.is_empty() {
+            self.frame = None;
+        }
+        self.stack.push(frame);
+    }
+
+    /// Pops the frame that ended. Returns `None` if the frame was not recorded.
+    fn end_frame(&mut self) -> Option<&mut CallFrame> {
+        if self.skipped_depth > 0 {
+            self.skipped_depth -= 1;
+            return None;
+        }
+        self.stack.last_mut()
+    }
+
+    /// Attaches the top of the stack to its parent or stores it as the finished frame.
+    fn finish_frame(&mut self) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if frame.is_error() {
+            frame.clear_logs();
+        }
+        match self.stack.last_mut() {
+            Some(parent) => parent.calls.push(frame),
+            None => self.frame = Some(frame),
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for CallTracer
+where
+    CTX: ContextTr,
+    INTR: InterpreterTypes,
+{
+    fn log(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX, log: Log) {
+        if !self.config.with_log || self.skipped_depth > 0 {
+            return;
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            return;
+        };
+        frame.logs.push(CallLog {
+            address: log.address,
+            topics: log.data.topics().to_vec(),
+            data: log.data.data,
+            position: frame.calls.len() as u64,
+        });
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let is_top = self.stack.is_empty() && self.skipped_depth == 0;
+        let gas = if is_top {
+            context.tx().gas_limit()
+        } else {
+            inputs.gas_limit
+        };
+        let mut frame = CallFrame::new(
+            inputs.scheme.into(),
+            inputs.caller,
+            Some(inputs.target_address),
+            gas,
+        );
+        if !self.skip_frame() {
+            frame.input = inputs.input.bytes(context);
+        }
+        frame.value = match inputs.scheme {
+            CallScheme::DelegateCall | CallScheme::StaticCall => None,
+            CallScheme::Call | CallScheme::CallCode => Some(inputs.call_value()),
+        };
+        self.start_frame(frame);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        let Some(frame) = self.end_frame() else {
+            return;
+        };
+        let result = *outcome.instruction_result();
+        frame.gas_used = gas_used(frame.gas, result, outcome.gas().remaining());
+        frame.process_output(result, outcome.output());
+        self.finish_frame();
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let is_top = self.stack.is_empty() && self.skipped_depth == 0;
+        let gas = if is_top {
+            context.tx().gas_limit()
+        } else {
+            inputs.gas_limit
+        };
+        let mut frame = CallFrame::new(inputs.scheme.into(), inputs.caller, None, gas);
+        frame.input = inputs.init_code.clone();
+        frame.value = Some(inputs.value);
+        self.start_frame(frame);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let Some(frame) = self.end_frame() else {
+            return;
+        };
+        let result = *outcome.instruction_result();
+        frame.to = outcome.address;
+        frame.gas_used = gas_used(frame.gas, result, outcome.gas().remaining());
+        frame.process_output(result, outcome.output());
+        self.finish_frame();
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.config.only_top_call || self.skipped_depth > 0 {
+            return;
+        }
+        let Some(parent) = self.stack.last_mut() else {
+            return;
+        };
+        let mut frame = CallFrame::new(CallKind::SelfDestruct, contract, Some(target), 0);
+        frame.value = Some(value);
+        parent.calls.push(frame);
+    }
+}
+
+/// Gas used by a frame, halted frames consume all of their gas.
+fn gas_used(gas: u64, result: InstructionResult, remaining: u64) -> u64 {
+    if result.is_ok_or_revert() {
+        gas.saturating_sub(remaining)
+    } else {
+        gas
+    }
+}
+
+/// Maps the instruction result to the error message geth uses.
+fn error_string(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "execution reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "invalid opcode",
+        InstructionResult::InvalidJump => "invalid jump destination",
+        InstructionResult::CallTooDeep => "max call depth exceeded",
+        InstructionResult::OutOfFunds => "insufficient balance for transfer",
+        InstructionResult::StackUnderflow => "stack underflow",
+        InstructionResult::StackOverflow => "stack limit reached 1024",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "write protection",
+        InstructionResult::OutOfOffset => "return data out of bounds",
+        InstructionResult::CreateCollision => "contract address collision",
+        InstructionResult::NonceOverflow => "nonce uint64 overflow",
+        InstructionResult::CreateContractSizeLimit => "max code size exceeded",
+        InstructionResult::CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        InstructionResult::CreateInitCodeSizeLimit => "max initcode size exceeded",
+        InstructionResult::PrecompileError => "precompile failed",
+        other => return format!("{other:?}"),
+    }
+    .to_string()
+}
+
+/// Decodes the revert reason like geth, from `Error(string)` or `Panic(uint256)`.
+fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    match RevertReason::decode(output) {
+        RevertReason::Error(reason) => Some(reason),
+        RevertReason::Panic(code) => Some(RevertReason::panic_reason(code)),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) mod serde_hex_u64 {
+    use std::{format, string::String};
+
+    pub(crate) fn serialize<S: serde::Serializer>(
+        n: &u64,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{n:#x}"))
+    }
+
+    pub(crate) fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<u64, D::Error> {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        let s = s.strip_prefix("0x").unwrap_or(&s);
+        u64::from_str_radix(s, 16).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLEE: Address = address!("0x1000000000000000000000000000000000000001");
+    const BENEFICIARY: Address = address!("0x2000000000000000000000000000000000000002");
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+
+    fn push_address(code: &mut Vec<u8>, address: Address) {
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(address.as_slice());
+    }
+
+    fn insert_code(db: &mut InMemoryDB, address: Address, code: Vec<u8>) {
+        let bytecode = Bytecode::new_raw(code.into());
+        db.insert_account_info(
+            address,
+            AccountInfo::default()
+                .with_code_hash(bytecode.hash_slow())
+                .with_code(bytecode),
+        );
+    }
+
+    fn run(config: CallTracerConfig) -> CallFrame {
+        // Emits a log, static calls the identity precompile, calls the reverting callee
+        // and selfdestructs to the beneficiary.
+        let mut code = vec![opcode::PUSH1, 0x00, opcode::PUSH1, 0x00, opcode::LOG0];
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+        push_address(&mut code, IDENTITY);
+        code.extend([opcode::GAS, opcode::STATICCALL, opcode::POP]);
+        code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+        code.extend([
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+        ]);
+        push_address(&mut code, CALLEE);
+        code.extend([opcode::GAS, opcode::CALL, opcode::POP]);
+        push_address(&mut code, BENEFICIARY);
+        code.push(opcode::SELFDESTRUCT);
+
+        // Emits a log and reverts with empty data.
+        let callee = vec![
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::LOG0,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::REVERT,
+        ];
+
+        let mut db = InMemoryDB::default();
+        insert_code(&mut db, BENCH_TARGET, code);
+        insert_code(&mut db, CALLEE, callee);
+
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(CallTracer::with_config(config));
+        let result = evm
+            .inspect_one_tx(
+                TxEnv::builder()
+                    .caller(BENCH_CALLER)
+                    .kind(TxKind::Call(BENCH_TARGET))
+                    .gas_limit(100_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(result.is_success());
+        evm.inspector.take_frame().unwrap()
+    }
+
+    #[test]
+    fn test_call_tracer() {
+        let frame = run(CallTracerConfig {
+            with_log: true,
+            ..Default::default()
+        });
+
+        assert_eq!(frame.kind, CallKind::Call);
+        assert_eq!(frame.from, BENCH_CALLER);
+        assert_eq!(frame.to, Some(BENCH_TARGET));
+        assert_eq!(frame.gas, 100_000);
+        assert_eq!(frame.value, Some(U256::ZERO));
+        assert!(frame.gas_used > 21_000);
+        assert_eq!(frame.error, None);
+        assert_eq!(frame.logs.len(), 1);
+        assert_eq!(frame.logs[0].position, 0);
+
+        let kinds: Vec<_> = frame.calls.iter().map(|call| call.kind).collect();
+        assert_eq!(
+            kinds,
+            [CallKind::StaticCall, CallKind::Call, CallKind::SelfDestruct]
+        );
+
+        let precompile = &frame.calls[0];
+        assert_eq!(precompile.to, Some(IDENTITY));
+        assert_eq!(precompile.value, None);
+        assert_eq!(precompile.error, None);
+
+        let reverted = &frame.calls[1];
+        assert_eq!(reverted.to, Some(CALLEE));
+        assert_eq!(reverted.error.as_deref(), Some("execution reverted"));
+        assert_eq!(reverted.output, None);
+        assert!(reverted.logs.is_empty());
+
+        let selfdestruct = &frame.calls[2];
+        assert_eq!(selfdestruct.from, BENCH_TARGET);
+        assert_eq!(selfdestruct.to, Some(BENEFICIARY));
+    }
+
+    #[test]
+    fn test_call_tracer_only_top_call() {
+        let frame = run(CallTracerConfig {
+            only_top_call: true,
+            with_log: true,
+        });
+        assert!(frame.calls.is_empty());
+        assert_eq!(frame.logs.len(), 1);
+    }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let output = primitives::hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000004"
+            "6f6f707300000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(decode_revert_reason(&output).as_deref(), Some("oops"));
+        assert_eq!(decode_revert_reason(&output[..40]), None);
+
+        let panic = primitives::hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(
+            decode_revert_reason(&panic).as_deref(),
+            Some("assert(false)")
+        );
+
+        let unknown_panic = primitives::hex!(
+            "4e487b71"
+            "0000000000000000000000000000000000000000000000000000000000000099"
+        );
+        assert_eq!(
+            decode_revert_reason(&unknown_panic).as_deref(),
+            Some("unknown panic code: 0x99")
+        );
+    }
+
+    #[cfg(feature = "tracer")]
+    #[test]
+    fn test_call_tracer_json() {
+        let frame = run(CallTracerConfig::default());
+        let json = serde_json::to_value(&frame).unwrap();
+        assert_eq!(json["type"], "CALL");
+        assert_eq!(json["gas"], "0x186a0");
+        assert_eq!(json["calls"][0]["type"], "STATICCALL");
+        assert!(json["calls"][0].get("value").is_none());
+        assert_eq!(json["calls"][1]["error"], "execution reverted");
+        assert_eq!(json["calls"][2]["type"], "SELFDESTRUCT");
+        assert!(json.get("logs").is_none());
+    }
+}
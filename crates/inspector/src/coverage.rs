@@ -0,0 +1,355 @@
+//! CoverageInspector - Inspector that records instruction and branch coverage of bytecodes.
+use crate::Inspector;
+use core::fmt::Write;
+use interpreter::{interpreter::EthInterpreter, interpreter_types::Jumps, Interpreter};
+use primitives::{HashMap, B256};
+use state::{bytecode::opcode, Bytecode};
+use std::{collections::BTreeMap, string::String, vec::Vec};
+
+/// Number of times a `JUMPI` jumped and fell through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BranchHits {
+    /// Number of times the condition was non zero and the jump was taken.
+    pub taken: u64,
+    /// Number of times the condition was zero and the execution continued.
+    pub not_taken: u64,
+}
+
+/// Coverage of a single bytecode.
+#[derive(Clone, Debug, Default)]
+pub struct BytecodeCoverage {
+    /// Executed bytecode.
+    pub bytecode: Bytecode,
+    /// Number of times each program counter was executed.
+    pub pc_hits: BTreeMap<usize, u64>,
+    /// Branch hits of each executed `JUMPI`.
+    pub branches: BTreeMap<usize, BranchHits>,
+}
+
+impl BytecodeCoverage {
+    /// Creates empty coverage of the bytecode.
+    pub fn new(bytecode: Bytecode) -> Self {
+        Self {
+            bytecode,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the program counters of all instructions with their opcodes.
+    ///
+    /// The padding added by the bytecode analysis is not included.
+    pub fn instructions(&self) -> Vec<(usize, u8)> {
+        let len = self.bytecode.original_byte_slice().len();
+        let mut iter = self.bytecode.iter_opcodes();
+        let mut instructions = Vec::new();
+        loop {
+            let pc = iter.position();
+            match iter.next() {
+                Some(op) if pc < len => instructions.push((pc, op)),
+                _ => break,
+            }
+        }
+        instructions
+    }
+
+    /// Returns the coverage report of the bytecode.
+    pub fn report(&self, code_hash: B256) -> CoverageReport {
+        let instructions = self.instructions();
+        let jumpis = instructions
+            .iter()
+            .filter(|(_, op)| *op == opcode::JUMPI)
+            .count();
+        let branches_hit = self
+            .branches
+            .values()
+            .map(|hits| (hits.taken != 0) as usize + (hits.not_taken != 0) as usize)
+            .sum();
+        CoverageReport {
+            code_hash,
+            instructions: instructions.len(),
+            instructions_hit: instructions
+                .iter()
+                .filter(|(pc, _)| self.pc_hits.contains_key(pc))
+                .count(),
+            branches: jumpis * 2,
+            branches_hit,
+        }
+    }
+
+    /// Writes the coverage as a LCOV record.
+    ///
+    /// The source file is the code hash and the line number of an instruction is its
+    /// program counter plus one, as LCOV line numbers start at one.
+    pub fn write_lcov(&self, code_hash: B256, out: &mut String) {
+        let instructions = self.instructions();
+        let report = self.report(code_hash);
+
+        let _ = writeln!(out, "TN:");
+        let _ = writeln!(out, "SF:{code_hash}");
+        for (pc, op) in &instructions {
+            if *op != opcode::JUMPI {
+                continue;
+            }
+            let line = pc + 1;
+            match self.branches.get(pc) {
+                Some(hits) => {
+                    let _ = writeln!(out, "BRDA:{line},0,0,{}", hits.taken);
+                    let _ = writeln!(out, "BRDA:{line},0,1,{}", hits.not_taken);
+                }
+                None => {
+                    let _ = writeln!(out, "BRDA:{line},0,0,-");
+                    let _ = writeln!(out, "BRDA:{line},0,1,-");
+                }
+            }
+        }
+        let _ = writeln!(out, "BRF:{}", report.branches);
+        let _ = writeln!(out, "BRH:{}", report.branches_hit);
+        for (pc, _) in &instructions {
+            let hits = self.pc_hits.get(pc).copied().unwrap_or_default();
+            let _ = writeln!(out, "DA:{},{hits}", pc + 1);
+        }
+        let _ = writeln!(out, "LF:{}", report.instructions);
+        let _ = writeln!(out, "LH:{}", report.instructions_hit);
+        let _ = writeln!(out, "end_of_record");
+    }
+}
+
+/// Instruction and branch coverage of a bytecode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CoverageReport {
+    /// Hash of the bytecode.
+    pub code_hash: B256,
+    /// Number of instructions in the bytecode.
+    pub instructions: usize,
+    /// Number of executed instructions.
+    pub instructions_hit: usize,
+    /// Number of branches, two for every `JUMPI`.
+    pub branches: usize,
+    /// Number of branches that were taken at least once.
+    pub branches_hit: usize,
+}
+
+impl CoverageReport {
+    /// Returns the ratio of executed instructions.
+    pub fn instruction_coverage(&self) -> f64 {
+        ratio(self.instructions_hit, self.instructions)
+    }
+
+    /// Returns the ratio of taken branches.
+    pub fn branch_coverage(&self) -> f64 {
+        ratio(self.branches_hit, self.branches)
+    }
+}
+
+fn ratio(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 1.0;
+    }
+    hit as f64 / total as f64
+}
+
+/// Inspector that records executed program counters and taken and not taken `JUMPI` branches
+/// per code hash.
+///
+/// Coverage of multiple transactions is accumulated until [`CoverageInspector::clear`] is called.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageInspector {
+    coverage: HashMap<B256, BytecodeCoverage>,
+}
+
+impl CoverageInspector {
+    /// Creates a new coverage inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the coverage of the bytecode with the given hash.
+    pub fn coverage(&self, code_hash: &B256) -> Option<&BytecodeCoverage> {
+        self.coverage.get(code_hash)
+    }
+
+    /// Returns the coverage of all executed bytecodes.
+    pub fn coverages(&self) -> &HashMap<B256, BytecodeCoverage> {
+        &self.coverage
+    }
+
+    /// Returns the coverage reports of all executed bytecodes, ordered by code hash.
+    pub fn reports(&self) -> Vec<CoverageReport> {
+        let mut reports: Vec<_> = self
+            .coverage
+            .iter()
+            .map(|(code_hash, coverage)| coverage.report(*code_hash))
+            .collect();
+        reports.sort_by_key(|report| report.code_hash);
+        reports
+    }
+
+    /// Returns the coverage of all executed bytecodes in the LCOV format.
+    ///
+    /// See [`BytecodeCoverage::write_lcov`] for the mapping of bytecode to LCOV records.
+    pub fn lcov(&self) -> String {
+        let mut code_hashes: Vec<_> = self.coverage.keys().collect();
+        code_hashes.sort();
+
+        let mut out = String::new();
+        for code_hash in code_hashes {
+            self.coverage[code_hash].write_lcov(*code_hash, &mut out);
+        }
+        out
+    }
+
+    /// Serializes the coverage reports with their coverage ratios into a JSON value.
+    #[cfg(feature = "tracer")]
+    pub fn json(&self) -> serde_json::Value {
+        self.reports()
+            .into_iter()
+            .map(|report| {
+                let mut value = serde_json::to_value(report).unwrap_or_default();
+                value["instructionCoverage"] = report.instruction_coverage().into();
+                value["branchCoverage"] = report.branch_coverage().into();
+                value
+            })
+            .collect()
+    }
+
+    /// Clears the recorded coverage.
+    pub fn clear(&mut self) {
+        self.coverage.clear();
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for CoverageInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let code_hash = interp.bytecode.get_or_calculate_hash();
+        self.coverage
+            .entry(code_hash)
+            .or_insert_with(|| BytecodeCoverage::new((*interp.bytecode).clone()));
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let code_hash = interp.bytecode.get_or_calculate_hash();
+        let Some(coverage) = self.coverage.get_mut(&code_hash) else {
+            return;
+        };
+        let pc = interp.bytecode.pc();
+        *coverage.pc_hits.entry(pc).or_default() += 1;
+
+        if interp.bytecode.opcode() == opcode::JUMPI {
+            // Stack is [.., condition, destination].
+            let stack = interp.stack.data();
+            let Some(condition) = stack.len().checked_sub(2).map(|i| stack[i]) else {
+                return;
+            };
+            let hits = coverage.branches.entry(pc).or_default();
+            if condition.is_zero() {
+                hits.not_taken += 1;
+            } else {
+                hits.taken += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{Bytes, TxKind};
+
+    fn run(condition: u8) -> (CoverageInspector, B256) {
+        let bytecode = Bytecode::new_raw(Bytes::from(vec![
+            opcode::PUSH1,
+            condition,
+            opcode::PUSH1,
+            0x07,
+            opcode::JUMPI,
+            opcode::STOP,
+            opcode::STOP,
+            opcode::JUMPDEST,
+            opcode::STOP,
+        ]));
+        let code_hash = bytecode.hash_slow();
+
+        let mut evm = Context::mainnet()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .build_mainnet_with_inspector(CoverageInspector::new());
+        evm.inspect_one_tx(
+            TxEnv::builder()
+                .caller(BENCH_CALLER)
+                .kind(TxKind::Call(BENCH_TARGET))
+                .build()
+                .unwrap(),
+        )
+        .unwrap();
+        (evm.inspector, code_hash)
+    }
+
+    #[test]
+    fn test_coverage_inspector() {
+        let (inspector, code_hash) = run(1);
+        let coverage = inspector.coverage(&code_hash).unwrap();
+        assert_eq!(
+            coverage.pc_hits.keys().copied().collect::<Vec<_>>(),
+            [0, 2, 4, 7, 8]
+        );
+        assert_eq!(
+            coverage.branches.get(&4),
+            Some(&BranchHits {
+                taken: 1,
+                not_taken: 0
+            })
+        );
+
+        let report = coverage.report(code_hash);
+        assert_eq!(report.instructions, 7);
+        assert_eq!(report.instructions_hit, 5);
+        assert_eq!(report.branches, 2);
+        assert_eq!(report.branches_hit, 1);
+        assert_eq!(report.branch_coverage(), 0.5);
+        assert_eq!(inspector.reports(), [report]);
+    }
+
+    #[test]
+    fn test_coverage_inspector_not_taken() {
+        let (inspector, code_hash) = run(0);
+        let coverage = inspector.coverage(&code_hash).unwrap();
+        assert_eq!(
+            coverage.pc_hits.keys().copied().collect::<Vec<_>>(),
+            [0, 2, 4, 5]
+        );
+        assert_eq!(coverage.branches[&4].not_taken, 1);
+    }
+
+    #[test]
+    fn test_coverage_lcov() {
+        let (inspector, code_hash) = run(1);
+        let lcov = inspector.lcov();
+        let lines: Vec<_> = lcov.lines().collect();
+        assert_eq!(lines[0], "TN:");
+        assert_eq!(lines[1], format!("SF:{code_hash}"));
+        assert_eq!(
+            &lines[2..6],
+            ["BRDA:5,0,0,1", "BRDA:5,0,1,0", "BRF:2", "BRH:1"]
+        );
+        assert_eq!(lines[6], "DA:1,1");
+        assert_eq!(lines[10], "DA:7,0");
+        assert_eq!(&lines[13..], ["LF:7", "LH:5", "end_of_record"]);
+    }
+
+    #[test]
+    #[cfg(feature = "tracer")]
+    fn test_coverage_json() {
+        let (inspector, code_hash) = run(1);
+        let json = inspector.json();
+        assert_eq!(json[0]["codeHash"], code_hash.to_string());
+        assert_eq!(json[0]["instructionsHit"], 5);
+        assert_eq!(json[0]["branchCoverage"], 0.5);
+    }
+}
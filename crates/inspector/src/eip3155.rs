@@ -1,6 +1,6 @@
 use crate::inspectors::GasInspector;
 use crate::Inspector;
-use context::{Cfg, ContextTr, JournalTr, Transaction};
+use context::{result::RevertReason, Cfg, ContextTr, JournalTr, Transaction};
 use interpreter::{
     interpreter_types::{Jumps, LoopControl, MemoryTr, StackTr},
     CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterResult,
@@ -115,6 +115,9 @@ struct Summary {
     /// Name of the fork rules used for execution
     #[serde(default, skip_serializing_if = "Option::is_none")]
     fork: Option<String>,
+    /// Decoded revert reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 impl TracerEip3155 {
@@ -202,6 +205,10 @@ impl TracerEip3155 {
             pass: result.is_ok(),
             time: None,
             fork: Some(spec.to_string()),
+            error: result
+                .result
+                .is_revert()
+                .then(|| RevertReason::decode(&result.output).to_string()),
         };
         let _ = self.write_value(&value);
     }
@@ -0,0 +1,496 @@
+//! GasProfiler - Inspector that attributes gas to contracts, program counters and opcodes.
+use crate::{inspectors::GasInspector, Inspector};
+use core::fmt::Write;
+use interpreter::{
+    interpreter::EthInterpreter, interpreter_types::Jumps, CallInputs, CallOutcome, CreateInputs,
+    CreateOutcome, Interpreter, InterpreterResult,
+};
+use primitives::{Address, HashMap, B256};
+use state::bytecode::opcode::OpCode;
+use std::{string::String, vec::Vec};
+
+/// Gas spent by a single opcode at a program counter of a contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OpcodeGas {
+    /// Address of the executed code.
+    pub address: Address,
+    /// Hash of the executed code.
+    pub code_hash: B256,
+    /// Program counter of the opcode.
+    pub pc: usize,
+    /// Opcode.
+    pub opcode: u8,
+    /// Name of the opcode.
+    pub name: String,
+    /// Number of times the opcode was executed.
+    pub count: u64,
+    /// Gas spent by the opcode, excluding the gas spent by the frames it created.
+    pub gas: u64,
+}
+
+/// Gas spent by the frames created at a call site.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallSiteGas {
+    /// Address of the code that made the call.
+    pub caller: Address,
+    /// Program counter of the call opcode.
+    pub pc: usize,
+    /// Call or create opcode.
+    pub opcode: u8,
+    /// Name of the call or create opcode.
+    pub name: String,
+    /// Address of the called or created contract.
+    pub target: Address,
+    /// Number of times the call was made.
+    pub count: u64,
+    /// Gas spent by the created frames, including their subcalls.
+    pub gas: u64,
+}
+
+/// Summary of the hottest opcodes and call sites.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct GasProfile {
+    /// Gas spent by the top-level frames.
+    pub total_gas: u64,
+    /// Opcodes ordered by spent gas.
+    pub opcodes: Vec<OpcodeGas>,
+    /// Call sites ordered by spent gas.
+    pub call_sites: Vec<CallSiteGas>,
+}
+
+/// Execution count and spent gas.
+#[derive(Clone, Copy, Debug, Default)]
+struct Counter {
+    count: u64,
+    gas: u64,
+}
+
+impl Counter {
+    fn add(&mut self, gas: u64) {
+        self.count += 1;
+        self.gas += gas;
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    /// Address of the executed code, the created address for creates.
+    address: Address,
+    code_hash: B256,
+    /// Interned call stack of the frame, `None` until the address is known.
+    path: Option<usize>,
+    /// Whether the frame is executed by the interpreter, false for precompiles.
+    interp: bool,
+    /// Tracks the gas of the frame.
+    gas: GasInspector,
+    /// Opcode whose cost is known at the next step or at the end of the frame.
+    pending: Option<(usize, u8)>,
+    /// Gas spent by the frames created by the pending opcode.
+    child_gas: u64,
+}
+
+/// Inspector that attributes gas to (contract address, code hash, pc, opcode) and
+/// folds it across the call stack.
+///
+/// The cost of an opcode is the gas difference between it and the next executed opcode of
+/// the same frame, minus the gas spent by the frames it created, so the cost of a `CALL`
+/// contains only the call overhead and the callee gas is attributed to the callee. Code run by
+/// `DELEGATECALL` and `CALLCODE` is attributed to the address that holds the code.
+///
+/// Gas of multiple transactions is accumulated until [`GasProfiler::clear`] is called.
+/// Intrinsic gas and refunds are not attributed.
+#[derive(Clone, Debug, Default)]
+pub struct GasProfiler {
+    stack: Vec<Frame>,
+    opcodes: HashMap<(Address, B256, usize, u8), Counter>,
+    call_sites: HashMap<(Address, usize, u8, Address), Counter>,
+    /// Gas folded by call stack and opcode, `None` for frames that are not interpreted.
+    folded: HashMap<(usize, Option<(usize, u8)>), u64>,
+    /// Call stacks in collapsed format.
+    paths: Vec<String>,
+    path_ids: HashMap<String, usize>,
+    total_gas: u64,
+}
+
+impl GasProfiler {
+    /// Creates a new gas profiler.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the gas spent by the top-level frames.
+    pub fn total_gas(&self) -> u64 {
+        self.total_gas
+    }
+
+    /// Returns the gas spent per opcode, ordered by spent gas.
+    pub fn opcodes(&self) -> Vec<OpcodeGas> {
+        let mut opcodes: Vec<_> = self
+            .opcodes
+            .iter()
+            .map(|(&(address, code_hash, pc, opcode), counter)| OpcodeGas {
+                address,
+                code_hash,
+                pc,
+                opcode,
+                name: OpCode::name_by_op(opcode).into(),
+                count: counter.count,
+                gas: counter.gas,
+            })
+            .collect();
+        opcodes.sort_by(|a, b| {
+            (b.gas, a.address, a.code_hash, a.pc).cmp(&(a.gas, b.address, b.code_hash, b.pc))
+        });
+        opcodes
+    }
+
+    /// Returns the gas spent per call site, ordered by spent gas.
+    pub fn call_sites(&self) -> Vec<CallSiteGas> {
+        let mut call_sites: Vec<_> = self
+            .call_sites
+            .iter()
+            .map(|(&(caller, pc, opcode, target), counter)| CallSiteGas {
+                caller,
+                pc,
+                opcode,
+                name: OpCode::name_by_op(opcode).into(),
+                target,
+                count: counter.count,
+                gas: counter.gas,
+            })
+            .collect();
+        call_sites.sort_by(|a, b| {
+            (b.gas, a.caller, a.pc, a.target).cmp(&(a.gas, b.caller, b.pc, b.target))
+        });
+        call_sites
+    }
+
+    /// Returns the `limit` hottest opcodes and call sites.
+    pub fn summary(&self, limit: usize) -> GasProfile {
+        let mut opcodes = self.opcodes();
+        opcodes.truncate(limit);
+        let mut call_sites = self.call_sites();
+        call_sites.truncate(limit);
+        GasProfile {
+            total_gas: self.total_gas,
+            opcodes,
+            call_sites,
+        }
+    }
+
+    /// Serializes the `limit` hottest opcodes and call sites into a JSON value.
+    #[cfg(feature = "tracer")]
+    pub fn json(&self, limit: usize) -> serde_json::Value {
+        serde_json::to_value(self.summary(limit)).unwrap_or_default()
+    }
+
+    /// Returns the gas in the collapsed stack format used by flamegraph tools.
+    ///
+    /// Every line contains the `;` separated addresses of the call stack, followed by the
+    /// opcode and its program counter and the spent gas, e.g. `0x..01;0x..02;SLOAD@12 2100`.
+    pub fn collapsed_stacks(&self) -> String {
+        let mut lines: Vec<_> = self
+            .folded
+            .iter()
+            .map(|(&(path, leaf), gas)| {
+                let mut line = self.paths[path].clone();
+                if let Some((pc, opcode)) = leaf {
+                    let _ = write!(line, ";{}@{pc}", OpCode::name_by_op(opcode));
+                }
+                let _ = write!(line, " {gas}");
+                line
+            })
+            .collect();
+        lines.sort();
+
+        let mut out = String::new();
+        for line in lines {
+            out.push_str(&line);
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Clears all the recorded gas.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn intern_path(&mut self, address: Address) -> usize {
+        let mut path = self
+            .stack
+            .iter()
+            .rev()
+            .find_map(|frame| frame.path)
+            .map(|parent| self.paths[parent].clone() + ";")
+            .unwrap_or_default();
+        let _ = write!(path, "{address}");
+        if let Some(id) = self.path_ids.get(&path) {
+            return *id;
+        }
+        let id = self.paths.len();
+        self.paths.push(path.clone());
+        self.path_ids.insert(path, id);
+        id
+    }
+
+    fn record(&mut self, frame: &Frame, pc: usize, opcode: u8, gas: u64) {
+        self.opcodes
+            .entry((frame.address, frame.code_hash, pc, opcode))
+            .or_default()
+            .add(gas);
+        if let Some(path) = frame.path {
+            *self.folded.entry((path, Some((pc, opcode)))).or_default() += gas;
+        }
+    }
+
+    fn frame_start(&mut self, address: Address, is_create: bool) {
+        let path = (!is_create).then(|| self.intern_path(address));
+        self.stack.push(Frame {
+            address,
+            code_hash: B256::ZERO,
+            path,
+            interp: false,
+            gas: GasInspector::new(),
+            pending: None,
+            child_gas: 0,
+        });
+    }
+
+    fn frame_end(&mut self, result: &InterpreterResult, target: Address) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        // Halted frames do not return the remaining gas to the parent.
+        let ok_or_revert = result.result.is_ok_or_revert();
+        let used = if ok_or_revert {
+            result.gas.spent()
+        } else {
+            result.gas.limit()
+        };
+
+        if frame.interp {
+            if let Some((pc, opcode)) = frame.pending {
+                let remaining = if ok_or_revert {
+                    result.gas.remaining()
+                } else {
+                    0
+                };
+                let gas = frame
+                    .gas
+                    .gas_remaining()
+                    .saturating_sub(remaining)
+                    .saturating_sub(frame.child_gas);
+                self.record(&frame, pc, opcode, gas);
+            }
+        } else if used != 0 {
+            let path = frame
+                .path
+                .unwrap_or_else(|| self.intern_path(frame.address));
+            *self.folded.entry((path, None)).or_default() += used;
+        }
+
+        match self.stack.last_mut() {
+            Some(parent) => {
+                parent.child_gas += used;
+                if let Some((pc, opcode)) = parent.pending {
+                    let key = (parent.address, pc, opcode, target);
+                    self.call_sites.entry(key).or_default().add(used);
+                }
+            }
+            None => self.total_gas += used,
+        }
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for GasProfiler {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if frame.path.is_none() {
+            frame.address = interp.input.target_address;
+            frame.path = Some(self.intern_path(frame.address));
+        }
+        frame.code_hash = interp.bytecode.get_or_calculate_hash();
+        frame.interp = true;
+        frame.gas.initialize_interp(&interp.gas);
+        self.stack.push(frame);
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(mut frame) = self.stack.pop() else {
+            return;
+        };
+        if let Some((pc, opcode)) = frame.pending {
+            let gas = frame
+                .gas
+                .gas_remaining()
+                .saturating_sub(interp.gas.remaining())
+                .saturating_sub(frame.child_gas);
+            self.record(&frame, pc, opcode, gas);
+        }
+        frame.pending = Some((interp.bytecode.pc(), interp.bytecode.opcode()));
+        frame.child_gas = 0;
+        frame.gas.step(&interp.gas);
+        self.stack.push(frame);
+    }
+
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start(inputs.bytecode_address, false);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.frame_end(&outcome.result, inputs.bytecode_address);
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start(Address::ZERO, true);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let target = outcome.address.unwrap_or_default();
+        if let Some(frame) = self.stack.last_mut() {
+            if frame.path.is_none() {
+                frame.address = target;
+            }
+        }
+        self.frame_end(&outcome.result, target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    const CALLEE: Address = address!("0x1000000000000000000000000000000000000001");
+    const IDENTITY: Address = address!("0x0000000000000000000000000000000000000004");
+
+    fn insert_code(db: &mut InMemoryDB, address: Address, code: Vec<u8>) {
+        let bytecode = Bytecode::new_raw(code.into());
+        db.insert_account_info(
+            address,
+            AccountInfo::default()
+                .with_code_hash(bytecode.hash_slow())
+                .with_code(bytecode),
+        );
+    }
+
+    fn run() -> GasProfiler {
+        // Stores to slot zero, calls the identity precompile and `CALLEE`.
+        let mut code = vec![opcode::PUSH1, 0x01, opcode::PUSH1, 0x00, opcode::SSTORE];
+        for address in [IDENTITY, CALLEE] {
+            code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+            code.extend([opcode::PUSH1, 0x00, opcode::PUSH1, 0x00]);
+            code.push(opcode::PUSH20);
+            code.extend_from_slice(address.as_slice());
+            code.extend([opcode::GAS, opcode::STATICCALL, opcode::POP]);
+        }
+
+        // Loads slot one.
+        let callee = vec![opcode::PUSH1, 0x01, opcode::SLOAD, opcode::STOP];
+
+        let mut db = InMemoryDB::default();
+        insert_code(&mut db, BENCH_TARGET, code);
+        insert_code(&mut db, CALLEE, callee);
+
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(GasProfiler::new());
+        let result = evm
+            .inspect_one_tx(
+                TxEnv::builder()
+                    .caller(BENCH_CALLER)
+                    .kind(TxKind::Call(BENCH_TARGET))
+                    .gas_limit(100_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(result.is_success());
+        evm.inspector
+    }
+
+    #[test]
+    fn test_gas_profiler() {
+        let profiler = run();
+
+        // All gas spent by the frames is attributed.
+        let attributed: u64 = profiler.folded.values().sum();
+        assert_eq!(attributed, profiler.total_gas());
+        // Intrinsic gas is not attributed.
+        assert!(profiler.total_gas() > 22_100 && profiler.total_gas() < 40_000);
+
+        let opcodes = profiler.opcodes();
+        assert_eq!(opcodes[0].address, BENCH_TARGET);
+        assert_eq!(opcodes[0].name, "SSTORE");
+        assert_eq!(opcodes[0].pc, 4);
+        assert_eq!(opcodes[0].gas, 22_100);
+
+        let sload = opcodes
+            .iter()
+            .find(|op| op.opcode == opcode::SLOAD)
+            .unwrap();
+        assert_eq!(sload.address, CALLEE);
+        assert_eq!(sload.pc, 2);
+        assert_eq!(sload.count, 1);
+        assert_eq!(sload.gas, 2_100);
+
+        // Call overhead does not include the callee gas, precompiles are warm.
+        let calls: Vec<_> = opcodes
+            .iter()
+            .filter(|op| op.opcode == opcode::STATICCALL)
+            .map(|op| op.gas)
+            .collect();
+        assert_eq!(calls, [2_600, 100]);
+
+        let call_sites = profiler.call_sites();
+        assert_eq!(call_sites.len(), 2);
+        assert_eq!(call_sites[0].caller, BENCH_TARGET);
+        assert_eq!(call_sites[0].target, CALLEE);
+        assert_eq!(call_sites[0].name, "STATICCALL");
+        assert_eq!(call_sites[0].gas, 2_103);
+        assert_eq!(call_sites[1].target, IDENTITY);
+        assert_eq!(call_sites[1].gas, 15);
+    }
+
+    #[test]
+    fn test_gas_profiler_collapsed_stacks() {
+        let profiler = run();
+        let collapsed = profiler.collapsed_stacks();
+        let lines: Vec<_> = collapsed.lines().collect();
+        assert!(lines.contains(&format!("{BENCH_TARGET};SSTORE@4 22100").as_str()));
+        assert!(lines.contains(&format!("{BENCH_TARGET};{CALLEE};SLOAD@2 2100").as_str()));
+        assert!(lines.contains(&format!("{BENCH_TARGET};{IDENTITY} 15").as_str()));
+    }
+
+    #[test]
+    #[cfg(feature = "tracer")]
+    fn test_gas_profiler_json() {
+        let profiler = run();
+        let json = profiler.json(1);
+        assert_eq!(json["totalGas"], profiler.total_gas());
+        assert_eq!(json["opcodes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["opcodes"][0]["name"], "SSTORE");
+        assert_eq!(json["callSites"][0]["target"], format!("{CALLEE:?}"));
+    }
+}
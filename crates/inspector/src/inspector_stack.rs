@@ -0,0 +1,339 @@
+//! InspectorStack - Inspector that dispatches to a runtime list of inspectors.
+use crate::Inspector;
+use core::fmt;
+use interpreter::{
+    interpreter::EthInterpreter, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    InterpreterTypes,
+};
+use primitives::{Address, Log, U256};
+use std::{boxed::Box, vec::Vec};
+
+/// Identifier of an inspector inside of [`InspectorStack`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InspectorId(usize);
+
+struct Entry<'a, CTX, INTR: InterpreterTypes> {
+    id: InspectorId,
+    priority: i32,
+    enabled: bool,
+    inspector: Box<dyn Inspector<CTX, INTR> + 'a>,
+}
+
+/// Inspector that holds a list of inspectors that can be added, removed, enabled and
+/// disabled at runtime.
+///
+/// Unlike the `(L, R)` tuple and [`either::Either`] implementations, the set of inspectors
+/// does not need to be known at compile time, which allows selecting tracers per request.
+/// Inspectors are boxed and can be borrowed, so results can be read after the execution:
+///
+/// ```ignore
+/// let mut tracer = CallTracer::default();
+/// let mut stack = InspectorStack::new();
+/// stack.push(&mut tracer);
+/// ```
+///
+/// Hooks of the enabled inspectors are called in order of descending priority, inspectors
+/// with the same priority are called in insertion order.
+///
+/// If multiple inspectors return an overriding outcome from [`Inspector::call`] or
+/// [`Inspector::create`], the outcome of the inspector that is called first wins and the other
+/// outcomes are discarded. In contrast to the tuple implementation, all enabled inspectors are
+/// still called so that every inspector sees the matching `call_end` or `create_end`.
+///
+/// Inspectors that track frames expect balanced calls, so enabling and disabling should be done
+/// between transactions.
+pub struct InspectorStack<'a, CTX, INTR: InterpreterTypes = EthInterpreter> {
+    inspectors: Vec<Entry<'a, CTX, INTR>>,
+    next_id: usize,
+}
+
+impl<CTX, INTR: InterpreterTypes> Default for InspectorStack<'_, CTX, INTR> {
+    fn default() -> Self {
+        Self {
+            inspectors: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> fmt::Debug for InspectorStack<'_, CTX, INTR> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.inspectors
+                    .iter()
+                    .map(|entry| (entry.id, entry.priority, entry.enabled)),
+            )
+            .finish()
+    }
+}
+
+impl<'a, CTX, INTR: InterpreterTypes> InspectorStack<'a, CTX, INTR> {
+    /// Creates an empty inspector stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the enabled inspector with the default priority of zero.
+    pub fn push(&mut self, inspector: impl Inspector<CTX, INTR> + 'a) -> InspectorId {
+        self.insert(inspector, 0)
+    }
+
+    /// Adds the enabled inspector with the given priority.
+    ///
+    /// Inspectors with higher priority are called first.
+    pub fn insert(
+        &mut self,
+        inspector: impl Inspector<CTX, INTR> + 'a,
+        priority: i32,
+    ) -> InspectorId {
+        let id = InspectorId(self.next_id);
+        self.next_id += 1;
+        let index = self
+            .inspectors
+            .iter()
+            .position(|entry| entry.priority < priority)
+            .unwrap_or(self.inspectors.len());
+        self.inspectors.insert(
+            index,
+            Entry {
+                id,
+                priority,
+                enabled: true,
+                inspector: Box::new(inspector),
+            },
+        );
+        id
+    }
+
+    /// Removes the inspector, returns `None` if it is not in the stack.
+    pub fn remove(&mut self, id: InspectorId) -> Option<Box<dyn Inspector<CTX, INTR> + 'a>> {
+        let index = self.inspectors.iter().position(|entry| entry.id == id)?;
+        Some(self.inspectors.remove(index).inspector)
+    }
+
+    /// Enables or disables the inspector, returns `false` if it is not in the stack.
+    pub fn set_enabled(&mut self, id: InspectorId, enabled: bool) -> bool {
+        match self.inspectors.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns whether the inspector is enabled, `None` if it is not in the stack.
+    pub fn is_enabled(&self, id: InspectorId) -> Option<bool> {
+        self.inspectors
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.enabled)
+    }
+
+    /// Returns the number of inspectors in the stack.
+    pub fn len(&self) -> usize {
+        self.inspectors.len()
+    }
+
+    /// Returns `true` if there are no inspectors in the stack.
+    pub fn is_empty(&self) -> bool {
+        self.inspectors.is_empty()
+    }
+
+    /// Removes all inspectors.
+    pub fn clear(&mut self) {
+        self.inspectors.clear();
+    }
+
+    #[inline]
+    fn enabled(&mut self) -> impl Iterator<Item = &mut (dyn Inspector<CTX, INTR> + 'a)> {
+        self.inspectors
+            .iter_mut()
+            .filter(|entry| entry.enabled)
+            .map(|entry| &mut *entry.inspector)
+    }
+}
+
+impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for InspectorStack<'_, CTX, INTR> {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX) {
+        for inspector in self.enabled() {
+            inspector.initialize_interp(interp, context);
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX) {
+        for inspector in self.enabled() {
+            inspector.step(interp, context);
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX) {
+        for inspector in self.enabled() {
+            inspector.step_end(interp, context);
+        }
+    }
+
+    fn log(&mut self, interp: &mut Interpreter<INTR>, context: &mut CTX, log: Log) {
+        for inspector in self.enabled() {
+            inspector.log(interp, context, log.clone());
+        }
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let mut outcome = None;
+        for inspector in self.enabled() {
+            if let Some(output) = inspector.call(context, inputs) {
+                outcome.get_or_insert(output);
+            }
+        }
+        outcome
+    }
+
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        for inspector in self.enabled() {
+            inspector.call_end(context, inputs, outcome);
+        }
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        let mut outcome = None;
+        for inspector in self.enabled() {
+            if let Some(output) = inspector.create(context, inputs) {
+                outcome.get_or_insert(output);
+            }
+        }
+        outcome
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        for inspector in self.enabled() {
+            inspector.create_end(context, inputs, outcome);
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        for inspector in self.enabled() {
+            inspector.selfdestruct(contract, target, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CountInspector, InspectEvm};
+    use context::{Context, TxEnv};
+    use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use interpreter::{Gas, InstructionResult, InterpreterResult};
+    use primitives::{Bytes, TxKind};
+    use state::bytecode::{opcode, Bytecode};
+
+    /// Overrides every call with a revert that returns `output`.
+    #[derive(Default)]
+    struct OverrideInspector {
+        output: u8,
+        calls: usize,
+        call_ends: usize,
+    }
+
+    impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for OverrideInspector {
+        fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+            self.calls += 1;
+            Some(CallOutcome::new(
+                InterpreterResult::new(
+                    InstructionResult::Revert,
+                    Bytes::from(vec![self.output]),
+                    Gas::new(inputs.gas_limit),
+                ),
+                inputs.return_memory_offset.clone(),
+            ))
+        }
+
+        fn call_end(&mut self, _c: &mut CTX, _i: &CallInputs, _outcome: &mut CallOutcome) {
+            self.call_ends += 1;
+        }
+    }
+
+    fn tx() -> TxEnv {
+        TxEnv::builder()
+            .caller(BENCH_CALLER)
+            .kind(TxKind::Call(BENCH_TARGET))
+            .build()
+            .unwrap()
+    }
+
+    fn db() -> BenchmarkDB {
+        BenchmarkDB::new_bytecode(Bytecode::new_legacy(
+            [opcode::PUSH1, 0x01, opcode::POP, opcode::STOP].into(),
+        ))
+    }
+
+    #[test]
+    fn test_inspector_stack_enable_disable() {
+        let mut first = CountInspector::new();
+        let mut second = CountInspector::new();
+
+        let mut stack = InspectorStack::new();
+        let first_id = stack.push(&mut first);
+        let second_id = stack.push(&mut second);
+        assert_eq!(stack.len(), 2);
+        assert!(stack.set_enabled(second_id, false));
+        assert_eq!(stack.is_enabled(second_id), Some(false));
+
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(stack);
+        evm.inspect_tx(tx()).unwrap();
+
+        evm.inspector.set_enabled(second_id, true);
+        evm.inspector.remove(first_id).unwrap();
+        assert_eq!(evm.inspector.is_enabled(first_id), None);
+        evm.inspect_one_tx(tx()).unwrap();
+        drop(evm);
+
+        assert_eq!(first.step_count(), 3);
+        assert_eq!(first.call_count(), 1);
+        assert_eq!(second.step_count(), 3);
+        assert_eq!(second.call_end_count(), 1);
+    }
+
+    #[test]
+    fn test_inspector_stack_override_priority() {
+        let mut low = OverrideInspector {
+            output: 1,
+            ..Default::default()
+        };
+        let mut high = OverrideInspector {
+            output: 2,
+            ..Default::default()
+        };
+        let mut count = CountInspector::new();
+
+        let mut stack = InspectorStack::new();
+        stack.push(&mut low);
+        stack.push(&mut count);
+        stack.insert(&mut high, 1);
+
+        let mut evm = Context::mainnet()
+            .with_db(db())
+            .build_mainnet_with_inspector(stack);
+        let result = evm.inspect_one_tx(tx()).unwrap();
+        drop(evm);
+
+        // Highest priority wins, the frame is not executed.
+        assert_eq!(result.output(), Some(&Bytes::from(vec![2])));
+        assert_eq!(count.step_count(), 0);
+        // All inspectors see balanced calls.
+        for inspector in [&low, &high] {
+            assert_eq!((inspector.calls, inspector.call_ends), (1, 1));
+        }
+        assert_eq!((count.call_count(), count.call_end_count()), (1, 1));
+    }
+}
@@ -800,4 +800,79 @@ mod tests {
 
         assert!(evm.inspector.get_step_count() > 0);
     }
+
+    /// Writer that keeps the output accessible after it is moved into the tracer.
+    #[cfg(feature = "tracer")]
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    #[cfg(feature = "tracer")]
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "tracer")]
+    #[test]
+    fn test_eip3155_summary_revert_reason() {
+        // Revert data: `Error("boom")`.
+        let revert_data = primitives::hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000004"
+            "626f6f6d00000000000000000000000000000000000000000000000000000000"
+        );
+        // PUSH1 0x64, PUSH1 0x0c, PUSH1 0x00, CODECOPY, PUSH1 0x64, PUSH1 0x00, REVERT
+        let mut code = vec![
+            opcode::PUSH1,
+            0x64,
+            opcode::PUSH1,
+            0x0c,
+            opcode::PUSH1,
+            0x00,
+            opcode::CODECOPY,
+            opcode::PUSH1,
+            0x64,
+            opcode::PUSH1,
+            0x00,
+            opcode::REVERT,
+        ];
+        code.extend_from_slice(&revert_data);
+
+        let output = SharedBuffer::default();
+        let tracer = crate::inspectors::TracerEip3155::new(Box::new(output.clone()));
+        let ctx = Context::mainnet().with_db(BenchmarkDB::new_bytecode(Bytecode::new_raw(
+            Bytes::from(code),
+        )));
+        let mut evm = ctx.build_mainnet_with_inspector(tracer);
+        let result = evm
+            .inspect_one_tx(
+                TxEnv::builder()
+                    .caller(BENCH_CALLER)
+                    .kind(TxKind::Call(BENCH_TARGET))
+                    .gas_limit(100_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(matches!(
+            result,
+            context::result::ExecutionResult::Revert { .. }
+        ));
+
+        let output = output.0.borrow();
+        let summary = std::str::from_utf8(&output)
+            .unwrap()
+            .lines()
+            .last()
+            .unwrap();
+        let summary: serde_json::Value = serde_json::from_str(summary).unwrap();
+        assert_eq!(summary["pass"], false);
+        assert_eq!(summary["error"], "boom");
+    }
 }
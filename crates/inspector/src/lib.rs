@@ -5,17 +5,27 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc as std;
+
+mod access_list;
+mod call_tracer;
 mod count_inspector;
+mod coverage;
 #[cfg(feature = "tracer")]
 mod eip3155;
 mod either;
 mod gas;
+mod gas_profiler;
 /// Handler implementations for inspector integration.
 pub mod handler;
 mod inspect;
 mod inspector;
+mod inspector_stack;
 mod mainnet_inspect;
 mod noop;
+mod parity;
+mod prestate;
 mod traits;
 
 #[cfg(test)]
@@ -23,15 +33,32 @@ mod inspector_tests;
 
 /// Inspector implementations.
 pub mod inspectors {
+    pub use super::access_list::{
+        create_access_list, AccessListInspector, AccessListResult, CreateAccessListError,
+        MAX_ACCESS_LIST_ITERATIONS,
+    };
+    pub use super::call_tracer::{CallFrame, CallKind, CallLog, CallTracer, CallTracerConfig};
+    pub use super::coverage::{BranchHits, BytecodeCoverage, CoverageInspector, CoverageReport};
     #[cfg(feature = "tracer")]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
+    pub use super::gas_profiler::{CallSiteGas, GasProfile, GasProfiler, OpcodeGas};
+    pub use super::parity::{
+        AccountDiff, Action, CallAction, CallOutput, CallType, ChangedType, CreateAction,
+        CreateOutput, CreationMethod, Delta, MemoryDelta, ParityTracer, ParityTracerConfig,
+        RewardAction, RewardType, SelfdestructAction, StateDiff, StorageDelta, TraceOutput,
+        TraceResults, TransactionTrace, VmExecutedOperation, VmInstruction, VmTrace,
+    };
+    pub use super::prestate::{
+        PrestateAccount, PrestateFrame, PrestateTracer, PrestateTracerConfig,
+    };
 }
 
 pub use count_inspector::CountInspector;
 pub use handler::{inspect_instructions, InspectorHandler};
 pub use inspect::{InspectCommitEvm, InspectEvm, InspectSystemCallEvm};
 pub use inspector::*;
+pub use inspector_stack::{InspectorId, InspectorStack};
 pub use noop::NoOpInspector;
 pub use traits::*;
 
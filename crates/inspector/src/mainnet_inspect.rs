@@ -6,7 +6,7 @@ use context::{ContextSetters, ContextTr, Evm, JournalTr};
 use database_interface::DatabaseCommit;
 use handler::{
     instructions::InstructionProvider, system_call::SystemCallTx, EthFrame, EvmTr, EvmTrError,
-    Handler, MainnetHandler, PrecompileProvider,
+    Handler, L2FeeModel, L2Handler, MainnetHandler, PrecompileProvider,
 };
 use interpreter::{interpreter::EthInterpreter, InterpreterResult};
 use primitives::{Address, Bytes};
@@ -25,6 +25,20 @@ where
     type IT = EthInterpreter;
 }
 
+// Implementing InspectorHandler for L2Handler.
+impl<EVM, ERROR, FEE> InspectorHandler for L2Handler<EVM, ERROR, EthFrame<EthInterpreter>, FEE>
+where
+    EVM: InspectorEvmTr<
+        Context: ContextTr<Journal: JournalTr<State = EvmState>>,
+        Frame = EthFrame<EthInterpreter>,
+        Inspector: Inspector<<<Self as Handler>::Evm as EvmTr>::Context, EthInterpreter>,
+    >,
+    ERROR: EvmTrError<EVM>,
+    FEE: L2FeeModel<EVM::Context>,
+{
+    type IT = EthInterpreter;
+}
+
 // Implementing InspectEvm for Evm
 impl<CTX, INSP, INST, PRECOMPILES> InspectEvm
     for Evm<CTX, INSP, INST, PRECOMPILES, EthFrame<EthInterpreter>>
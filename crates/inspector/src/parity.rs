@@ -0,0 +1,1038 @@
+//! Parity/OpenEthereum style tracer [Inspector].
+//!
+//! Produces the flat traces of `trace_transaction` and the `trace`, `stateDiff` and `vmTrace`
+//! outputs of `trace_replayTransaction`.
+use crate::{prestate::StateSnapshot, Inspector, JournalExt};
+use context::{ContextTr, CreateScheme};
+use interpreter::{
+    interpreter::EthInterpreter,
+    interpreter_types::{Jumps, LoopControl, MemoryTr},
+    CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, InstructionResult,
+    Interpreter, InterpreterAction,
+};
+use primitives::{alloy_primitives::U64, Address, Bytes, B256, U256};
+use state::bytecode::opcode::{self, OpCode};
+use std::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[cfg(feature = "serde")]
+use crate::call_tracer::serde_hex_u64;
+
+/// Selects the outputs recorded by the [`ParityTracer`], mirrors the `traceTypes` parameter of
+/// `trace_replayTransaction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParityTracerConfig {
+    /// Record flat call traces.
+    pub trace: bool,
+    /// Record the virtual machine execution trace.
+    pub vm_trace: bool,
+    /// Record the state difference.
+    pub state_diff: bool,
+}
+
+impl Default for ParityTracerConfig {
+    fn default() -> Self {
+        Self {
+            trace: true,
+            vm_trace: false,
+            state_diff: false,
+        }
+    }
+}
+
+impl ParityTracerConfig {
+    /// Config that records all outputs.
+    pub const fn all() -> Self {
+        Self {
+            trace: true,
+            vm_trace: true,
+            state_diff: true,
+        }
+    }
+}
+
+/// Type of a call action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CallType {
+    /// `CALL` opcode or call transaction.
+    Call,
+    /// `CALLCODE` opcode.
+    CallCode,
+    /// `DELEGATECALL` opcode.
+    DelegateCall,
+    /// `STATICCALL` opcode.
+    StaticCall,
+}
+
+impl From<CallScheme> for CallType {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall => Self::StaticCall,
+        }
+    }
+}
+
+/// Opcode used to create a contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CreationMethod {
+    /// `CREATE` opcode or create transaction.
+    Create,
+    /// `CREATE2` opcode.
+    Create2,
+}
+
+impl From<CreateScheme> for CreationMethod {
+    fn from(scheme: CreateScheme) -> Self {
+        match scheme {
+            CreateScheme::Create2 { .. } => Self::Create2,
+            CreateScheme::Create | CreateScheme::Custom { .. } => Self::Create,
+        }
+    }
+}
+
+/// Type of a block reward.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RewardType {
+    /// Reward of the block author.
+    Block,
+    /// Reward of an uncle block author.
+    Uncle,
+}
+
+/// Action of a call trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallAction {
+    /// Caller of the call.
+    pub from: Address,
+    /// Type of the call.
+    pub call_type: CallType,
+    /// Gas available to the call.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas: u64,
+    /// Call data.
+    pub input: Bytes,
+    /// Target of the call.
+    pub to: Address,
+    /// Value transferred or apparent value of the call.
+    pub value: U256,
+}
+
+/// Action of a create trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateAction {
+    /// Creator of the contract.
+    pub from: Address,
+    /// Gas available to the create.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas: u64,
+    /// Init code.
+    pub init: Bytes,
+    /// Value transferred to the new contract.
+    pub value: U256,
+    /// Opcode used to create the contract.
+    pub creation_method: CreationMethod,
+}
+
+/// Action of a selfdestruct trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SelfdestructAction {
+    /// Selfdestructed contract.
+    pub address: Address,
+    /// Beneficiary of the balance.
+    pub refund_address: Address,
+    /// Balance transferred to the beneficiary.
+    pub balance: U256,
+}
+
+/// Action of a block reward trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RewardAction {
+    /// Receiver of the reward.
+    pub author: Address,
+    /// Type of the reward.
+    pub reward_type: RewardType,
+    /// Value of the reward.
+    pub value: U256,
+}
+
+/// Action of a [`TransactionTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "action", rename_all = "lowercase")
+)]
+pub enum Action {
+    /// Call or call transaction.
+    Call(CallAction),
+    /// Create or create transaction.
+    Create(CreateAction),
+    /// Selfdestruct.
+    #[cfg_attr(feature = "serde", serde(rename = "suicide"))]
+    Selfdestruct(SelfdestructAction),
+    /// Block reward.
+    Reward(RewardAction),
+}
+
+/// Result of a successful call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallOutput {
+    /// Gas used by the call.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas_used: u64,
+    /// Return data.
+    pub output: Bytes,
+}
+
+/// Result of a successful create.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateOutput {
+    /// Address of the created contract.
+    pub address: Address,
+    /// Deployed code.
+    pub code: Bytes,
+    /// Gas used by the create.
+    #[cfg_attr(feature = "serde", serde(with = "serde_hex_u64"))]
+    pub gas_used: u64,
+}
+
+/// Result of a [`TransactionTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum TraceOutput {
+    /// Result of a call.
+    Call(CallOutput),
+    /// Result of a create.
+    Create(CreateOutput),
+}
+
+/// Single flat trace of `trace_transaction`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TransactionTrace {
+    /// Action of the trace.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub action: Action,
+    /// Error of the frame if it did not succeed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Result of the frame, missing if the frame did not succeed.
+    pub result: Option<TraceOutput>,
+    /// Number of direct sub traces.
+    pub subtraces: usize,
+    /// Position of the trace in the call tree.
+    pub trace_address: Vec<usize>,
+}
+
+impl TransactionTrace {
+    /// Creates a new trace without result.
+    pub fn new(action: Action, trace_address: Vec<usize>) -> Self {
+        Self {
+            action,
+            error: None,
+            result: None,
+            subtraces: 0,
+            trace_address,
+        }
+    }
+
+    /// Creates a block reward trace.
+    pub fn reward(author: Address, reward_type: RewardType, value: U256) -> Self {
+        Self::new(
+            Action::Reward(RewardAction {
+                author,
+                reward_type,
+                value,
+            }),
+            Vec::new(),
+        )
+    }
+}
+
+/// Changed value of the [`Delta::Changed`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangedType<T> {
+    /// Value before the transaction.
+    pub from: T,
+    /// Value after the transaction.
+    pub to: T,
+}
+
+/// Difference of a single value in the [`StateDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Delta<T> {
+    /// Value did not change.
+    #[cfg_attr(feature = "serde", serde(rename = "="))]
+    Unchanged,
+    /// Value was added.
+    #[cfg_attr(feature = "serde", serde(rename = "+"))]
+    Added(T),
+    /// Value was removed.
+    #[cfg_attr(feature = "serde", serde(rename = "-"))]
+    Removed(T),
+    /// Value changed.
+    #[cfg_attr(feature = "serde", serde(rename = "*"))]
+    Changed(ChangedType<T>),
+}
+
+impl<T: PartialEq> Delta<T> {
+    /// Creates [`Delta::Changed`] or [`Delta::Unchanged`] from the values.
+    pub fn new(from: T, to: T) -> Self {
+        if from == to {
+            Self::Unchanged
+        } else {
+            Self::Changed(ChangedType { from, to })
+        }
+    }
+
+    /// Returns `true` if the value did not change.
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Self::Unchanged)
+    }
+}
+
+/// Difference of a single account in the [`StateDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+    /// Balance difference.
+    pub balance: Delta<U256>,
+    /// Code difference.
+    pub code: Delta<Bytes>,
+    /// Nonce difference.
+    pub nonce: Delta<U64>,
+    /// Storage differences of changed slots.
+    pub storage: BTreeMap<B256, Delta<B256>>,
+}
+
+/// State difference of the transaction, keyed by account address.
+pub type StateDiff = BTreeMap<Address, AccountDiff>;
+
+/// Memory written by an instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryDelta {
+    /// Offset of the written memory.
+    pub off: usize,
+    /// Written data.
+    pub data: Bytes,
+}
+
+/// Storage slot written by an instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageDelta {
+    /// Key of the storage slot.
+    pub key: U256,
+    /// Written value.
+    pub val: U256,
+}
+
+/// Effects of an executed instruction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmExecutedOperation {
+    /// Gas remaining after the instruction.
+    pub used: u64,
+    /// Values pushed to the stack.
+    pub push: Vec<U256>,
+    /// Memory written by the instruction.
+    pub mem: Option<MemoryDelta>,
+    /// Storage slot written by the instruction.
+    pub store: Option<StorageDelta>,
+}
+
+/// Single instruction of the [`VmTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmInstruction {
+    /// Program counter.
+    pub pc: usize,
+    /// Gas cost of the instruction.
+    pub cost: u64,
+    /// Effects of the instruction, missing if the instruction failed.
+    pub ex: Option<VmExecutedOperation>,
+    /// Trace of the frame created by the instruction.
+    pub sub: Option<VmTrace>,
+    /// Name of the opcode.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub op: Option<String>,
+}
+
+/// Virtual machine execution trace of a single frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmTrace {
+    /// Code executed by the frame.
+    pub code: Bytes,
+    /// Executed instructions.
+    pub ops: Vec<VmInstruction>,
+}
+
+/// Output of `trace_replayTransaction`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TraceResults {
+    /// Output of the transaction.
+    pub output: Bytes,
+    /// State difference, if requested.
+    pub state_diff: Option<StateDiff>,
+    /// Flat traces, empty if not requested.
+    pub trace: Vec<TransactionTrace>,
+    /// Virtual machine trace, if requested.
+    pub vm_trace: Option<VmTrace>,
+}
+
+/// Instruction of a frame that has started but not finished.
+#[derive(Clone, Debug, Default)]
+struct PendingInstruction {
+    /// Gas remaining before the instruction.
+    gas_before: u64,
+    /// Number of values pushed by the instruction.
+    outputs: usize,
+    /// Memory range written by the instruction.
+    mem: Option<(usize, usize)>,
+    /// Storage slot written by the instruction.
+    store: Option<StorageDelta>,
+}
+
+/// Virtual machine trace of a frame that has not finished.
+#[derive(Clone, Debug, Default)]
+struct VmFrame {
+    trace: VmTrace,
+    /// Instruction that is executing.
+    pending: Option<PendingInstruction>,
+    /// Last instruction created a new frame and its effects are known on the next step.
+    awaiting_return: bool,
+}
+
+/// Parity/OpenEthereum style tracer [Inspector].
+///
+/// Records flat traces with `traceAddress` and `subtraces`, the `stateDiff` and the `vmTrace`
+/// of a transaction, as selected by the [`ParityTracerConfig`].
+///
+/// The state difference is built from the same journal data as [`PrestateTracer`][crate::inspectors::PrestateTracer]
+/// and has the same limitations: changes applied after the execution (caller gas refund and
+/// beneficiary reward) are not captured.
+#[derive(Clone, Debug, Default)]
+pub struct ParityTracer {
+    config: ParityTracerConfig,
+    /// Depth of the current frame.
+    depth: usize,
+    /// Recorded flat traces.
+    traces: Vec<TransactionTrace>,
+    /// Indices of the traces of frames that have not finished.
+    open: Vec<usize>,
+    /// Virtual machine traces of frames that have not finished, `None` for precompiles.
+    vm_stack: Vec<Option<VmFrame>>,
+    /// Finished virtual machine trace.
+    vm_trace: Option<VmTrace>,
+    /// Touched accounts before and after the transaction.
+    snapshot: StateSnapshot,
+    /// Output of the top-level frame.
+    output: Bytes,
+}
+
+impl ParityTracer {
+    /// Creates a new tracer with the given config.
+    pub fn new(config: ParityTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &ParityTracerConfig {
+        &self.config
+    }
+
+    /// Resets the tracer so it can be used for the next transaction.
+    pub fn clear(&mut self) {
+        self.depth = 0;
+        self.traces.clear();
+        self.open.clear();
+        self.vm_stack.clear();
+        self.vm_trace = None;
+        self.snapshot.clear();
+        self.output = Bytes::new();
+    }
+
+    /// Returns the recorded flat traces in `trace_transaction` order.
+    pub fn traces(&self) -> &[TransactionTrace] {
+        &self.traces
+    }
+
+    /// Returns the recorded virtual machine trace.
+    pub fn vm_trace(&self) -> Option<&VmTrace> {
+        self.vm_trace.as_ref()
+    }
+
+    /// Returns the state difference of the transaction.
+    pub fn state_diff(&self) -> Option<StateDiff> {
+        if !self.config.state_diff {
+            return None;
+        }
+        let mut diff = StateDiff::new();
+        for (address, pre) in &self.snapshot.pre {
+            let Some(post) = self.snapshot.post.get(address) else {
+                continue;
+            };
+            let existed = !pre.is_empty();
+            let exists = !post.destroyed && !post.is_empty();
+            let code = |code: &Option<Bytes>| code.clone().unwrap_or_default();
+            let account = match (existed, exists) {
+                (false, false) => continue,
+                (false, true) => AccountDiff {
+                    balance: Delta::Added(post.balance),
+                    code: Delta::Added(code(&post.code)),
+                    nonce: Delta::Added(U64::from(post.nonce)),
+                    storage: post
+                        .storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(key, value)| (*key, Delta::Added(B256::from(*value))))
+                        .collect(),
+                },
+                (true, false) => AccountDiff {
+                    balance: Delta::Removed(pre.balance),
+                    code: Delta::Removed(code(&pre.code)),
+                    nonce: Delta::Removed(U64::from(pre.nonce)),
+                    storage: pre
+                        .storage
+                        .iter()
+                        .filter(|(_, value)| !value.is_zero())
+                        .map(|(key, value)| (*key, Delta::Removed(B256::from(*value))))
+                        .collect(),
+                },
+                (true, true) => {
+                    let account = AccountDiff {
+                        balance: Delta::new(pre.balance, post.balance),
+                        code: Delta::new(code(&pre.code), code(&post.code)),
+                        nonce: Delta::new(U64::from(pre.nonce), U64::from(post.nonce)),
+                        storage: pre
+                            .storage
+                            .iter()
+                            .filter_map(|(key, from)| {
+                                let to = post.storage.get(key)?;
+                                let delta = Delta::new(B256::from(*from), B256::from(*to));
+                                (!delta.is_unchanged()).then_some((*key, delta))
+                            })
+                            .collect(),
+                    };
+                    if account.balance.is_unchanged()
+                        && account.code.is_unchanged()
+                        && account.nonce.is_unchanged()
+                        && account.storage.is_empty()
+                    {
+                        continue;
+                    }
+                    account
+                }
+            };
+            diff.insert(*address, account);
+        }
+        Some(diff)
+    }
+
+    /// Returns the output of `trace_replayTransaction`.
+    pub fn trace_results(&self) -> TraceResults {
+        TraceResults {
+            output: self.output.clone(),
+            state_diff: self.state_diff(),
+            trace: self.traces.clone(),
+            vm_trace: self.vm_trace.clone(),
+        }
+    }
+
+    /// Serializes the recorded data into a `trace_replayTransaction` JSON value.
+    #[cfg(feature = "tracer")]
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::to_value(self.trace_results()).unwrap_or_default()
+    }
+
+    fn frame_start(&mut self, action: impl FnOnce() -> Action) {
+        if self.depth == 0 {
+            self.clear();
+        }
+        self.depth += 1;
+        if self.config.vm_trace {
+            self.vm_stack.push(None);
+        }
+        if self.config.trace {
+            let trace_address = self.child_trace_address();
+            self.open.push(self.traces.len());
+            self.traces
+                .push(TransactionTrace::new(action(), trace_address));
+        }
+    }
+
+    /// Returns the trace address of the next child of the current frame and counts it as a
+    /// sub trace.
+    fn child_trace_address(&mut self) -> Vec<usize> {
+        let Some(parent) = self.open.last().map(|i| &mut self.traces[*i]) else {
+            return Vec::new();
+        };
+        let mut trace_address = parent.trace_address.clone();
+        trace_address.push(parent.subtraces);
+        parent.subtraces += 1;
+        trace_address
+    }
+
+    fn frame_end<CTX: ContextTr<Journal: JournalExt>>(
+        &mut self,
+        context: &mut CTX,
+        result: InstructionResult,
+        output: &Bytes,
+        trace_output: impl FnOnce() -> TraceOutput,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+
+        if self.config.trace {
+            if let Some(trace) = self.open.pop().map(|i| &mut self.traces[i]) {
+                if result.is_ok() {
+                    trace.result = Some(trace_output());
+                } else {
+                    trace.error = Some(error_string(result));
+                }
+            }
+        }
+
+        if self.config.vm_trace {
+            let trace = self
+                .vm_stack
+                .pop()
+                .flatten()
+                .map(|frame| frame.trace)
+                .unwrap_or_default();
+            match self.vm_stack.last_mut() {
+                Some(Some(parent)) => {
+                    if let Some(op) = parent.trace.ops.last_mut() {
+                        op.sub = Some(trace);
+                    }
+                }
+                Some(None) => {}
+                None => self.vm_trace = Some(trace),
+            }
+        }
+
+        if self.depth == 0 {
+            self.output = output.clone();
+            if self.config.state_diff {
+                self.snapshot.record(context.journal_ref());
+            }
+        }
+    }
+}
+
+impl<CTX> Inspector<CTX, EthInterpreter> for ParityTracer
+where
+    CTX: ContextTr<Journal: JournalExt>,
+{
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if let Some(frame) = self.vm_stack.last_mut() {
+            *frame = Some(VmFrame {
+                trace: VmTrace {
+                    code: interp.bytecode.original_bytes(),
+                    ops: Vec::new(),
+                },
+                ..Default::default()
+            });
+        }
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(Some(frame)) = self.vm_stack.last_mut() else {
+            return;
+        };
+        let stack = interp.stack.data();
+
+        // Previous instruction created a frame that has now returned.
+        if core::mem::take(&mut frame.awaiting_return) {
+            if let Some(op) = frame.trace.ops.last_mut() {
+                op.ex = Some(VmExecutedOperation {
+                    used: interp.gas.remaining(),
+                    push: stack.last().copied().into_iter().collect(),
+                    mem: None,
+                    store: None,
+                });
+            }
+        }
+
+        let op = interp.bytecode.opcode();
+        let peek = |i: usize| {
+            stack
+                .len()
+                .checked_sub(i + 1)
+                .map(|i| stack[i])
+                .unwrap_or_default()
+        };
+        let range = |offset: U256, len: usize| Some((as_usize(offset), len));
+        let mem = match op {
+            opcode::MSTORE => range(peek(0), 32),
+            opcode::MSTORE8 => range(peek(0), 1),
+            opcode::CALLDATACOPY | opcode::CODECOPY | opcode::RETURNDATACOPY | opcode::MCOPY => {
+                range(peek(0), as_usize(peek(2)))
+            }
+            opcode::EXTCODECOPY => range(peek(1), as_usize(peek(3))),
+            _ => None,
+        };
+        let store = (op == opcode::SSTORE).then(|| StorageDelta {
+            key: peek(0),
+            val: peek(1),
+        });
+
+        frame.pending = Some(PendingInstruction {
+            gas_before: interp.gas.remaining(),
+            outputs: OpCode::new(op).map_or(0, |op| op.outputs() as usize),
+            mem,
+            store,
+        });
+        frame.trace.ops.push(VmInstruction {
+            pc: interp.bytecode.pc(),
+            cost: 0,
+            ex: None,
+            sub: None,
+            op: Some(OpCode::name_by_op(op).to_string()),
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(Some(frame)) = self.vm_stack.last_mut() else {
+            return;
+        };
+        let Some(pending) = frame.pending.take() else {
+            return;
+        };
+        let Some(op) = frame.trace.ops.last_mut() else {
+            return;
+        };
+        let remaining = interp.gas.remaining();
+        op.cost = pending.gas_before.saturating_sub(remaining);
+
+        match interp.bytecode.action() {
+            Some(InterpreterAction::NewFrame(_)) => {
+                frame.awaiting_return = true;
+                return;
+            }
+            Some(InterpreterAction::Return(result)) if result.result.is_error() => return,
+            _ => {}
+        }
+
+        let stack = interp.stack.data();
+        let pushed = stack.len().saturating_sub(pending.outputs);
+        let mem = pending
+            .mem
+            .filter(|(_, len)| *len != 0)
+            .filter(|(off, len)| off.saturating_add(*len) <= interp.memory.size())
+            .map(|(off, len)| MemoryDelta {
+                off,
+                data: Bytes::copy_from_slice(&interp.memory.slice(off..off + len)),
+            });
+        op.ex = Some(VmExecutedOperation {
+            used: remaining,
+            push: stack[pushed..].to_vec(),
+            mem,
+            store: pending.store,
+        });
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let input = if self.config.trace {
+            inputs.input.bytes(context)
+        } else {
+            Bytes::new()
+        };
+        self.frame_start(|| {
+            Action::Call(CallAction {
+                from: inputs.caller,
+                call_type: inputs.scheme.into(),
+                gas: inputs.gas_limit,
+                input,
+                to: inputs.target_address,
+                value: inputs.call_value(),
+            })
+        });
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        let result = *outcome.instruction_result();
+        let output = outcome.output().clone();
+        let gas_used = outcome.gas().spent();
+        self.frame_end(context, result, &output, || {
+            TraceOutput::Call(CallOutput {
+                gas_used,
+                output: output.clone(),
+            })
+        });
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start(|| {
+            Action::Create(CreateAction {
+                from: inputs.caller,
+                gas: inputs.gas_limit,
+                init: inputs.init_code.clone(),
+                value: inputs.value,
+                creation_method: inputs.scheme.into(),
+            })
+        });
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let result = *outcome.instruction_result();
+        let output = outcome.output().clone();
+        let address = outcome.address.unwrap_or_default();
+        let gas_used = outcome.gas().spent();
+        self.frame_end(context, result, &output, || {
+            TraceOutput::Create(CreateOutput {
+                address,
+                code: output.clone(),
+                gas_used,
+            })
+        });
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if !self.config.trace || self.open.is_empty() {
+            return;
+        }
+        let trace_address = self.child_trace_address();
+        self.traces.push(TransactionTrace::new(
+            Action::Selfdestruct(SelfdestructAction {
+                address: contract,
+                refund_address: target,
+                balance: value,
+            }),
+            trace_address,
+        ));
+    }
+}
+
+fn as_usize(value: U256) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+/// Maps the instruction result to the error message OpenEthereum uses.
+fn error_string(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "Reverted",
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG
+        | InstructionResult::ReentrancySentryOOG => "Out of gas",
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => "Bad instruction",
+        InstructionResult::InvalidJump => "Bad jump destination",
+        InstructionResult::StackUnderflow => "Stack underflow",
+        InstructionResult::StackOverflow => "Out of stack",
+        InstructionResult::CallNotAllowedInsideStatic
+        | InstructionResult::StateChangeDuringStaticCall => "Mutable Call In Static Context",
+        InstructionResult::OutOfOffset => "Out of bounds",
+        InstructionResult::PrecompileError => "Built-in failed",
+        other => return format!("{other:?}"),
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::{address, TxKind};
+    use state::{AccountInfo, Bytecode};
+
+    const CALLEE: Address = address!("0x1000000000000000000000000000000000000001");
+    const BENEFICIARY: Address = address!("0x2000000000000000000000000000000000000002");
+
+    fn insert_code(db: &mut InMemoryDB, address: Address, code: Vec<u8>) {
+        let bytecode = Bytecode::new_raw(code.into());
+        db.insert_account_info(
+            address,
+            AccountInfo::default()
+                .with_balance(U256::from(50))
+                .with_code_hash(bytecode.hash_slow())
+                .with_code(bytecode),
+        );
+    }
+
+    fn run(config: ParityTracerConfig) -> ParityTracer {
+        // Calls the callee and selfdestructs to the beneficiary.
+        let mut code = vec![opcode::PUSH1, 0x00, opcode::PUSH1, 0x00];
+        code.extend([
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+            opcode::PUSH1,
+            0x00,
+        ]);
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(CALLEE.as_slice());
+        code.extend([opcode::GAS, opcode::CALL, opcode::POP]);
+        code.push(opcode::PUSH20);
+        code.extend_from_slice(BENEFICIARY.as_slice());
+        code.push(opcode::SELFDESTRUCT);
+
+        // Stores 1 into slot 0.
+        let callee = vec![
+            opcode::PUSH1,
+            0x01,
+            opcode::PUSH1,
+            0x00,
+            opcode::SSTORE,
+            opcode::STOP,
+        ];
+
+        let mut db = InMemoryDB::default();
+        insert_code(&mut db, BENCH_TARGET, code);
+        insert_code(&mut db, CALLEE, callee);
+
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(ParityTracer::new(config));
+        let result = evm
+            .inspect_one_tx(
+                TxEnv::builder()
+                    .caller(BENCH_CALLER)
+                    .kind(TxKind::Call(BENCH_TARGET))
+                    .gas_limit(100_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(result.is_success());
+        evm.inspector
+    }
+
+    #[test]
+    fn test_parity_traces() {
+        let tracer = run(ParityTracerConfig::default());
+        let traces = tracer.traces();
+        assert_eq!(traces.len(), 3);
+
+        assert!(matches!(&traces[0].action, Action::Call(call) if call.to == BENCH_TARGET));
+        assert_eq!(traces[0].subtraces, 2);
+        assert!(traces[0].trace_address.is_empty());
+
+        assert!(matches!(&traces[1].action, Action::Call(call) if call.to == CALLEE));
+        assert_eq!(traces[1].trace_address, vec![0]);
+        assert!(matches!(traces[1].result, Some(TraceOutput::Call(_))));
+
+        let Action::Selfdestruct(selfdestruct) = &traces[2].action else {
+            panic!("expected selfdestruct");
+        };
+        assert_eq!(selfdestruct.address, BENCH_TARGET);
+        assert_eq!(selfdestruct.refund_address, BENEFICIARY);
+        assert_eq!(selfdestruct.balance, U256::from(50));
+        assert_eq!(traces[2].trace_address, vec![1]);
+
+        assert!(tracer.vm_trace().is_none());
+        assert!(tracer.state_diff().is_none());
+    }
+
+    #[test]
+    fn test_parity_state_diff() {
+        let diff = run(ParityTracerConfig::all()).state_diff().unwrap();
+
+        let callee = &diff[&CALLEE];
+        assert!(callee.balance.is_unchanged());
+        assert_eq!(
+            callee.storage[&B256::ZERO],
+            Delta::new(B256::ZERO, B256::from(U256::ONE))
+        );
+
+        // Caller did not exist before the transaction bumped its nonce.
+        let caller = &diff[&BENCH_CALLER];
+        assert_eq!(caller.nonce, Delta::Added(U64::ONE));
+
+        assert_eq!(diff[&BENEFICIARY].balance, Delta::Added(U256::from(50)));
+    }
+
+    #[test]
+    fn test_parity_vm_trace() {
+        let tracer = run(ParityTracerConfig::all());
+        let vm_trace = tracer.vm_trace().unwrap();
+
+        let call = vm_trace
+            .ops
+            .iter()
+            .find(|op| op.op.as_deref() == Some("CALL"))
+            .unwrap();
+        assert_eq!(call.ex.as_ref().unwrap().push, vec![U256::ONE]);
+
+        let sub = call.sub.as_ref().unwrap();
+        assert_eq!(sub.ops.len(), 4);
+        let sstore = &sub.ops[2];
+        assert_eq!(
+            sstore.ex.as_ref().unwrap().store,
+            Some(StorageDelta {
+                key: U256::ZERO,
+                val: U256::ONE
+            })
+        );
+        assert_eq!(sub.ops[0].ex.as_ref().unwrap().push, vec![U256::ONE]);
+    }
+
+    #[cfg(feature = "tracer")]
+    #[test]
+    fn test_parity_json() {
+        let json = run(ParityTracerConfig::all()).json();
+        assert_eq!(json["trace"][0]["type"], "call");
+        assert_eq!(json["trace"][0]["action"]["callType"], "call");
+        assert_eq!(json["trace"][2]["type"], "suicide");
+        let callee = CALLEE.to_string().to_lowercase();
+        assert_eq!(json["stateDiff"][&callee]["balance"], "=");
+        assert_eq!(
+            json["stateDiff"][&callee]["storage"][B256::ZERO.to_string()]["*"]["to"],
+            B256::from(U256::ONE).to_string()
+        );
+    }
+}
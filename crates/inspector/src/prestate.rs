@@ -0,0 +1,535 @@
+//! Geth compatible `prestateTracer` [Inspector].
+//!
+//! Records the state of every account touched by the transaction as it was before the
+//! transaction started, and optionally the changes made by the transaction.
+use crate::{Inspector, JournalExt};
+use context::{ContextTr, JournalEntry};
+use interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, InterpreterTypes};
+use primitives::{Address, Bytes, B256, U256};
+use state::{Account, EvmState};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Configuration of the [`PrestateTracer`], mirrors geth's `prestateTracer` config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct PrestateTracerConfig {
+    /// Emit `pre` and `post` objects that only contain the changed accounts and fields.
+    pub diff_mode: bool,
+    /// Do not record contract code.
+    pub disable_code: bool,
+    /// Do not record storage slots.
+    pub disable_storage: bool,
+}
+
+/// State of a single account in the [`PrestateTracer`] output.
+///
+/// Fields that are empty or unchanged (in diff mode) are left out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PrestateAccount {
+    /// Balance of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<U256>,
+    /// Nonce of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Code of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<Bytes>,
+    /// Storage slots of the account.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+/// Output of the [`PrestateTracer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum PrestateFrame {
+    /// State of all touched accounts before the transaction.
+    Prestate(BTreeMap<Address, PrestateAccount>),
+    /// State of modified accounts before and after the transaction.
+    Diff {
+        /// Modified accounts before the transaction.
+        pre: BTreeMap<Address, PrestateAccount>,
+        /// Changed fields of modified accounts after the transaction.
+        post: BTreeMap<Address, PrestateAccount>,
+    },
+}
+
+/// Full state of an account at the start or at the end of the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct AccountSnapshot {
+    pub(crate) balance: U256,
+    pub(crate) nonce: u64,
+    pub(crate) code: Option<Bytes>,
+    pub(crate) storage: BTreeMap<B256, U256>,
+    pub(crate) created: bool,
+    pub(crate) destroyed: bool,
+}
+
+impl AccountSnapshot {
+    /// Current state of the account, with storage slots touched in the given transaction.
+    fn new(account: &Account, transaction_id: usize) -> Self {
+        Self {
+            balance: account.info.balance,
+            nonce: account.info.nonce,
+            code: account
+                .info
+                .code
+                .as_ref()
+                .map(|code| code.original_bytes())
+                .filter(|code| !code.is_empty()),
+            storage: account
+                .storage
+                .iter()
+                .filter(|(_, slot)| slot.transaction_id == transaction_id)
+                .map(|(key, slot)| (B256::from(*key), slot.present_value))
+                .collect(),
+            created: account.is_created(),
+            destroyed: account.is_selfdestructed(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.balance.is_zero() && self.nonce == 0 && self.code.is_none()
+    }
+}
+
+/// State of the accounts touched by the transaction, before and after it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StateSnapshot {
+    /// Touched accounts before the transaction.
+    pub(crate) pre: BTreeMap<Address, AccountSnapshot>,
+    /// Touched accounts after the transaction.
+    pub(crate) post: BTreeMap<Address, AccountSnapshot>,
+}
+
+impl StateSnapshot {
+    pub(crate) fn clear(&mut self) {
+        self.pre.clear();
+        self.post.clear();
+    }
+
+    /// Records the current state as the post state and reverts the journal to get the pre state.
+    pub(crate) fn record(&mut self, journal: &impl JournalExt) {
+        let state: &EvmState = journal.evm_state();
+        // Accounts touched by the current transaction have the latest transaction id.
+        let Some(transaction_id) = state.values().map(|acc| acc.transaction_id).max() else {
+            return;
+        };
+        self.post = state
+            .iter()
+            .filter(|(_, acc)| acc.transaction_id == transaction_id)
+            .map(|(address, acc)| (*address, AccountSnapshot::new(acc, transaction_id)))
+            .collect();
+        self.pre = self.post.clone();
+
+        for entry in journal.journal().iter().rev() {
+            self.revert_entry(entry);
+        }
+    }
+
+    /// Applies the revert of the journal entry to the pre state.
+    fn revert_entry(&mut self, entry: &JournalEntry) {
+        match entry {
+            JournalEntry::BalanceChange {
+                old_balance,
+                address,
+            } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.balance = *old_balance;
+                }
+            }
+            JournalEntry::BalanceTransfer { balance, from, to } => {
+                if let Some(acc) = self.pre.get_mut(from) {
+                    acc.balance = acc.balance.saturating_add(*balance);
+                }
+                if let Some(acc) = self.pre.get_mut(to) {
+                    acc.balance = acc.balance.saturating_sub(*balance);
+                }
+            }
+            JournalEntry::AccountDestroyed {
+                had_balance,
+                address,
+                target,
+                ..
+            } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.balance = acc.balance.saturating_add(*had_balance);
+                    acc.destroyed = false;
+                }
+                if address != target {
+                    if let Some(acc) = self.pre.get_mut(target) {
+                        acc.balance = acc.balance.saturating_sub(*had_balance);
+                    }
+                }
+            }
+            JournalEntry::NonceChange { address } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.nonce = acc.nonce.saturating_sub(1);
+                }
+            }
+            JournalEntry::AccountCreated { address, .. } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.nonce = 0;
+                    acc.created = true;
+                }
+            }
+            JournalEntry::StorageChanged {
+                key,
+                had_value,
+                address,
+            } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.storage.insert(B256::from(*key), *had_value);
+                }
+            }
+            JournalEntry::CodeChange { address } => {
+                if let Some(acc) = self.pre.get_mut(address) {
+                    acc.code = None;
+                }
+            }
+            JournalEntry::AccountWarmed { .. }
+            | JournalEntry::AccountTouched { .. }
+            | JournalEntry::StorageWarmed { .. }
+            | JournalEntry::TransientStorageChange { .. } => {}
+        }
+    }
+}
+
+/// Geth compatible `prestateTracer` [Inspector].
+///
+/// When the top-level call ends, the pre-transaction state of every touched account is
+/// reconstructed from [`JournalExt::evm_state`] by reverting the [`JournalEntry`]s of the
+/// transaction, so the database is never queried again.
+///
+/// Changes applied after the execution (gas refund to the caller and the beneficiary reward)
+/// and EIP-7702 authorizations (that are applied directly to the state) are not part of
+/// the journal and are not captured. Code is only recorded for accounts whose code was loaded
+/// during execution.
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    config: PrestateTracerConfig,
+    /// Depth of the current frame.
+    depth: usize,
+    /// Touched accounts before and after the transaction.
+    snapshot: StateSnapshot,
+}
+
+impl PrestateTracer {
+    /// Creates a new prestate tracer with the default config.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new prestate tracer with the given config.
+    pub fn with_config(config: PrestateTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the config of the tracer.
+    pub fn config(&self) -> &PrestateTracerConfig {
+        &self.config
+    }
+
+    /// Resets the tracer so it can be used for the next transaction.
+    pub fn clear(&mut self) {
+        self.depth = 0;
+        self.snapshot.clear();
+    }
+
+    /// Returns the recorded state in the configured mode.
+    pub fn frame(&self) -> PrestateFrame {
+        if self.config.diff_mode {
+            self.diff_frame()
+        } else {
+            PrestateFrame::Prestate(self.prestate_frame())
+        }
+    }
+
+    /// Serializes the recorded state into a geth `prestateTracer` JSON value.
+    #[cfg(feature = "tracer")]
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::to_value(self.frame()).unwrap_or_default()
+    }
+
+    fn prestate_frame(&self) -> BTreeMap<Address, PrestateAccount> {
+        self.snapshot
+            .pre
+            .iter()
+            .filter(|(_, pre)| !(pre.created && pre.is_empty()))
+            .map(|(address, pre)| {
+                let account = PrestateAccount {
+                    balance: Some(pre.balance),
+                    nonce: (pre.nonce != 0).then_some(pre.nonce),
+                    code: self.code(&pre.code),
+                    storage: self.storage(pre.storage.iter().map(|(k, v)| (*k, *v))),
+                };
+                (*address, account)
+            })
+            .collect()
+    }
+
+    fn diff_frame(&self) -> PrestateFrame {
+        let mut pre = BTreeMap::new();
+        let mut post = BTreeMap::new();
+        for (address, old) in &self.snapshot.pre {
+            let Some(new) = self.snapshot.post.get(address) else {
+                continue;
+            };
+            let changed_slots: Vec<_> = old
+                .storage
+                .iter()
+                .filter(|(key, value)| new.storage.get(*key).is_some_and(|new| new != *value))
+                .map(|(key, _)| *key)
+                .collect();
+            let balance_changed = old.balance != new.balance;
+            let nonce_changed = old.nonce != new.nonce;
+            let code_changed = old.code != new.code;
+            let modified =
+                balance_changed || nonce_changed || code_changed || !changed_slots.is_empty();
+            if !modified && !new.destroyed {
+                continue;
+            }
+
+            if !(old.created && old.is_empty()) {
+                let account = PrestateAccount {
+                    balance: Some(old.balance),
+                    nonce: (old.nonce != 0).then_some(old.nonce),
+                    code: self.code(&old.code),
+                    storage: self.storage(
+                        changed_slots
+                            .iter()
+                            .map(|key| (*key, old.storage[key]))
+                            .filter(|(_, value)| !value.is_zero()),
+                    ),
+                };
+                pre.insert(*address, account);
+            }
+
+            // Destroyed accounts are pruned from the post state.
+            if new.destroyed {
+                continue;
+            }
+            let account = PrestateAccount {
+                balance: balance_changed.then_some(new.balance),
+                nonce: nonce_changed.then_some(new.nonce),
+                code: if code_changed {
+                    self.code(&new.code)
+                } else {
+                    None
+                },
+                storage: self.storage(
+                    changed_slots
+                        .iter()
+                        .map(|key| (*key, new.storage[key]))
+                        .filter(|(_, value)| !value.is_zero()),
+                ),
+            };
+            post.insert(*address, account);
+        }
+        PrestateFrame::Diff { pre, post }
+    }
+
+    fn code(&self, code: &Option<Bytes>) -> Option<Bytes> {
+        if self.config.disable_code {
+            return None;
+        }
+        code.clone()
+    }
+
+    fn storage(&self, slots: impl Iterator<Item = (B256, U256)>) -> BTreeMap<B256, B256> {
+        if self.config.disable_storage {
+            return BTreeMap::new();
+        }
+        slots.map(|(key, value)| (key, B256::from(value))).collect()
+    }
+
+    fn frame_start(&mut self) {
+        if self.depth == 0 {
+            self.clear();
+        }
+        self.depth += 1;
+    }
+
+    fn frame_end<CTX: ContextTr<Journal: JournalExt>>(&mut self, context: &mut CTX) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            self.snapshot.record(context.journal_ref());
+        }
+    }
+}
+
+impl<CTX, INTR> Inspector<CTX, INTR> for PrestateTracer
+where
+    CTX: ContextTr<Journal: JournalExt>,
+    INTR: InterpreterTypes,
+{
+    fn call(&mut self, _context: &mut CTX, _inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn call_end(&mut self, context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        self.frame_end(context);
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.frame_start();
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.frame_end(context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InspectEvm;
+    use context::{Context, TxEnv};
+    use database::{InMemoryDB, BENCH_CALLER, BENCH_TARGET};
+    use handler::{MainBuilder, MainContext};
+    use primitives::TxKind;
+    use state::{bytecode::opcode, AccountInfo, Bytecode};
+
+    fn run(config: PrestateTracerConfig) -> PrestateFrame {
+        // Reads slot 1 and writes 9 into slot 0.
+        let code = Bytecode::new_raw(
+            [
+                opcode::PUSH1,
+                0x01,
+                opcode::SLOAD,
+                opcode::POP,
+                opcode::PUSH1,
+                0x09,
+                opcode::PUSH1,
+                0x00,
+                opcode::SSTORE,
+                opcode::STOP,
+            ]
+            .into(),
+        );
+
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            BENCH_CALLER,
+            AccountInfo::default().with_balance(U256::from(1_000)),
+        );
+        db.insert_account_info(
+            BENCH_TARGET,
+            AccountInfo::default()
+                .with_balance(U256::from(100))
+                .with_code_hash(code.hash_slow())
+                .with_code(code),
+        );
+        db.insert_account_storage(BENCH_TARGET, U256::ZERO, U256::from(5))
+            .unwrap();
+        db.insert_account_storage(BENCH_TARGET, U256::ONE, U256::from(7))
+            .unwrap();
+
+        let mut evm = Context::mainnet()
+            .with_db(db)
+            .build_mainnet_with_inspector(PrestateTracer::with_config(config));
+        let result = evm
+            .inspect_one_tx(
+                TxEnv::builder()
+                    .caller(BENCH_CALLER)
+                    .kind(TxKind::Call(BENCH_TARGET))
+                    .value(U256::from(10))
+                    .gas_limit(100_000)
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(result.is_success());
+        evm.inspector.frame()
+    }
+
+    fn slot(value: u64) -> B256 {
+        B256::from(U256::from(value))
+    }
+
+    #[test]
+    fn test_prestate_tracer() {
+        let PrestateFrame::Prestate(pre) = run(PrestateTracerConfig::default()) else {
+            panic!("expected prestate frame");
+        };
+
+        let caller = &pre[&BENCH_CALLER];
+        assert_eq!(caller.balance, Some(U256::from(1_000)));
+        assert_eq!(caller.nonce, None);
+
+        let target = &pre[&BENCH_TARGET];
+        assert_eq!(target.balance, Some(U256::from(100)));
+        assert!(target.code.is_some());
+        assert_eq!(
+            target.storage,
+            BTreeMap::from([(slot(0), slot(5)), (slot(1), slot(7))])
+        );
+    }
+
+    #[test]
+    fn test_prestate_tracer_diff_mode() {
+        let PrestateFrame::Diff { pre, post } = run(PrestateTracerConfig {
+            diff_mode: true,
+            ..Default::default()
+        }) else {
+            panic!("expected diff frame");
+        };
+
+        let caller = &pre[&BENCH_CALLER];
+        assert_eq!(caller.balance, Some(U256::from(1_000)));
+        let target = &pre[&BENCH_TARGET];
+        assert_eq!(target.balance, Some(U256::from(100)));
+        assert_eq!(target.storage, BTreeMap::from([(slot(0), slot(5))]));
+
+        let caller = &post[&BENCH_CALLER];
+        assert_eq!(caller.balance, Some(U256::from(990)));
+        assert_eq!(caller.nonce, Some(1));
+        let target = &post[&BENCH_TARGET];
+        assert_eq!(target.balance, Some(U256::from(110)));
+        assert_eq!(target.nonce, None);
+        assert_eq!(target.code, None);
+        assert_eq!(target.storage, BTreeMap::from([(slot(0), slot(9))]));
+    }
+
+    #[cfg(feature = "tracer")]
+    #[test]
+    fn test_prestate_tracer_json() {
+        let json = serde_json::to_value(run(PrestateTracerConfig {
+            diff_mode: true,
+            disable_code: true,
+            disable_storage: false,
+        }))
+        .unwrap();
+        let target = BENCH_TARGET.to_string().to_lowercase();
+        assert_eq!(json["pre"][&target]["balance"], "0x64");
+        assert_eq!(json["post"][&target]["balance"], "0x6e");
+        assert!(json["pre"][&target].get("code").is_none());
+    }
+}
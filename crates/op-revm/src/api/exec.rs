@@ -1,13 +1,13 @@
 //! Implementation of the [`ExecuteEvm`] trait for the [`OpEvm`].
 use crate::{
-    evm::OpEvm, handler::OpHandler, transaction::OpTxTr, L1BlockInfo, OpHaltReason, OpSpecId,
-    OpTransactionError,
+    evm::OpEvm, handler::OpHandler, transaction::OpTxTr, L1BlockInfo, OpExecutionResult,
+    OpHaltReason, OpReceiptInfo, OpSpecId, OpTransactionError,
 };
 use revm::{
     context::{result::ExecResultAndState, ContextSetters},
     context_interface::{
         result::{EVMError, ExecutionResult},
-        Cfg, ContextTr, Database, JournalTr,
+        Cfg, ContextTr, Database, JournalTr, Transaction,
     },
     handler::{
         instructions::EthInstructions, system_call::SystemCallEvm, EthFrame, Handler,
@@ -83,6 +83,42 @@ where
     }
 }
 
+impl<CTX, INSP, PRECOMPILE> OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
+where
+    CTX: OpContextTr + ContextSetters,
+    PRECOMPILE: PrecompileProvider<CTX, Output = InterpreterResult>,
+{
+    /// Executes the transaction like [`ExecuteEvm::transact_one`] and returns the result with its
+    /// Optimism receipt fields.
+    ///
+    /// For deposit transactions the nonce of the sender is read before execution, for other
+    /// transactions the L1 fee fields are taken from the L1 block info used during execution.
+    pub fn transact_one_with_receipt_info(
+        &mut self,
+        tx: <CTX as ContextTr>::Tx,
+    ) -> Result<OpExecutionResult, OpError<CTX>> {
+        let spec = self.0.ctx.cfg().spec();
+        let deposit_nonce = if tx.is_deposit() {
+            let caller = self.0.ctx.journal_mut().load_account(tx.caller())?;
+            Some(caller.data.info.nonce)
+        } else {
+            None
+        };
+
+        let result = self.transact_one(tx)?;
+
+        let ctx = &self.0.ctx;
+        let receipt_info = match deposit_nonce {
+            Some(nonce) => OpReceiptInfo::deposit(nonce, spec),
+            None => {
+                let breakdown = ctx.chain().tx_l1_fee_breakdown(ctx.tx(), spec);
+                OpReceiptInfo::l1_fee(ctx.chain(), &breakdown, spec)
+            }
+        };
+        Ok(OpExecutionResult::new(result, receipt_info))
+    }
+}
+
 impl<CTX, INSP, PRECOMPILE> ExecuteCommitEvm
     for OpEvm<CTX, INSP, EthInstructions<EthInterpreter, CTX>, PRECOMPILE>
 where
@@ -167,3 +203,96 @@ where
         h.inspect_run_system_call(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        result::DEPOSIT_RECEIPT_VERSION, DefaultOp, L1BlockInfo, OpBuilder, OpReceiptInfo,
+        OpSpecId, OpTransaction,
+    };
+    use revm::{
+        context::{Context, TxEnv},
+        database::InMemoryDB,
+        primitives::{bytes, Address, B256, U256},
+        state::AccountInfo,
+    };
+
+    fn db(caller: Address) -> InMemoryDB {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000),
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+        db
+    }
+
+    #[test]
+    fn test_deposit_receipt_info() {
+        let caller = Address::repeat_byte(1);
+        let deposit = || {
+            OpTransaction::builder()
+                .base(TxEnv::builder().caller(caller).gas_limit(100_000))
+                .source_hash(B256::repeat_byte(1))
+                .build_fill()
+        };
+
+        for (spec, deposit_nonce, deposit_receipt_version) in [
+            (OpSpecId::BEDROCK, None, None),
+            (OpSpecId::REGOLITH, Some(5), None),
+            (OpSpecId::CANYON, Some(5), Some(DEPOSIT_RECEIPT_VERSION)),
+        ] {
+            let mut evm = Context::op()
+                .with_db(db(caller))
+                .modify_cfg_chained(|cfg| cfg.spec = spec)
+                .build_op();
+
+            let output = evm.transact_one_with_receipt_info(deposit()).unwrap();
+            assert!(output.result.is_success());
+            assert_eq!(
+                output.receipt_info,
+                OpReceiptInfo {
+                    deposit_nonce,
+                    deposit_receipt_version,
+                    ..Default::default()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_l1_fee_receipt_info() {
+        let caller = Address::repeat_byte(1);
+        let mut evm = Context::op()
+            .with_db(db(caller))
+            .with_chain(L1BlockInfo {
+                l1_base_fee: U256::from(1_000),
+                l1_fee_overhead: Some(U256::from(1_000)),
+                l1_base_fee_scalar: U256::from(1_000),
+                ..Default::default()
+            })
+            .modify_cfg_chained(|cfg| cfg.spec = OpSpecId::REGOLITH)
+            .build_op();
+
+        let tx = OpTransaction::builder()
+            .base(TxEnv::builder().caller(caller).nonce(5).gas_limit(100_000))
+            .enveloped_tx(Some(bytes!("FACADE")))
+            .build_fill();
+        let output = evm.transact_one_with_receipt_info(tx).unwrap();
+        assert!(output.result.is_success());
+        assert_eq!(
+            output.receipt_info,
+            OpReceiptInfo {
+                // 3 non-zero bytes plus the overhead.
+                l1_gas_used: Some(U256::from(48 + 1_000)),
+                l1_gas_price: Some(U256::from(1_000)),
+                l1_fee: Some(U256::from(1_048)),
+                l1_base_fee_scalar: Some(U256::from(1_000)),
+                ..Default::default()
+            }
+        );
+    }
+}
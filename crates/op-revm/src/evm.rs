@@ -1,5 +1,5 @@
 //! Contains the `[OpEvm]` type and its implementation of the execution EVM traits.
-use crate::precompiles::OpPrecompiles;
+use crate::{interop::InteropMessageValidator, precompiles::OpPrecompiles, L1BlockInfo};
 use revm::{
     context::{ContextError, ContextSetters, Evm, FrameStack},
     context_interface::ContextTr,
@@ -12,6 +12,7 @@ use revm::{
     interpreter::{interpreter::EthInterpreter, InterpreterResult},
     Database, Inspector,
 };
+use std::sync::Arc;
 
 /// Optimism EVM extends the [`Evm`] type with Optimism specific types and logic.
 #[derive(Debug, Clone)]
@@ -56,6 +57,22 @@ impl<CTX, INSP, I, P> OpEvm<CTX, INSP, I, P> {
     }
 }
 
+impl<CTX, INSP, I, P> OpEvm<CTX, INSP, I, P>
+where
+    CTX: ContextTr<Chain = L1BlockInfo>,
+{
+    /// Sets the validator of the executing messages declared in the access list.
+    ///
+    /// The validator is stored in the [`L1BlockInfo`] of the context.
+    pub fn with_interop_validator(
+        mut self,
+        validator: impl InteropMessageValidator + 'static,
+    ) -> Self {
+        self.0.ctx.chain_mut().interop_validator = Some(Arc::new(validator));
+        self
+    }
+}
+
 impl<CTX, INSP, I, P> InspectorEvmTr for OpEvm<CTX, INSP, I, P>
 where
     CTX: ContextTr<Journal: JournalExt> + ContextSetters,
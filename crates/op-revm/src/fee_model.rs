@@ -0,0 +1,115 @@
+//! Optimism implementation of the [`L2FeeModel`].
+use crate::{
+    api::exec::OpContextTr,
+    constants::{BASE_FEE_RECIPIENT, L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT},
+    transaction::{deposit::DEPOSIT_TRANSACTION_TYPE, encode_enveloped_tx, OpTxTr},
+    L1BlockInfo, OpSpecId,
+};
+use revm::{
+    context_interface::{Block, Cfg, ContextTr, Database, JournalTr, Transaction},
+    handler::L2FeeModel,
+    interpreter::Gas,
+    primitives::{Bytes, U256},
+};
+
+/// Optimism fee model.
+///
+/// Non-deposit transactions are charged the L1 data fee and, since Isthmus, the operator fee.
+/// Fees are paid out to the L1 fee vault, the base fee vault and the operator fee vault.
+/// Deposit transactions are not charged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpFeeModel;
+
+impl OpFeeModel {
+    fn is_deposit<CTX: OpContextTr>(context: &CTX) -> bool {
+        context.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE
+    }
+
+    fn enveloped_tx<CTX: OpContextTr>(context: &CTX) -> Bytes {
+        context
+            .tx()
+            .enveloped_tx()
+            .cloned()
+            .unwrap_or_else(|| encode_enveloped_tx(context.tx()))
+    }
+}
+
+impl<CTX: OpContextTr> L2FeeModel<CTX> for OpFeeModel {
+    fn upfront_cost(&self, context: &mut CTX) -> Result<U256, <CTX::Db as Database>::Error> {
+        if Self::is_deposit(context) {
+            return Ok(U256::ZERO);
+        }
+
+        // L1 block info is stored in the context for later use.
+        // and it will be reloaded from the database if it is not for the current block.
+        let spec = context.cfg().spec();
+        let block_number = context.block().number();
+        if context.chain().l2_block != block_number {
+            let interop_validator = context.chain_mut().interop_validator.take();
+            *context.chain_mut() = L1BlockInfo {
+                interop_validator,
+                ..L1BlockInfo::try_fetch(context.db_mut(), block_number, spec)?
+            };
+        }
+
+        let enveloped_tx = Self::enveloped_tx(context);
+
+        // compute L1 cost
+        let mut cost = context
+            .chain_mut()
+            .calculate_tx_l1_cost(&enveloped_tx, spec);
+
+        // compute operator fee
+        if spec.is_enabled_in(OpSpecId::ISTHMUS) {
+            let gas_limit = U256::from(context.tx().gas_limit());
+            let operator_fee_charge = context
+                .chain()
+                .operator_fee_charge(&enveloped_tx, gas_limit);
+            cost = cost.saturating_add(operator_fee_charge);
+        }
+
+        Ok(cost)
+    }
+
+    fn refund(&self, context: &mut CTX, gas: &Gas) -> U256 {
+        if Self::is_deposit(context) {
+            return U256::ZERO;
+        }
+        let spec = context.cfg().spec();
+        context.chain().operator_fee_refund(gas, spec)
+    }
+
+    fn distribute_fees(
+        &self,
+        context: &mut CTX,
+        gas: &Gas,
+    ) -> Result<(), <CTX::Db as Database>::Error> {
+        if Self::is_deposit(context) {
+            return Ok(());
+        }
+
+        let basefee = context.block().basefee() as u128;
+        let enveloped_tx = Self::enveloped_tx(context);
+        let spec = context.cfg().spec();
+        let l1_block_info = context.chain_mut();
+
+        let l1_cost = l1_block_info.calculate_tx_l1_cost(&enveloped_tx, spec);
+        let operator_fee_cost = if spec.is_enabled_in(OpSpecId::ISTHMUS) {
+            l1_block_info.operator_fee_charge(&enveloped_tx, U256::from(gas.used()))
+        } else {
+            U256::ZERO
+        };
+        let base_fee_amount = U256::from(basefee.saturating_mul(gas.used() as u128));
+
+        // Send fees to their respective recipients
+        for (recipient, amount) in [
+            (L1_FEE_RECIPIENT, l1_cost),
+            (BASE_FEE_RECIPIENT, base_fee_amount),
+            (OPERATOR_FEE_RECIPIENT, operator_fee_cost),
+        ] {
+            context.journal_mut().balance_incr(recipient, amount)?;
+        }
+
+        Ok(())
+    }
+}
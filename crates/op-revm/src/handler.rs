@@ -1,9 +1,10 @@
 //!Handler related to Optimism chain
 use crate::{
     api::exec::OpContextTr,
-    constants::{BASE_FEE_RECIPIENT, L1_FEE_RECIPIENT, OPERATOR_FEE_RECIPIENT},
+    fee_model::OpFeeModel,
+    interop::{inbox_entries, ExecutingDescriptor, InboxAccess},
     transaction::{deposit::DEPOSIT_TRANSACTION_TYPE, OpTransactionError, OpTxTr},
-    L1BlockInfo, OpHaltReason, OpSpecId,
+    OpHaltReason, OpSpecId,
 };
 use revm::{
     context::{result::InvalidTransaction, LocalContextTr},
@@ -17,7 +18,7 @@ use revm::{
         handler::EvmTrError,
         post_execution::{self, reimburse_caller},
         pre_execution::validate_account_nonce_and_code,
-        EthFrame, EvmTr, FrameResult, Handler, MainnetHandler,
+        EthFrame, EvmTr, FrameResult, Handler, L2FeeModel, MainnetHandler,
     },
     inspector::{Inspector, InspectorEvmTr, InspectorHandler},
     interpreter::{interpreter::EthInterpreter, interpreter_action::FrameInit, Gas},
@@ -91,7 +92,26 @@ where
             }
             return Ok(());
         }
-        self.mainnet.validate_env(evm)
+        self.mainnet.validate_env(evm)?;
+
+        // Validate the executing messages declared in the access list.
+        let ctx = evm.ctx();
+        if ctx.cfg().spec().is_enabled_in(OpSpecId::INTEROP) {
+            let accesses = InboxAccess::parse_entries(&inbox_entries(ctx.tx()))
+                .map_err(OpTransactionError::InvalidExecutingMessage)?;
+            if let Some(validator) = &ctx.chain().interop_validator {
+                let executing = ExecutingDescriptor {
+                    chain_id: ctx.cfg().chain_id(),
+                    timestamp: ctx.block().timestamp().saturating_to(),
+                };
+                for access in &accesses {
+                    validator
+                        .validate_message(access, &executing)
+                        .map_err(OpTransactionError::InvalidExecutingMessage)?;
+                }
+            }
+        }
+        Ok(())
     }
 
     fn validate_against_state_and_deduct_caller(
@@ -103,8 +123,6 @@ where
         let basefee = ctx.block().basefee() as u128;
         let blob_price = ctx.block().blob_gasprice().unwrap_or_default();
         let is_deposit = ctx.tx().tx_type() == DEPOSIT_TRANSACTION_TYPE;
-        let spec = ctx.cfg().spec();
-        let block_number = ctx.block().number();
         let is_balance_check_disabled = ctx.cfg().is_balance_check_disabled();
         let is_eip3607_disabled = ctx.cfg().is_eip3607_disabled();
         let is_nonce_check_disabled = ctx.cfg().is_nonce_check_disabled();
@@ -115,33 +133,8 @@ where
             0
         };
 
-        let mut additional_cost = U256::ZERO;
-
-        // The L1-cost fee is only computed for Optimism non-deposit transactions.
-        if !is_deposit {
-            // L1 block info is stored in the context for later use.
-            // and it will be reloaded from the database if it is not for the current block.
-            if ctx.chain().l2_block != block_number {
-                *ctx.chain_mut() = L1BlockInfo::try_fetch(ctx.db_mut(), block_number, spec)?;
-            }
-
-            // account for additional cost of l1 fee and operator fee
-            let enveloped_tx = ctx
-                .tx()
-                .enveloped_tx()
-                .expect("all not deposit tx have enveloped tx")
-                .clone();
-
-            // compute L1 cost
-            additional_cost = ctx.chain_mut().calculate_tx_l1_cost(&enveloped_tx, spec);
-
-            // compute operator fee
-            if spec.is_enabled_in(OpSpecId::ISTHMUS) {
-                let gas_limit = U256::from(ctx.tx().gas_limit());
-                let operator_fee_charge = ctx.chain().operator_fee_charge(&enveloped_tx, gas_limit);
-                additional_cost = additional_cost.saturating_add(operator_fee_charge);
-            }
-        }
+        // The L1-cost fee and operator fee are only charged for non-deposit transactions.
+        let additional_cost = OpFeeModel.upfront_cost(ctx)?;
 
         let (tx, journal) = ctx.tx_journal_mut();
 
@@ -288,15 +281,7 @@ where
         evm: &mut Self::Evm,
         frame_result: &mut <<Self::Evm as EvmTr>::Frame as FrameTr>::FrameResult,
     ) -> Result<(), Self::Error> {
-        let mut additional_refund = U256::ZERO;
-
-        if evm.ctx().tx().tx_type() != DEPOSIT_TRANSACTION_TYPE {
-            let spec = evm.ctx().cfg().spec();
-            additional_refund = evm
-                .ctx()
-                .chain()
-                .operator_fee_refund(frame_result.gas(), spec);
-        }
+        let additional_refund = OpFeeModel.refund(evm.ctx(), frame_result.gas());
 
         reimburse_caller(evm.ctx(), frame_result.gas(), additional_refund).map_err(From::from)
     }
@@ -338,39 +323,12 @@ where
         }
 
         self.mainnet.reward_beneficiary(evm, frame_result)?;
-        let basefee = evm.ctx().block().basefee() as u128;
 
         // If the transaction is not a deposit transaction, fees are paid out
         // to both the Base Fee Vault as well as the L1 Fee Vault.
-        let ctx = evm.ctx();
-        let enveloped = ctx.tx().enveloped_tx().cloned();
-        let spec = ctx.cfg().spec();
-        let l1_block_info = ctx.chain_mut();
-
-        let Some(enveloped_tx) = &enveloped else {
-            return Err(ERROR::from_string(
-                "[OPTIMISM] Failed to load enveloped transaction.".into(),
-            ));
-        };
-
-        let l1_cost = l1_block_info.calculate_tx_l1_cost(enveloped_tx, spec);
-        let operator_fee_cost = if spec.is_enabled_in(OpSpecId::ISTHMUS) {
-            l1_block_info.operator_fee_charge(enveloped_tx, U256::from(frame_result.gas().used()))
-        } else {
-            U256::ZERO
-        };
-        let base_fee_amount = U256::from(basefee.saturating_mul(frame_result.gas().used() as u128));
-
-        // Send fees to their respective recipients
-        for (recipient, amount) in [
-            (L1_FEE_RECIPIENT, l1_cost),
-            (BASE_FEE_RECIPIENT, base_fee_amount),
-            (OPERATOR_FEE_RECIPIENT, operator_fee_cost),
-        ] {
-            ctx.journal_mut().balance_incr(recipient, amount)?;
-        }
-
-        Ok(())
+        OpFeeModel
+            .distribute_fees(evm.ctx(), frame_result.gas())
+            .map_err(From::from)
     }
 
     fn execution_result(
@@ -496,7 +454,7 @@ mod tests {
             BASE_FEE_SCALAR_OFFSET, ECOTONE_L1_BLOB_BASE_FEE_SLOT, ECOTONE_L1_FEE_SCALARS_SLOT,
             L1_BASE_FEE_SLOT, L1_BLOCK_CONTRACT, OPERATOR_FEE_SCALARS_SLOT,
         },
-        DefaultOp, OpBuilder, OpTransaction,
+        DefaultOp, L1BlockInfo, OpBuilder, OpTransaction,
     };
     use alloy_primitives::uint;
     use revm::{
@@ -785,6 +743,7 @@ mod tests {
                 operator_fee_scalar: Some(U256::from(OPERATOR_FEE_SCALAR)),
                 operator_fee_constant: Some(U256::from(OPERATOR_FEE_CONST)),
                 tx_l1_cost: Some(U256::ZERO),
+                interop_validator: None,
             }
         );
     }
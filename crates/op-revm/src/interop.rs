@@ -0,0 +1,692 @@
+//! Contains the Superchain interop types and the [`InteropMessageValidator`] trait.
+//!
+//! Executing messages are calls to `validateMessage` of the [`CROSS_L2_INBOX_ADDRESS`] predeploy.
+//! Since [`OpSpecId::INTEROP`][crate::OpSpecId::INTEROP] every executing message must be declared
+//! in the access list of the transaction, as storage keys of the predeploy, see
+//! <https://specs.optimism.io/interop/predeploys.html#access-list>.
+//!
+//! The [`OpHandler`][crate::handler::OpHandler] checks the declared messages before execution and
+//! passes them to the [`InteropMessageValidator`] of the [`OpEvm`][crate::OpEvm], if any. Calls to
+//! `validateMessage` of messages that are not declared in the access list are reverted by the
+//! predeploy itself, which checks that the checksum storage slot is warm.
+use auto_impl::auto_impl;
+use core::fmt::{Debug, Display};
+use revm::{
+    context_interface::transaction::{AccessListItemTr, Transaction},
+    primitives::{address, keccak256, Address, HashMap, B256, U256},
+};
+use std::vec::Vec;
+
+/// Address of the `CrossL2Inbox` predeploy.
+pub const CROSS_L2_INBOX_ADDRESS: Address = address!("0x4200000000000000000000000000000000000022");
+
+/// Selector of `validateMessage((address,uint256,uint256,uint256,uint256),bytes32)`.
+pub const VALIDATE_MESSAGE_SELECTOR: [u8; 4] = [0xab, 0x4d, 0x6f, 0x75];
+
+/// Selector of the `NotInAccessList()` error of the `CrossL2Inbox` predeploy.
+pub const NOT_IN_ACCESS_LIST_SELECTOR: [u8; 4] = [0xe3, 0xc0, 0x08, 0x16];
+
+/// Time in seconds after which an initiating message can no longer be executed, 7 days.
+pub const MESSAGE_EXPIRY_WINDOW: u64 = 604_800;
+
+/// Type of the access list entry with the chain id, block number, timestamp and log index.
+pub const LOOKUP_ENTRY_TYPE: u8 = 0x01;
+/// Type of the access list entry with the upper bytes of chain ids that don't fit in 8 bytes.
+pub const CHAIN_ID_EXTENSION_ENTRY_TYPE: u8 = 0x02;
+/// Type of the access list entry with the checksum of the message.
+pub const CHECKSUM_ENTRY_TYPE: u8 = 0x03;
+
+/// Returns the access list storage keys of the `CrossL2Inbox` predeploy of the transaction.
+pub fn inbox_entries<T: Transaction>(tx: &T) -> Vec<B256> {
+    tx.access_list()
+        .into_iter()
+        .flatten()
+        .filter(|item| *item.address() == CROSS_L2_INBOX_ADDRESS)
+        .flat_map(|item| item.storage_slots().copied().collect::<Vec<_>>())
+        .collect()
+}
+
+/// Identifier of an initiating message, the `Identifier` struct of the `CrossL2Inbox` predeploy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageIdentifier {
+    /// Address that emitted the message log.
+    pub origin: Address,
+    /// Number of the block containing the log.
+    pub block_number: u64,
+    /// Index of the log in the block.
+    pub log_index: u32,
+    /// Timestamp of the block containing the log.
+    pub timestamp: u64,
+    /// Chain id of the chain that emitted the log.
+    pub chain_id: U256,
+}
+
+impl MessageIdentifier {
+    /// ABI encoded length of the identifier.
+    pub const ENCODED_LENGTH: usize = 5 * 32;
+
+    /// Decodes the ABI encoded identifier.
+    ///
+    /// Fails like the `CrossL2Inbox` predeploy if the block number, log index or timestamp are out
+    /// of range.
+    pub fn abi_decode(input: &[u8]) -> Result<Self, InteropError> {
+        let word = |i: usize| input.get(i * 32..(i + 1) * 32);
+        let (Some(origin), Some(block_number), Some(log_index), Some(timestamp), Some(chain_id)) =
+            (word(0), word(1), word(2), word(3), word(4))
+        else {
+            return Err(InteropError::MalformedIdentifier);
+        };
+        if origin[..12] != [0; 12] {
+            return Err(InteropError::MalformedIdentifier);
+        }
+
+        let int = |word: &[u8]| U256::from_be_slice(word);
+        Ok(Self {
+            origin: Address::from_slice(&origin[12..]),
+            block_number: int(block_number)
+                .try_into()
+                .map_err(|_| InteropError::MalformedIdentifier)?,
+            log_index: int(log_index)
+                .try_into()
+                .map_err(|_| InteropError::MalformedIdentifier)?,
+            timestamp: int(timestamp)
+                .try_into()
+                .map_err(|_| InteropError::MalformedIdentifier)?,
+            chain_id: int(chain_id),
+        })
+    }
+
+    /// ABI encodes the identifier.
+    pub fn abi_encode(&self) -> [u8; Self::ENCODED_LENGTH] {
+        let mut out = [0; Self::ENCODED_LENGTH];
+        out[12..32].copy_from_slice(self.origin.as_slice());
+        out[56..64].copy_from_slice(&self.block_number.to_be_bytes());
+        out[92..96].copy_from_slice(&self.log_index.to_be_bytes());
+        out[120..128].copy_from_slice(&self.timestamp.to_be_bytes());
+        out[128..].copy_from_slice(&self.chain_id.to_be_bytes::<32>());
+        out
+    }
+
+    /// Calculates the checksum of the message with the given payload hash, as the
+    /// `calculateChecksum` function of the `CrossL2Inbox` predeploy.
+    pub fn checksum(&self, msg_hash: B256) -> B256 {
+        let log_hash = keccak256([self.origin.as_slice(), msg_hash.as_slice()].concat());
+        let mut id_packed = [0u8; 32];
+        id_packed[12..20].copy_from_slice(&self.block_number.to_be_bytes());
+        id_packed[20..28].copy_from_slice(&self.timestamp.to_be_bytes());
+        id_packed[28..].copy_from_slice(&self.log_index.to_be_bytes());
+        let id_log_hash = keccak256([log_hash.as_slice(), &id_packed].concat());
+        let mut checksum =
+            keccak256([id_log_hash.as_slice(), &self.chain_id.to_be_bytes::<32>()].concat());
+        checksum[0] = CHECKSUM_ENTRY_TYPE;
+        checksum
+    }
+
+    /// Returns the access list storage keys of the `CrossL2Inbox` predeploy that declare the
+    /// message with the given payload hash.
+    pub fn access_list_entries(&self, msg_hash: B256) -> Vec<B256> {
+        let chain_id = self.chain_id.to_be_bytes::<32>();
+
+        let mut lookup = B256::ZERO;
+        lookup[0] = LOOKUP_ENTRY_TYPE;
+        lookup[4..12].copy_from_slice(&chain_id[24..]);
+        lookup[12..20].copy_from_slice(&self.block_number.to_be_bytes());
+        lookup[20..28].copy_from_slice(&self.timestamp.to_be_bytes());
+        lookup[28..].copy_from_slice(&self.log_index.to_be_bytes());
+
+        let mut entries = Vec::with_capacity(3);
+        entries.push(lookup);
+        if chain_id[..24] != [0; 24] {
+            let mut extension = B256::ZERO;
+            extension[0] = CHAIN_ID_EXTENSION_ENTRY_TYPE;
+            extension[8..].copy_from_slice(&chain_id[..24]);
+            entries.push(extension);
+        }
+        entries.push(self.checksum(msg_hash));
+        entries
+    }
+}
+
+/// Executing message declared in the access list of a transaction.
+///
+/// Unlike [`MessageIdentifier`], the origin and payload hash of the message are only committed to
+/// in the checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InboxAccess {
+    /// Chain id of the chain that emitted the initiating message.
+    pub chain_id: U256,
+    /// Number of the block containing the initiating message.
+    pub block_number: u64,
+    /// Timestamp of the block containing the initiating message.
+    pub timestamp: u64,
+    /// Index of the log of the initiating message in the block.
+    pub log_index: u32,
+    /// Checksum of the message, see [`MessageIdentifier::checksum`].
+    pub checksum: B256,
+}
+
+impl InboxAccess {
+    /// Parses the access list storage keys of the `CrossL2Inbox` predeploy.
+    ///
+    /// Every message is declared by a lookup entry, optionally followed by a chain id extension
+    /// entry, followed by the checksum entry.
+    pub fn parse_entries<'a>(
+        entries: impl IntoIterator<Item = &'a B256>,
+    ) -> Result<Vec<Self>, InteropError> {
+        let mut entries = entries.into_iter();
+        let mut accesses = Vec::new();
+        while let Some(lookup) = entries.next() {
+            if lookup[0] != LOOKUP_ENTRY_TYPE || lookup[1..4] != [0; 3] {
+                return Err(InteropError::MalformedAccessList);
+            }
+            let mut chain_id = [0u8; 32];
+            chain_id[24..].copy_from_slice(&lookup[4..12]);
+
+            let mut next = entries.next().ok_or(InteropError::MalformedAccessList)?;
+            if next[0] == CHAIN_ID_EXTENSION_ENTRY_TYPE {
+                if next[1..8] != [0; 7] {
+                    return Err(InteropError::MalformedAccessList);
+                }
+                chain_id[..24].copy_from_slice(&next[8..]);
+                next = entries.next().ok_or(InteropError::MalformedAccessList)?;
+            }
+            if next[0] != CHECKSUM_ENTRY_TYPE {
+                return Err(InteropError::MalformedAccessList);
+            }
+
+            accesses.push(Self {
+                chain_id: U256::from_be_bytes(chain_id),
+                block_number: u64::from_be_bytes(lookup[12..20].try_into().unwrap()),
+                timestamp: u64::from_be_bytes(lookup[20..28].try_into().unwrap()),
+                log_index: u32::from_be_bytes(lookup[28..].try_into().unwrap()),
+                checksum: *next,
+            });
+        }
+        Ok(accesses)
+    }
+}
+
+/// Chain and block executing the messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutingDescriptor {
+    /// Chain id of the executing chain.
+    pub chain_id: u64,
+    /// Timestamp of the executing block.
+    pub timestamp: u64,
+}
+
+/// Validator of executing messages.
+///
+/// Implemented by a client of the op-supervisor, or by [`InMemoryMessageValidator`] in tests.
+#[auto_impl(&, Box, Arc)]
+pub trait InteropMessageValidator: Debug + Send + Sync {
+    /// Validates that the initiating message declared in the access list exists and can be
+    /// executed by the executing block.
+    fn validate_message(
+        &self,
+        access: &InboxAccess,
+        executing: &ExecutingDescriptor,
+    ) -> Result<(), InteropError>;
+}
+
+/// In-memory [`InteropMessageValidator`] of known initiating messages.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InMemoryMessageValidator {
+    /// Known initiating messages by their checksum.
+    pub messages: HashMap<B256, MessageIdentifier>,
+}
+
+impl InMemoryMessageValidator {
+    /// Adds an initiating message with the given payload hash, returning its checksum.
+    pub fn insert(&mut self, id: MessageIdentifier, msg_hash: B256) -> B256 {
+        let checksum = id.checksum(msg_hash);
+        self.messages.insert(checksum, id);
+        checksum
+    }
+}
+
+impl InteropMessageValidator for InMemoryMessageValidator {
+    fn validate_message(
+        &self,
+        access: &InboxAccess,
+        executing: &ExecutingDescriptor,
+    ) -> Result<(), InteropError> {
+        let Some(id) = self.messages.get(&access.checksum) else {
+            return Err(InteropError::UnknownMessage(access.checksum));
+        };
+        if id.chain_id != access.chain_id
+            || id.block_number != access.block_number
+            || id.timestamp != access.timestamp
+            || id.log_index != access.log_index
+        {
+            return Err(InteropError::UnknownMessage(access.checksum));
+        }
+        if id.timestamp > executing.timestamp {
+            return Err(InteropError::FutureMessage(access.checksum));
+        }
+        if id.timestamp.saturating_add(MESSAGE_EXPIRY_WINDOW) < executing.timestamp {
+            return Err(InteropError::ExpiredMessage(access.checksum));
+        }
+        Ok(())
+    }
+}
+
+/// Interop executing message error.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InteropError {
+    /// The access list entries of the `CrossL2Inbox` predeploy are malformed.
+    MalformedAccessList,
+    /// The message identifier is malformed.
+    MalformedIdentifier,
+    /// The initiating message with the given checksum is unknown.
+    UnknownMessage(B256),
+    /// The initiating message with the given checksum is newer than the executing block.
+    FutureMessage(B256),
+    /// The initiating message with the given checksum has expired.
+    ExpiredMessage(B256),
+}
+
+impl Display for InteropError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MalformedAccessList => write!(f, "malformed CrossL2Inbox access list"),
+            Self::MalformedIdentifier => write!(f, "malformed message identifier"),
+            Self::UnknownMessage(checksum) => write!(f, "unknown initiating message {checksum}"),
+            Self::FutureMessage(checksum) => {
+                write!(
+                    f,
+                    "initiating message {checksum} is newer than executing block"
+                )
+            }
+            Self::ExpiredMessage(checksum) => write!(f, "initiating message {checksum} expired"),
+        }
+    }
+}
+
+impl core::error::Error for InteropError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultOp, L1BlockInfo, OpBuilder, OpSpecId, OpTransaction, OpTransactionError};
+    use revm::{
+        bytecode::{opcode::*, Bytecode},
+        context::{Context, TxEnv},
+        context_interface::{
+            result::{EVMError, ExecutionResult},
+            transaction::{AccessList, AccessListItem},
+        },
+        database::InMemoryDB,
+        inspector::NoOpInspector,
+        primitives::Bytes,
+        state::AccountInfo,
+        ExecuteEvm, InspectEvm,
+    };
+
+    const MSG_HASH: B256 = B256::repeat_byte(0xaa);
+
+    fn identifier(chain_id: U256) -> MessageIdentifier {
+        MessageIdentifier {
+            origin: Address::repeat_byte(1),
+            block_number: 100,
+            log_index: 3,
+            timestamp: 1_000,
+            chain_id,
+        }
+    }
+
+    #[test]
+    fn test_selectors() {
+        assert_eq!(
+            keccak256("validateMessage((address,uint256,uint256,uint256,uint256),bytes32)")[..4],
+            VALIDATE_MESSAGE_SELECTOR
+        );
+        assert_eq!(
+            keccak256("NotInAccessList()")[..4],
+            NOT_IN_ACCESS_LIST_SELECTOR
+        );
+    }
+
+    #[test]
+    fn test_identifier_abi() {
+        let id = identifier(U256::from(10));
+        assert_eq!(MessageIdentifier::abi_decode(&id.abi_encode()), Ok(id));
+
+        // Block number does not fit in 64 bits.
+        let mut encoded = id.abi_encode();
+        encoded[32] = 1;
+        assert_eq!(
+            MessageIdentifier::abi_decode(&encoded),
+            Err(InteropError::MalformedIdentifier)
+        );
+        assert_eq!(
+            MessageIdentifier::abi_decode(&encoded[..128]),
+            Err(InteropError::MalformedIdentifier)
+        );
+    }
+
+    #[test]
+    fn test_access_list_entries() {
+        for chain_id in [U256::from(10), U256::MAX] {
+            let id = identifier(chain_id);
+            let entries = id.access_list_entries(MSG_HASH);
+            assert_eq!(entries.len(), if chain_id == U256::MAX { 3 } else { 2 });
+            assert_eq!(entries.last(), Some(&id.checksum(MSG_HASH)));
+            assert_eq!(entries.last().unwrap()[0], CHECKSUM_ENTRY_TYPE);
+
+            let expected = InboxAccess {
+                chain_id,
+                block_number: id.block_number,
+                timestamp: id.timestamp,
+                log_index: id.log_index,
+                checksum: id.checksum(MSG_HASH),
+            };
+            assert_eq!(InboxAccess::parse_entries(&entries), Ok(vec![expected]));
+            let twice = [entries.clone(), entries.clone()].concat();
+            assert_eq!(
+                InboxAccess::parse_entries(&twice),
+                Ok(vec![expected, expected])
+            );
+
+            // Missing checksum entry or lookup entry.
+            assert_eq!(
+                InboxAccess::parse_entries(&entries[..entries.len() - 1]),
+                Err(InteropError::MalformedAccessList)
+            );
+            assert_eq!(
+                InboxAccess::parse_entries(&entries[1..]),
+                Err(InteropError::MalformedAccessList)
+            );
+        }
+        assert_eq!(InboxAccess::parse_entries(&[]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_in_memory_validator() {
+        let id = identifier(U256::from(10));
+        let mut validator = InMemoryMessageValidator::default();
+        let checksum = validator.insert(id, MSG_HASH);
+        let access = InboxAccess::parse_entries(&id.access_list_entries(MSG_HASH)).unwrap()[0];
+        let executing = |timestamp| ExecutingDescriptor {
+            chain_id: 11,
+            timestamp,
+        };
+
+        assert_eq!(
+            validator.validate_message(&access, &executing(1_000)),
+            Ok(())
+        );
+        assert_eq!(
+            validator.validate_message(&access, &executing(999)),
+            Err(InteropError::FutureMessage(checksum))
+        );
+        assert_eq!(
+            validator.validate_message(&access, &executing(1_000 + MESSAGE_EXPIRY_WINDOW + 1)),
+            Err(InteropError::ExpiredMessage(checksum))
+        );
+
+        let unknown = InboxAccess::parse_entries(&id.access_list_entries(B256::ZERO)).unwrap()[0];
+        assert_eq!(
+            validator.validate_message(&unknown, &executing(1_000)),
+            Err(InteropError::UnknownMessage(unknown.checksum))
+        );
+        // Lookup entry does not match the message of the checksum.
+        let mismatch = InboxAccess {
+            log_index: 4,
+            ..access
+        };
+        assert_eq!(
+            validator.validate_message(&mismatch, &executing(1_000)),
+            Err(InteropError::UnknownMessage(checksum))
+        );
+    }
+
+    /// Runtime code of the `CrossL2Inbox` predeploy reduced to `validateMessage`: computes the
+    /// checksum of the message, reverts with `NotInAccessList` if its storage slot is cold and
+    /// emits `ExecutingMessage` otherwise.
+    fn cross_l2_inbox_code() -> Bytecode {
+        let executing_message =
+            keccak256("ExecutingMessage(bytes32,(address,uint256,uint256,uint256,uint256))");
+        let mut code = vec![PUSH0, CALLDATALOAD, PUSH1, 224, SHR, PUSH4];
+        code.extend_from_slice(&VALIDATE_MESSAGE_SELECTOR);
+        code.extend_from_slice(&[EQ, ISZERO, PUSH2, 0, 0, JUMPI]);
+        let fail_label = code.len() - 3;
+        code.extend_from_slice(&[
+            // keccak256(abi.encodePacked(origin, msgHash))
+            PUSH1,
+            4,
+            CALLDATALOAD,
+            PUSH1,
+            96,
+            SHL,
+            PUSH0,
+            MSTORE,
+            PUSH1,
+            164,
+            CALLDATALOAD,
+            PUSH1,
+            20,
+            MSTORE,
+            PUSH1,
+            52,
+            PUSH0,
+            KECCAK256,
+            PUSH0,
+            MSTORE,
+            // keccak256(abi.encodePacked(logHash, blockNumber, timestamp, logIndex))
+            PUSH1,
+            36,
+            CALLDATALOAD,
+            PUSH1,
+            96,
+            SHL,
+            PUSH1,
+            100,
+            CALLDATALOAD,
+            PUSH1,
+            32,
+            SHL,
+            OR,
+            PUSH1,
+            68,
+            CALLDATALOAD,
+            OR,
+            PUSH1,
+            32,
+            MSTORE,
+            PUSH1,
+            64,
+            PUSH0,
+            KECCAK256,
+            PUSH0,
+            MSTORE,
+            // keccak256(abi.encode(idLogHash, chainId)) with the checksum entry type
+            PUSH1,
+            132,
+            CALLDATALOAD,
+            PUSH1,
+            32,
+            MSTORE,
+            PUSH1,
+            64,
+            PUSH0,
+            KECCAK256,
+            PUSH1,
+            8,
+            SHL,
+            PUSH1,
+            8,
+            SHR,
+            PUSH1,
+            CHECKSUM_ENTRY_TYPE,
+            PUSH1,
+            248,
+            SHL,
+            OR,
+            // Gas of reading the checksum slot
+            GAS,
+            SWAP1,
+            SLOAD,
+            POP,
+            GAS,
+            SWAP1,
+            SUB,
+            PUSH2,
+            0x03,
+            0xe8,
+            GT,
+            PUSH2,
+            0,
+            0,
+            JUMPI,
+        ]);
+        let ok_label = code.len() - 3;
+        code.push(PUSH4);
+        code.extend_from_slice(&NOT_IN_ACCESS_LIST_SELECTOR);
+        code.extend_from_slice(&[PUSH1, 224, SHL, PUSH0, MSTORE, PUSH1, 4, PUSH0, REVERT]);
+
+        let ok = code.len() as u16;
+        code.extend_from_slice(&[
+            JUMPDEST,
+            PUSH1,
+            160,
+            PUSH1,
+            4,
+            PUSH0,
+            CALLDATACOPY,
+            PUSH1,
+            164,
+            CALLDATALOAD,
+            PUSH32,
+        ]);
+        code.extend_from_slice(executing_message.as_slice());
+        code.extend_from_slice(&[PUSH1, 160, PUSH0, LOG2, STOP]);
+
+        let fail = code.len() as u16;
+        code.extend_from_slice(&[JUMPDEST, PUSH0, PUSH0, REVERT]);
+
+        code[ok_label..ok_label + 2].copy_from_slice(&ok.to_be_bytes());
+        code[fail_label..fail_label + 2].copy_from_slice(&fail.to_be_bytes());
+        Bytecode::new_raw(code.into())
+    }
+
+    /// Transaction calling `validateMessage` of the message, with the given inbox entries.
+    fn validate_message_tx(id: &MessageIdentifier, entries: Vec<B256>) -> OpTransaction<TxEnv> {
+        let data = [
+            &VALIDATE_MESSAGE_SELECTOR[..],
+            &id.abi_encode(),
+            MSG_HASH.as_slice(),
+        ]
+        .concat();
+        OpTransaction::builder()
+            .base(
+                TxEnv::builder()
+                    .tx_type(Some(1))
+                    .gas_limit(100_000)
+                    .call(CROSS_L2_INBOX_ADDRESS)
+                    .data(data.into())
+                    .access_list(AccessList::from(vec![AccessListItem {
+                        address: CROSS_L2_INBOX_ADDRESS,
+                        storage_keys: entries,
+                    }])),
+            )
+            .build_fill()
+    }
+
+    fn ctx(spec: OpSpecId) -> crate::OpContext<InMemoryDB> {
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(
+            CROSS_L2_INBOX_ADDRESS,
+            AccountInfo::default().with_code(cross_l2_inbox_code()),
+        );
+        Context::op()
+            .with_db(db)
+            .with_chain(L1BlockInfo {
+                operator_fee_scalar: Some(U256::ZERO),
+                operator_fee_constant: Some(U256::ZERO),
+                ..Default::default()
+            })
+            .modify_block_chained(|block| block.timestamp = U256::from(2_000))
+            .modify_cfg_chained(|cfg| cfg.spec = spec)
+    }
+
+    #[test]
+    fn test_validate_message_predeploy() {
+        let id = identifier(U256::from(10));
+
+        let mut evm = ctx(OpSpecId::INTEROP).build_op();
+        let result = evm
+            .transact_one(validate_message_tx(&id, id.access_list_entries(MSG_HASH)))
+            .unwrap();
+        let ExecutionResult::Success { logs, .. } = result else {
+            panic!("expected success, got {result:?}");
+        };
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].address, CROSS_L2_INBOX_ADDRESS);
+        assert_eq!(logs[0].topics()[1], MSG_HASH);
+        assert_eq!(logs[0].data.data, Bytes::copy_from_slice(&id.abi_encode()));
+
+        // Message is not declared in the access list, the predeploy reads a cold slot.
+        let mut evm = ctx(OpSpecId::INTEROP).build_op();
+        let result = evm.transact_one(validate_message_tx(&id, vec![])).unwrap();
+        let ExecutionResult::Revert { gas_used, output } = result else {
+            panic!("expected revert, got {result:?}");
+        };
+        assert_eq!(output, Bytes::from_static(&NOT_IN_ACCESS_LIST_SELECTOR));
+        // Intrinsic gas, cold storage read and the execution of the predeploy are charged.
+        assert!(gas_used > 21_000 + 2_100, "gas used {gas_used}");
+        assert!(gas_used < 100_000, "gas used {gas_used}");
+    }
+
+    #[test]
+    fn test_evm_interop_validator() {
+        let id = identifier(U256::from(10));
+        let mut validator = InMemoryMessageValidator::default();
+        validator.insert(id, MSG_HASH);
+
+        let mut evm = ctx(OpSpecId::INTEROP)
+            .build_op_with_inspector(NoOpInspector {})
+            .with_interop_validator(validator.clone());
+        let result = evm
+            .transact_one(validate_message_tx(&id, id.access_list_entries(MSG_HASH)))
+            .unwrap();
+        assert!(result.is_success());
+
+        // Unknown message, rejected when executing and inspecting.
+        let unknown = identifier(U256::from(12));
+        let expected = Err(EVMError::Transaction(
+            OpTransactionError::InvalidExecutingMessage(InteropError::UnknownMessage(
+                unknown.checksum(MSG_HASH),
+            )),
+        ));
+        let tx = || validate_message_tx(&unknown, unknown.access_list_entries(MSG_HASH));
+        assert_eq!(evm.transact_one(tx()), expected);
+        assert_eq!(evm.inspect_one_tx(tx()), expected);
+
+        // The validator is kept when the L1 block info is reloaded for a new block.
+        evm.finalize();
+        evm.0.ctx.block.number = U256::from(1);
+        assert!(evm
+            .transact_one(validate_message_tx(&id, id.access_list_entries(MSG_HASH)))
+            .unwrap()
+            .is_success());
+        assert_eq!(evm.0.ctx.chain.l2_block, U256::from(1));
+        assert_eq!(evm.transact_one(tx()), expected);
+
+        // Without a validator the message is only checked by the predeploy.
+        let mut evm = ctx(OpSpecId::INTEROP).build_op();
+        assert!(evm.transact_one(tx()).unwrap().is_success());
+
+        // Malformed access list is rejected even without a validator.
+        assert_eq!(
+            evm.transact_one(validate_message_tx(&id, vec![id.checksum(MSG_HASH)])),
+            Err(EVMError::Transaction(
+                OpTransactionError::InvalidExecutingMessage(InteropError::MalformedAccessList)
+            ))
+        );
+    }
+}
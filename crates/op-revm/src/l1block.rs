@@ -6,7 +6,11 @@ use crate::{
         L1_OVERHEAD_SLOT, L1_SCALAR_SLOT, NON_ZERO_BYTE_COST, OPERATOR_FEE_CONSTANT_OFFSET,
         OPERATOR_FEE_SCALARS_SLOT, OPERATOR_FEE_SCALAR_DECIMAL, OPERATOR_FEE_SCALAR_OFFSET,
     },
-    transaction::estimate_tx_compressed_size,
+    interop::InteropMessageValidator,
+    transaction::{
+        decode_tx_type_and_gas_limit, deposit::DEPOSIT_TRANSACTION_TYPE, encode_enveloped_tx,
+        estimate_tx_compressed_size, OpTxTr,
+    },
     OpSpecId,
 };
 use revm::{
@@ -17,6 +21,37 @@ use revm::{
     },
     primitives::{hardfork::SpecId, U256},
 };
+use std::sync::Arc;
+
+/// Breakdown of the L1 fees of a transaction.
+///
+/// Computed by [`L1BlockInfo::l1_fee_breakdown`] from a signed transaction, or by
+/// [`L1BlockInfo::tx_l1_fee_breakdown`] from the transaction fields. All fees are zero for deposit
+/// transactions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct L1FeeBreakdown {
+    /// EIP-2718 type of the transaction.
+    pub tx_type: u8,
+    /// Length of the enveloped transaction in bytes.
+    pub tx_size: u64,
+    /// FastLZ based estimate of the compressed transaction size, scaled by 1e6. None before Fjord.
+    pub estimated_size: Option<U256>,
+    /// Gas used to post the transaction data on L1, see [`L1BlockInfo::data_gas`].
+    pub l1_gas_used: U256,
+    /// L1 data fee.
+    pub l1_fee: U256,
+    /// Operator fee charged for the gas limit, the fee of the unused gas is refunded after
+    /// execution. Zero before Isthmus.
+    pub operator_fee: U256,
+}
+
+impl L1FeeBreakdown {
+    /// Returns the total fee charged on top of the L2 execution fee.
+    pub fn total(&self) -> U256 {
+        self.l1_fee.saturating_add(self.operator_fee)
+    }
+}
 
 /// L1 block info
 ///
@@ -29,7 +64,7 @@ use revm::{
 /// uint64 _sequenceNumber, bytes32 _batcherHash, uint256 _l1FeeOverhead, uint256 _l1FeeScalar)
 ///
 /// For now, we only care about the fields necessary for L1 cost calculation.
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default)]
 pub struct L1BlockInfo {
     /// The L2 block number. If not same as the one in the context,
     /// L1BlockInfo is not valid and will be reloaded from the database.
@@ -52,8 +87,43 @@ pub struct L1BlockInfo {
     pub(crate) empty_ecotone_scalars: bool,
     /// Last calculated l1 fee cost. Uses as a cache between validation and pre execution stages.
     pub tx_l1_cost: Option<U256>,
+    /// Validator of the executing messages declared in the access list since Interop.
+    ///
+    /// If not set, the executing messages are not validated before execution. It is kept when the
+    /// block info is reloaded for a new block and is not part of the equality of block infos.
+    pub interop_validator: Option<Arc<dyn InteropMessageValidator>>,
+}
+
+impl PartialEq for L1BlockInfo {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            l2_block,
+            l1_base_fee,
+            l1_fee_overhead,
+            l1_base_fee_scalar,
+            l1_blob_base_fee,
+            l1_blob_base_fee_scalar,
+            operator_fee_scalar,
+            operator_fee_constant,
+            empty_ecotone_scalars,
+            tx_l1_cost,
+            interop_validator: _,
+        } = self;
+        *l2_block == other.l2_block
+            && *l1_base_fee == other.l1_base_fee
+            && *l1_fee_overhead == other.l1_fee_overhead
+            && *l1_base_fee_scalar == other.l1_base_fee_scalar
+            && *l1_blob_base_fee == other.l1_blob_base_fee
+            && *l1_blob_base_fee_scalar == other.l1_blob_base_fee_scalar
+            && *operator_fee_scalar == other.operator_fee_scalar
+            && *operator_fee_constant == other.operator_fee_constant
+            && *empty_ecotone_scalars == other.empty_ecotone_scalars
+            && *tx_l1_cost == other.tx_l1_cost
+    }
 }
 
+impl Eq for L1BlockInfo {}
+
 impl L1BlockInfo {
     /// Try to fetch the L1 block info from the database.
     pub fn try_fetch<DB: Database>(
@@ -133,6 +203,7 @@ impl L1BlockInfo {
                     operator_fee_scalar: Some(operator_fee_scalar),
                     operator_fee_constant: Some(operator_fee_constant),
                     tx_l1_cost: None,
+                    interop_validator: None,
                 })
             } else {
                 // Pre-isthmus L1 block info
@@ -237,18 +308,80 @@ impl L1BlockInfo {
             return tx_l1_cost;
         }
         // If the input is a deposit transaction or empty, the default value is zero.
-        let tx_l1_cost = if input.is_empty() || input.first() == Some(&0x7E) {
+        if input.is_empty() || input.first() == Some(&0x7E) {
             return U256::ZERO;
-        } else if spec_id.is_enabled_in(OpSpecId::FJORD) {
+        }
+        let tx_l1_cost = self.tx_l1_cost_uncached(input, spec_id);
+
+        self.tx_l1_cost = Some(tx_l1_cost);
+        tx_l1_cost
+    }
+
+    /// Calculate the gas cost of a non-deposit transaction, without using the cached cost.
+    fn tx_l1_cost_uncached(&self, input: &[u8], spec_id: OpSpecId) -> U256 {
+        if spec_id.is_enabled_in(OpSpecId::FJORD) {
             self.calculate_tx_l1_cost_fjord(input)
         } else if spec_id.is_enabled_in(OpSpecId::ECOTONE) {
             self.calculate_tx_l1_cost_ecotone(input, spec_id)
         } else {
             self.calculate_tx_l1_cost_bedrock(input, spec_id)
+        }
+    }
+
+    /// Calculate the [`L1FeeBreakdown`] of a signed EIP-2718 enveloped transaction.
+    ///
+    /// The transaction type and gas limit are decoded from the envelope, fails if they can't be
+    /// decoded. Unlike [`L1BlockInfo::calculate_tx_l1_cost`], the cached L1 cost is not used.
+    pub fn l1_fee_breakdown(
+        &self,
+        enveloped_tx: &[u8],
+        spec_id: OpSpecId,
+    ) -> Result<L1FeeBreakdown, alloy_rlp::Error> {
+        let (tx_type, gas_limit) = decode_tx_type_and_gas_limit(enveloped_tx)?;
+        Ok(self.l1_fee_breakdown_inner(tx_type, enveloped_tx, gas_limit, spec_id))
+    }
+
+    /// Calculate the [`L1FeeBreakdown`] of a transaction.
+    ///
+    /// If the transaction has no enveloped bytes, they are derived from the transaction fields
+    /// with [`encode_enveloped_tx`], suitable for gas estimation of unsigned transactions.
+    pub fn tx_l1_fee_breakdown<T: OpTxTr>(&self, tx: &T, spec_id: OpSpecId) -> L1FeeBreakdown {
+        let derived;
+        let enveloped_tx = match tx.enveloped_tx() {
+            Some(enveloped_tx) => enveloped_tx,
+            None => {
+                derived = encode_enveloped_tx(tx);
+                &derived
+            }
         };
+        self.l1_fee_breakdown_inner(tx.tx_type(), enveloped_tx, tx.gas_limit(), spec_id)
+    }
 
-        self.tx_l1_cost = Some(tx_l1_cost);
-        tx_l1_cost
+    fn l1_fee_breakdown_inner(
+        &self,
+        tx_type: u8,
+        enveloped_tx: &[u8],
+        gas_limit: u64,
+        spec_id: OpSpecId,
+    ) -> L1FeeBreakdown {
+        let mut breakdown = L1FeeBreakdown {
+            tx_type,
+            tx_size: enveloped_tx.len() as u64,
+            ..Default::default()
+        };
+        if tx_type == DEPOSIT_TRANSACTION_TYPE || enveloped_tx.is_empty() {
+            return breakdown;
+        }
+
+        if spec_id.is_enabled_in(OpSpecId::FJORD) {
+            breakdown.estimated_size = Some(self.tx_estimated_size_fjord(enveloped_tx));
+        }
+        breakdown.l1_gas_used = self.data_gas(enveloped_tx, spec_id);
+        breakdown.l1_fee = self.tx_l1_cost_uncached(enveloped_tx, spec_id);
+        if spec_id.is_enabled_in(OpSpecId::ISTHMUS) {
+            breakdown.operator_fee = self.operator_fee_charge_inner(U256::from(gas_limit));
+        }
+        breakdown
     }
 
     /// Calculate the gas cost of a transaction based on L1 block data posted on L2, pre-Ecotone.
@@ -573,6 +706,108 @@ mod tests {
         assert_eq!(l1_fee, expected_l1_fee)
     }
 
+    #[test]
+    fn test_l1_fee_breakdown() {
+        // L1 block info and second tx of OP mainnet ecotone block 118024092, see
+        // `calculate_tx_l1_cost_ecotone`.
+        let mut l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(47036678951u64),
+            l1_base_fee_scalar: U256::from(1368),
+            l1_blob_base_fee: Some(U256::from(57422457042u64)),
+            l1_blob_base_fee_scalar: Some(U256::from(810949)),
+            ..Default::default()
+        };
+        const TX: &[u8] = &hex!("02f8b30a832253fc8402d11f39842c8a46398301388094dc6ff44d5d932cbd77b52e5612ba0529dc6226f180b844a9059cbb000000000000000000000000d43e02db81f4d46cdf8521f623d21ea0ec7562a50000000000000000000000000000000000000000000000008ac7230489e80000c001a02947e24750723b48f886931562c55d9e07f856d8e06468e719755e18bbc3a570a0784da9ce59fd7754ea5be6e17a86b348e441348cd48ace59d174772465eadbd1");
+
+        let breakdown = l1_block_info
+            .l1_fee_breakdown(TX, OpSpecId::ECOTONE)
+            .unwrap();
+        assert_eq!(
+            breakdown,
+            L1FeeBreakdown {
+                tx_type: 2,
+                tx_size: TX.len() as u64,
+                estimated_size: None,
+                l1_gas_used: U256::from(2456),
+                l1_fee: U256::from(7306020222001u64),
+                operator_fee: U256::ZERO,
+            }
+        );
+        assert_eq!(breakdown.total(), breakdown.l1_fee);
+        // The cached L1 cost is not used nor set.
+        l1_block_info.tx_l1_cost = Some(U256::from(1));
+        assert_eq!(
+            l1_block_info.l1_fee_breakdown(TX, OpSpecId::ECOTONE),
+            Ok(breakdown)
+        );
+
+        // Isthmus charges the operator fee for the gas limit of 80000.
+        l1_block_info.operator_fee_scalar = Some(U256::from(2_000));
+        l1_block_info.operator_fee_constant = Some(U256::from(5));
+        let breakdown = l1_block_info
+            .l1_fee_breakdown(TX, OpSpecId::ISTHMUS)
+            .unwrap();
+        assert_eq!(
+            breakdown.estimated_size,
+            Some(U256::from(estimate_tx_compressed_size(TX)))
+        );
+        assert_eq!(
+            breakdown.operator_fee,
+            U256::from(80_000 * 2_000 / 1_000_000 + 5)
+        );
+        assert_eq!(
+            breakdown.total(),
+            l1_block_info.calculate_tx_l1_cost_fjord(TX) + breakdown.operator_fee
+        );
+
+        assert!(l1_block_info
+            .l1_fee_breakdown(&TX[..10], OpSpecId::ECOTONE)
+            .is_err());
+    }
+
+    #[test]
+    fn test_tx_l1_fee_breakdown() {
+        use crate::transaction::{OpTransaction, OpTxTr};
+        use revm::{
+            context::TxEnv,
+            primitives::{Address, B256},
+        };
+
+        let l1_block_info = L1BlockInfo {
+            l1_base_fee: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: Some(U256::from(1_000)),
+            l1_blob_base_fee_scalar: Some(U256::from(1_000)),
+            ..Default::default()
+        };
+        let mut tx = OpTransaction::new(
+            TxEnv::builder()
+                .tx_type(Some(2))
+                .chain_id(Some(10))
+                .gas_limit(21_000)
+                .call(Address::repeat_byte(1))
+                .build_fill(),
+        );
+
+        // The envelope is derived from the transaction fields when missing.
+        let derived = l1_block_info.tx_l1_fee_breakdown(&tx, OpSpecId::FJORD);
+        tx.fill_enveloped_tx();
+        let enveloped_tx = tx.enveloped_tx().unwrap().clone();
+        assert_eq!(
+            l1_block_info.l1_fee_breakdown(&enveloped_tx, OpSpecId::FJORD),
+            Ok(derived.clone())
+        );
+        assert_eq!(derived.tx_size, enveloped_tx.len() as u64);
+        assert_eq!(derived.l1_fee, U256::from(1700));
+
+        // Deposit transactions have no L1 fee.
+        tx.deposit.source_hash = B256::repeat_byte(1);
+        tx.enveloped_tx = None;
+        let deposit = l1_block_info.tx_l1_fee_breakdown(&tx, OpSpecId::FJORD);
+        assert_eq!(deposit.tx_type, DEPOSIT_TRANSACTION_TYPE);
+        assert_eq!(deposit.total(), U256::ZERO);
+    }
+
     #[test]
     fn test_operator_fee_refund() {
         let gas = Gas::new(50000);
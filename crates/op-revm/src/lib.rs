@@ -9,7 +9,9 @@ pub mod api;
 pub mod constants;
 pub mod evm;
 pub mod fast_lz;
+pub mod fee_model;
 pub mod handler;
+pub mod interop;
 pub mod l1block;
 pub mod precompiles;
 pub mod result;
@@ -21,7 +23,8 @@ pub use api::{
     default_ctx::{DefaultOp, OpContext},
 };
 pub use evm::OpEvm;
-pub use l1block::L1BlockInfo;
-pub use result::OpHaltReason;
+pub use fee_model::OpFeeModel;
+pub use l1block::{L1BlockInfo, L1FeeBreakdown};
+pub use result::{OpExecutionResult, OpHaltReason, OpReceiptInfo};
 pub use spec::*;
 pub use transaction::{error::OpTransactionError, estimate_tx_compressed_size, OpTransaction};
@@ -1,5 +1,12 @@
-//! Contains the `[OpHaltReason]` type.
-use revm::context_interface::result::HaltReason;
+//! Contains the `[OpHaltReason]` and `[OpExecutionResult]` types.
+use crate::{L1BlockInfo, L1FeeBreakdown, OpSpecId};
+use revm::{
+    context_interface::result::{ExecutionResult, HaltReason},
+    primitives::U256,
+};
+
+/// Receipt version of deposit transactions since Canyon.
+pub const DEPOSIT_RECEIPT_VERSION: u64 = 1;
 
 /// Optimism halt reason.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -28,6 +35,99 @@ impl TryFrom<OpHaltReason> for HaltReason {
     }
 }
 
+/// Optimism specific receipt fields of an executed transaction.
+///
+/// Deposit transactions only set the deposit fields and other transactions only set the L1 fee
+/// fields.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpReceiptInfo {
+    /// Nonce of the deposit sender read before execution. Set since Regolith.
+    pub deposit_nonce: Option<u64>,
+    /// Receipt version of the deposit. Set since Canyon.
+    pub deposit_receipt_version: Option<u64>,
+    /// Gas used to post the transaction data on L1.
+    ///
+    /// Includes the L1 fee overhead when the Bedrock cost function is used.
+    pub l1_gas_used: Option<U256>,
+    /// Base fee of the L1 origin block.
+    pub l1_gas_price: Option<U256>,
+    /// Blob base fee of the L1 origin block. Set since Ecotone.
+    pub l1_blob_base_fee: Option<U256>,
+    /// L1 data fee.
+    pub l1_fee: Option<U256>,
+    /// L1 base fee scalar, the L1 fee scalar before Ecotone.
+    pub l1_base_fee_scalar: Option<U256>,
+    /// L1 blob base fee scalar. Set since Ecotone.
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// Operator fee scalar. Set since Isthmus.
+    pub operator_fee_scalar: Option<U256>,
+    /// Operator fee constant. Set since Isthmus.
+    pub operator_fee_constant: Option<U256>,
+}
+
+impl OpReceiptInfo {
+    /// Create the receipt fields of a deposit transaction, `nonce` is the nonce of the sender
+    /// before execution.
+    pub fn deposit(nonce: u64, spec_id: OpSpecId) -> Self {
+        Self {
+            deposit_nonce: spec_id.is_enabled_in(OpSpecId::REGOLITH).then_some(nonce),
+            deposit_receipt_version: spec_id
+                .is_enabled_in(OpSpecId::CANYON)
+                .then_some(DEPOSIT_RECEIPT_VERSION),
+            ..Default::default()
+        }
+    }
+
+    /// Create the receipt fields of a non-deposit transaction from the L1 block info it was
+    /// executed with and its [`L1FeeBreakdown`].
+    pub fn l1_fee(
+        l1_block_info: &L1BlockInfo,
+        breakdown: &L1FeeBreakdown,
+        spec_id: OpSpecId,
+    ) -> Self {
+        let is_ecotone = spec_id.is_enabled_in(OpSpecId::ECOTONE);
+        // The Bedrock cost function adds the overhead to the gas used.
+        let mut l1_gas_used = breakdown.l1_gas_used;
+        if !is_ecotone || l1_block_info.empty_ecotone_scalars {
+            l1_gas_used =
+                l1_gas_used.saturating_add(l1_block_info.l1_fee_overhead.unwrap_or_default());
+        }
+
+        Self {
+            l1_gas_used: Some(l1_gas_used),
+            l1_gas_price: Some(l1_block_info.l1_base_fee),
+            l1_blob_base_fee: l1_block_info.l1_blob_base_fee.filter(|_| is_ecotone),
+            l1_fee: Some(breakdown.l1_fee),
+            l1_base_fee_scalar: Some(l1_block_info.l1_base_fee_scalar),
+            l1_blob_base_fee_scalar: l1_block_info.l1_blob_base_fee_scalar.filter(|_| is_ecotone),
+            operator_fee_scalar: l1_block_info.operator_fee_scalar,
+            operator_fee_constant: l1_block_info.operator_fee_constant,
+            ..Default::default()
+        }
+    }
+}
+
+/// Execution result of a transaction with its Optimism receipt fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpExecutionResult {
+    /// Execution result.
+    pub result: ExecutionResult<OpHaltReason>,
+    /// Optimism specific receipt fields.
+    pub receipt_info: OpReceiptInfo,
+}
+
+impl OpExecutionResult {
+    /// Create a new execution result with its receipt fields.
+    pub fn new(result: ExecutionResult<OpHaltReason>, receipt_info: OpReceiptInfo) -> Self {
+        Self {
+            result,
+            receipt_info,
+        }
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod tests {
     use super::*;
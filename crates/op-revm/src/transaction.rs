@@ -1,9 +1,11 @@
 //! Contains the `[OpTransaction]` type and its implementation.
 pub mod abstraction;
 pub mod deposit;
+pub mod envelope;
 pub mod error;
 
 pub use abstraction::{OpTransaction, OpTxTr};
+pub use envelope::{decode_tx_type_and_gas_limit, encode_enveloped_tx};
 pub use error::OpTransactionError;
 
 use crate::fast_lz::flz_compress_len;
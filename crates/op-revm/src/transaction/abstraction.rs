@@ -1,5 +1,8 @@
 //! Optimism transaction abstraction containing the `[OpTxTr]` trait and corresponding `[OpTransaction]` type.
-use super::deposit::{DepositTransactionParts, DEPOSIT_TRANSACTION_TYPE};
+use super::{
+    deposit::{DepositTransactionParts, DEPOSIT_TRANSACTION_TYPE},
+    envelope::encode_enveloped_tx,
+};
 use auto_impl::auto_impl;
 use revm::{
     context::{
@@ -64,6 +67,22 @@ impl<T: Transaction> OpTransaction<T> {
             deposit: DepositTransactionParts::default(),
         }
     }
+
+    /// Returns the enveloped transaction bytes, derived from the transaction fields with
+    /// [`encode_enveloped_tx`] if they are missing.
+    pub fn enveloped_tx_or_derived(&self) -> Bytes {
+        self.enveloped_tx
+            .clone()
+            .unwrap_or_else(|| encode_enveloped_tx(self))
+    }
+
+    /// Sets the enveloped transaction bytes, derived from the transaction fields with
+    /// [`encode_enveloped_tx`], if they are missing.
+    pub fn fill_enveloped_tx(&mut self) {
+        if self.enveloped_tx.is_none() {
+            self.enveloped_tx = Some(encode_enveloped_tx(self));
+        }
+    }
 }
 
 impl OpTransaction<TxEnv> {
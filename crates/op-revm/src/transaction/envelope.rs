@@ -0,0 +1,294 @@
+//! Contains the EIP-2718 envelope encoding of [`OpTxTr`] transactions.
+//!
+//! The L1 cost of a transaction is computed from its signed EIP-2718 envelope. When the envelope
+//! is not known, for example when estimating gas of an unsigned call, it can be derived from the
+//! transaction fields with [`encode_enveloped_tx`].
+use super::{deposit::DEPOSIT_TRANSACTION_TYPE, OpTxTr};
+use alloy_rlp::{Decodable, Encodable, Header};
+use revm::{
+    context_interface::transaction::{
+        AccessListItemTr, AuthorizationTr, Transaction, TransactionType,
+    },
+    primitives::{Bytes, B256, U256},
+};
+use std::vec::Vec;
+
+/// `r` value of the placeholder signature of derived envelopes.
+///
+/// Taken from a real signature, so it is as incompressible as one.
+pub const PLACEHOLDER_SIGNATURE_R: U256 = U256::from_be_bytes(revm::primitives::hex!(
+    "2947e24750723b48f886931562c55d9e07f856d8e06468e719755e18bbc3a570"
+));
+
+/// `s` value of the placeholder signature of derived envelopes.
+pub const PLACEHOLDER_SIGNATURE_S: U256 = U256::from_be_bytes(revm::primitives::hex!(
+    "784da9ce59fd7754ea5be6e17a86b348e441348cd48ace59d174772465eadbd1"
+));
+
+/// `y_parity` of the placeholder signature of derived envelopes.
+pub const PLACEHOLDER_SIGNATURE_Y_PARITY: bool = true;
+
+/// Encodes the transaction as a signed EIP-2718 envelope.
+///
+/// As the signature is not part of the transaction, it is replaced by a placeholder of the same
+/// size, as are the signatures of EIP-7702 authorizations. The size of the envelope matches the
+/// signed transaction, making it suitable to compute the L1 cost of the transaction.
+///
+/// Custom transaction types are encoded with the EIP-1559 fields.
+pub fn encode_enveloped_tx<T: OpTxTr>(tx: &T) -> Bytes {
+    let mut payload = Vec::new();
+    let tx_type = tx.tx_type();
+
+    if tx_type == DEPOSIT_TRANSACTION_TYPE {
+        tx.source_hash().unwrap_or_default().encode(&mut payload);
+        tx.caller().encode(&mut payload);
+        tx.kind().encode(&mut payload);
+        tx.mint().unwrap_or_default().encode(&mut payload);
+        tx.value().encode(&mut payload);
+        tx.gas_limit().encode(&mut payload);
+        tx.is_system_transaction().encode(&mut payload);
+        tx.input().encode(&mut payload);
+        return envelope(tx_type, &payload);
+    }
+
+    match TransactionType::from(tx_type) {
+        TransactionType::Legacy => {
+            tx.nonce().encode(&mut payload);
+            tx.gas_price().encode(&mut payload);
+            tx.gas_limit().encode(&mut payload);
+            tx.kind().encode(&mut payload);
+            tx.value().encode(&mut payload);
+            tx.input().encode(&mut payload);
+            // EIP-155 `v` value.
+            let parity = PLACEHOLDER_SIGNATURE_Y_PARITY as u64;
+            match tx.chain_id() {
+                Some(chain_id) => U256::from(chain_id) * U256::from(2) + U256::from(35 + parity),
+                None => U256::from(27 + parity),
+            }
+            .encode(&mut payload);
+            PLACEHOLDER_SIGNATURE_R.encode(&mut payload);
+            PLACEHOLDER_SIGNATURE_S.encode(&mut payload);
+
+            let mut out = Vec::new();
+            encode_list(&payload, &mut out);
+            return out.into();
+        }
+        TransactionType::Eip2930 => {
+            tx.chain_id().unwrap_or_default().encode(&mut payload);
+            tx.nonce().encode(&mut payload);
+            tx.gas_price().encode(&mut payload);
+            tx.gas_limit().encode(&mut payload);
+            tx.kind().encode(&mut payload);
+            tx.value().encode(&mut payload);
+            tx.input().encode(&mut payload);
+            encode_access_list(tx, &mut payload);
+        }
+        TransactionType::Eip1559
+        | TransactionType::Eip4844
+        | TransactionType::Eip7702
+        | TransactionType::Custom => {
+            tx.chain_id().unwrap_or_default().encode(&mut payload);
+            tx.nonce().encode(&mut payload);
+            tx.max_priority_fee_per_gas()
+                .unwrap_or_default()
+                .encode(&mut payload);
+            tx.max_fee_per_gas().encode(&mut payload);
+            tx.gas_limit().encode(&mut payload);
+            tx.kind().encode(&mut payload);
+            tx.value().encode(&mut payload);
+            tx.input().encode(&mut payload);
+            encode_access_list(tx, &mut payload);
+
+            if tx_type == TransactionType::Eip4844 {
+                tx.max_fee_per_blob_gas().encode(&mut payload);
+                alloy_rlp::encode_list::<_, B256>(tx.blob_versioned_hashes(), &mut payload);
+            } else if tx_type == TransactionType::Eip7702 {
+                encode_authorization_list(tx, &mut payload);
+            }
+        }
+    }
+    PLACEHOLDER_SIGNATURE_Y_PARITY.encode(&mut payload);
+    PLACEHOLDER_SIGNATURE_R.encode(&mut payload);
+    PLACEHOLDER_SIGNATURE_S.encode(&mut payload);
+
+    envelope(tx_type, &payload)
+}
+
+/// Decodes the transaction type and gas limit of a signed EIP-2718 envelope.
+///
+/// Only the fields up to the gas limit are decoded, the rest of the envelope is not validated.
+pub fn decode_tx_type_and_gas_limit(enveloped_tx: &[u8]) -> alloy_rlp::Result<(u8, u64)> {
+    let (tx_type, mut buf) = match enveloped_tx.first() {
+        None => return Err(alloy_rlp::Error::InputTooShort),
+        // Legacy transactions are not prefixed with a type and start with the list header.
+        Some(&(0xc0..)) => (TransactionType::Legacy as u8, enveloped_tx),
+        Some(&tx_type) => (tx_type, &enveloped_tx[1..]),
+    };
+
+    // Number of fields preceding the gas limit.
+    let gas_limit_index = if tx_type == DEPOSIT_TRANSACTION_TYPE {
+        5
+    } else {
+        match TransactionType::from(tx_type) {
+            TransactionType::Legacy => 2,
+            TransactionType::Eip2930 => 3,
+            TransactionType::Eip1559 | TransactionType::Eip4844 | TransactionType::Eip7702 => 4,
+            TransactionType::Custom => {
+                return Err(alloy_rlp::Error::Custom("unsupported transaction type"))
+            }
+        }
+    };
+
+    let mut fields = Header::decode_bytes(&mut buf, true)?;
+    for _ in 0..gas_limit_index {
+        let header = Header::decode(&mut fields)?;
+        if fields.len() < header.payload_length {
+            return Err(alloy_rlp::Error::InputTooShort);
+        }
+        fields = &fields[header.payload_length..];
+    }
+    let gas_limit = u64::decode(&mut fields)?;
+
+    Ok((tx_type, gas_limit))
+}
+
+/// Prefixes the RLP list of the payload with the transaction type.
+fn envelope(tx_type: u8, payload: &[u8]) -> Bytes {
+    let mut out = Vec::with_capacity(payload.len() + 10);
+    out.push(tx_type);
+    encode_list(payload, &mut out);
+    out.into()
+}
+
+/// Encodes the already encoded fields as a RLP list.
+fn encode_list(payload: &[u8], out: &mut Vec<u8>) {
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(out);
+    out.extend_from_slice(payload);
+}
+
+/// Encodes the access list of the transaction, a list of `[address, [storage keys]]`.
+fn encode_access_list<T: Transaction>(tx: &T, out: &mut Vec<u8>) {
+    let mut items = Vec::new();
+    for item in tx.access_list().into_iter().flatten() {
+        let mut fields = Vec::new();
+        item.address().encode(&mut fields);
+        let mut slots = Vec::new();
+        for slot in item.storage_slots() {
+            slot.encode(&mut slots);
+        }
+        encode_list(&slots, &mut fields);
+        encode_list(&fields, &mut items);
+    }
+    encode_list(&items, out);
+}
+
+/// Encodes the authorization list of the transaction, with placeholder signatures.
+fn encode_authorization_list<T: Transaction>(tx: &T, out: &mut Vec<u8>) {
+    let mut items = Vec::new();
+    for authorization in tx.authorization_list() {
+        let mut fields = Vec::new();
+        authorization.chain_id().encode(&mut fields);
+        authorization.address().encode(&mut fields);
+        authorization.nonce().encode(&mut fields);
+        PLACEHOLDER_SIGNATURE_Y_PARITY.encode(&mut fields);
+        PLACEHOLDER_SIGNATURE_R.encode(&mut fields);
+        PLACEHOLDER_SIGNATURE_S.encode(&mut fields);
+        encode_list(&fields, &mut items);
+    }
+    encode_list(&items, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::OpTransaction;
+    use revm::{
+        context::{tx::TxEnvBuilder, TxEnv},
+        context_interface::transaction::{AccessList, AccessListItem},
+        primitives::{address, bytes, hex, Address, TxKind},
+    };
+
+    // Second tx in OP mainnet ecotone block 118024092, its signature is the placeholder signature.
+    // <https://optimistic.etherscan.io/tx/0xa75ef696bf67439b4d5b61da85de9f3ceaa2e145abe982212101b244b63749c2>
+    const TX: &[u8] = &hex!("02f8b30a832253fc8402d11f39842c8a46398301388094dc6ff44d5d932cbd77b52e5612ba0529dc6226f180b844a9059cbb000000000000000000000000d43e02db81f4d46cdf8521f623d21ea0ec7562a50000000000000000000000000000000000000000000000008ac7230489e80000c001a02947e24750723b48f886931562c55d9e07f856d8e06468e719755e18bbc3a570a0784da9ce59fd7754ea5be6e17a86b348e441348cd48ace59d174772465eadbd1");
+
+    fn op_tx(base: TxEnvBuilder) -> OpTransaction<TxEnv> {
+        OpTransaction::new(base.build_fill())
+    }
+
+    #[test]
+    fn test_encode_eip1559() {
+        let tx = op_tx(
+            TxEnv::builder()
+                .tx_type(Some(2))
+                .chain_id(Some(10))
+                .nonce(0x2253fc)
+                .gas_priority_fee(Some(0x02d11f39))
+                .max_fee_per_gas(0x2c8a4639)
+                .gas_limit(80_000)
+                .call(address!("0xdc6ff44d5d932cbd77b52e5612ba0529dc6226f1"))
+                .data(bytes!("a9059cbb000000000000000000000000d43e02db81f4d46cdf8521f623d21ea0ec7562a50000000000000000000000000000000000000000000000008ac7230489e80000")),
+        );
+        assert_eq!(encode_enveloped_tx(&tx), Bytes::from_static(TX));
+        assert_eq!(decode_tx_type_and_gas_limit(TX), Ok((2, 80_000)));
+    }
+
+    #[test]
+    fn test_encode_roundtrip_gas_limit() {
+        let base = || {
+            TxEnv::builder()
+                .chain_id(Some(10))
+                .nonce(7)
+                .gas_price(1_000)
+                .gas_limit(123_456)
+                .kind(TxKind::Create)
+                .data(bytes!("6080604052"))
+        };
+        let access_list = AccessList::from(vec![AccessListItem {
+            address: Address::repeat_byte(1),
+            storage_keys: vec![B256::repeat_byte(2)],
+        }]);
+
+        for tx_type in [0, 1, 2] {
+            let tx = op_tx(
+                base()
+                    .tx_type(Some(tx_type))
+                    .access_list(access_list.clone()),
+            );
+            let enveloped = encode_enveloped_tx(&tx);
+            assert_eq!(
+                decode_tx_type_and_gas_limit(&enveloped),
+                Ok((tx_type, 123_456))
+            );
+        }
+        // Legacy transactions are not prefixed with their type.
+        let legacy = encode_enveloped_tx(&op_tx(base().tx_type(Some(0))));
+        assert!(legacy[0] >= 0xc0);
+
+        let mut deposit = op_tx(base().call(Address::repeat_byte(3)));
+        deposit.deposit.source_hash = B256::repeat_byte(4);
+        deposit.deposit.mint = Some(100);
+        let enveloped = encode_enveloped_tx(&deposit);
+        assert_eq!(enveloped[0], DEPOSIT_TRANSACTION_TYPE);
+        assert_eq!(
+            decode_tx_type_and_gas_limit(&enveloped),
+            Ok((DEPOSIT_TRANSACTION_TYPE, 123_456))
+        );
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(
+            decode_tx_type_and_gas_limit(&[]),
+            Err(alloy_rlp::Error::InputTooShort)
+        );
+        assert!(decode_tx_type_and_gas_limit(&[0x05, 0xc0]).is_err());
+        assert!(decode_tx_type_and_gas_limit(&TX[..20]).is_err());
+        // Gas limit is missing.
+        assert!(decode_tx_type_and_gas_limit(&[0x02, 0xc2, 0x0a, 0x01]).is_err());
+    }
+}
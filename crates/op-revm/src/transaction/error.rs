@@ -1,4 +1,5 @@
 //! Contains the `[OpTransactionError]` type.
+use crate::interop::InteropError;
 use core::fmt::Display;
 use revm::context_interface::{
     result::{EVMError, InvalidTransaction},
@@ -41,6 +42,13 @@ pub enum OpTransactionError {
     /// are cause for non-inclusion, so a special [OpHaltReason][crate::OpHaltReason] variant was introduced to handle this
     /// case for failed deposit transactions.
     HaltedDepositPostRegolith,
+    /// An executing message declared in the access list of the transaction is invalid.
+    ///
+    /// Only checked since the Interop hardfork. Messages are only validated when the
+    /// [`L1BlockInfo`][crate::L1BlockInfo] of the context has an
+    /// [`InteropMessageValidator`][crate::interop::InteropMessageValidator], malformed access
+    /// lists are always rejected.
+    InvalidExecutingMessage(InteropError),
 }
 
 impl TransactionError for OpTransactionError {}
@@ -61,6 +69,9 @@ impl Display for OpTransactionError {
                     "deposit transaction halted post-regolith; error will be bubbled up to main return handler"
                 )
             }
+            Self::InvalidExecutingMessage(error) => {
+                write!(f, "invalid executing message: {error}")
+            }
         }
     }
 }
@@ -98,7 +109,12 @@ mod test {
         assert_eq!(
             OpTransactionError::HaltedDepositPostRegolith.to_string(),
             "deposit transaction halted post-regolith; error will be bubbled up to main return handler"
-        )
+        );
+        assert_eq!(
+            OpTransactionError::InvalidExecutingMessage(InteropError::MalformedAccessList)
+                .to_string(),
+            "invalid executing message: malformed CrossL2Inbox access list"
+        );
     }
 
     #[cfg(feature = "serde")]
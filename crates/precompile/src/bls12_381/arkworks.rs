@@ -528,3 +528,46 @@ pub(crate) fn p2_msm_bytes(
     // Encode result
     Ok(encode_g2_point(&result))
 }
+
+/// Verifies a BLS signature of `msg` signed by all `pubkeys`, as in the `FastAggregateVerify`
+/// of the proof of possession scheme with public keys in G1.
+///
+/// Public keys and the signature are compressed. Returns an error if any of them is not a valid
+/// point of the subgroup or if a public key is the point at infinity.
+#[cfg(feature = "bsc")]
+pub(crate) fn fast_aggregate_verify(
+    msg: &[u8],
+    dst: &[u8],
+    signature: &[u8; 96],
+    pubkeys: &[[u8; 48]],
+) -> Result<bool, PrecompileError> {
+    use ark_ec::hashing::{map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve};
+    use ark_ff::field_hashers::DefaultFieldHasher;
+
+    let signature = G2Affine::deserialize_compressed(&signature[..])
+        .map_err(|_| PrecompileError::other("invalid bls signature"))?;
+    let mut aggregated = G1Projective::zero();
+    for pubkey in pubkeys {
+        let pubkey = G1Affine::deserialize_compressed(&pubkey[..])
+            .ok()
+            .filter(|pubkey| !pubkey.is_zero())
+            .ok_or_else(|| PrecompileError::other("invalid bls public key"))?;
+        aggregated += pubkey;
+    }
+
+    let hasher = MapToCurveBasedHasher::<
+        G2Projective,
+        DefaultFieldHasher<sha2::Sha256, 128>,
+        WBMap<ark_bls12_381::g2::Config>,
+    >::new(dst)
+    .map_err(|_| PrecompileError::other("invalid hash to curve domain"))?;
+    let hashed = hasher
+        .hash(msg)
+        .map_err(|_| PrecompileError::other("hash to curve failed"))?;
+
+    // e(pk, H(msg)) == e(g1, signature)
+    Ok(pairing_check(&[
+        (aggregated.into_affine(), hashed),
+        (-G1Affine::generator(), signature),
+    ]))
+}
@@ -792,3 +792,32 @@ pub(crate) fn pairing_check_bytes(pairs: &[PairingPair]) -> Result<bool, crate::
 
     Ok(pairing_check(&parsed_pairs))
 }
+
+/// Verifies a BLS signature of `msg` signed by all `pubkeys`, as in the `FastAggregateVerify`
+/// of the proof of possession scheme with public keys in G1.
+///
+/// Public keys and the signature are compressed. Returns an error if any of them is not a valid
+/// point of the subgroup or if a public key is the point at infinity.
+#[cfg(feature = "bsc")]
+pub(crate) fn fast_aggregate_verify(
+    msg: &[u8],
+    dst: &[u8],
+    signature: &[u8; 96],
+    pubkeys: &[[u8; 48]],
+) -> Result<bool, PrecompileError> {
+    use blst::{
+        min_pk::{PublicKey, Signature},
+        BLST_ERROR,
+    };
+
+    let signature = Signature::sig_validate(signature, false)
+        .map_err(|_| PrecompileError::other("invalid bls signature"))?;
+    let pubkeys = pubkeys
+        .iter()
+        .map(|pubkey| PublicKey::key_validate(pubkey))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| PrecompileError::other("invalid bls public key"))?;
+    let pubkeys = pubkeys.iter().collect::<Vec<_>>();
+
+    Ok(signature.fast_aggregate_verify(false, msg, dst, &pubkeys) == BLST_ERROR::BLST_SUCCESS)
+}
@@ -0,0 +1,128 @@
+//! # BNB Smart Chain precompiles
+//!
+//! Precompiles that BNB Smart Chain adds next to the Ethereum ones. They are used by the system
+//! contracts for cross-chain communication with the BNB Beacon Chain and Greenfield.
+//!
+//! Which precompile is active depends on the BSC hardfork, the activation table lives in
+//! `bsc-revm`, this module only contains the precompiles and their variants:
+//!
+//! | Address | Precompile                    | Module          |
+//! | :-----: | :---------------------------- | :-------------- |
+//! | `0x64`  | `tmHeaderValidate`            | [`tendermint`]  |
+//! | `0x65`  | `iavlMerkleProofValidate`     | [`iavl`]        |
+//! | `0x66`  | `blsSignatureVerify`          | [`bls`]         |
+//! | `0x67`  | `cometBFTLightBlockValidate`  | [`cometbft`]    |
+//! | `0x68`  | `verifyDoubleSignEvidence`    | [`double_sign`] |
+//! | `0x69`  | `secp256k1SignatureRecover`   | [`secp256k1`]   |
+//!
+//! The input framing, the consensus state encoding and the suspension of the light client
+//! precompiles follow the BSC client. Verification of Tendermint headers, CometBFT light blocks and IAVL proofs
+//! requires the Tendermint amino and CometBFT protobuf encodings and is not supported, inputs that
+//! pass decoding fail with [`PrecompileError::Other`].
+use crate::{PrecompileError, PrecompileResult};
+
+pub mod bls;
+pub mod cometbft;
+pub mod double_sign;
+pub mod iavl;
+pub mod secp256k1;
+pub mod tendermint;
+
+/// Length of the big endian length words used in the inputs and outputs of the precompiles.
+///
+/// Only the last 8 bytes of a word are read.
+pub const LENGTH_WORD: usize = 32;
+
+/// Gas cost of the light client precompiles.
+pub const LIGHT_CLIENT_GAS: u64 = 3_000;
+
+/// Reads the length stored in the last 8 bytes of the 32 byte word at the start of `input`.
+///
+/// Returns `None` if the input is shorter than a word.
+#[inline]
+pub fn read_length(input: &[u8]) -> Option<u64> {
+    let word = input.get(..LENGTH_WORD)?;
+    Some(u64::from_be_bytes(
+        word[LENGTH_WORD - 8..].try_into().unwrap(),
+    ))
+}
+
+/// Writes `length` into a 32 byte word.
+#[inline]
+pub fn length_word(length: u64) -> [u8; LENGTH_WORD] {
+    let mut word = [0u8; LENGTH_WORD];
+    word[LENGTH_WORD - 8..].copy_from_slice(&length.to_be_bytes());
+    word
+}
+
+/// Strips the payload length word and checks that it matches the payload.
+pub(crate) fn payload(input: &[u8]) -> Result<&[u8], PrecompileError> {
+    if input.len() <= LENGTH_WORD {
+        return Err(PrecompileError::other(
+            "invalid input: input should include 32 bytes payload length and payload",
+        ));
+    }
+    let payload_length = read_length(input).unwrap();
+    if payload_length.checked_add(LENGTH_WORD as u64) != Some(input.len() as u64) {
+        return Err(PrecompileError::Other(format!(
+            "invalid input: input size should be {}, actual the size is {}",
+            payload_length.saturating_add(LENGTH_WORD as u64),
+            input.len()
+        )));
+    }
+    Ok(&input[LENGTH_WORD..])
+}
+
+/// Charges the light client gas and returns out of gas if the limit is too low.
+#[inline]
+pub(crate) fn charge(gas_limit: u64) -> Result<u64, PrecompileError> {
+    if LIGHT_CLIENT_GAS > gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+    Ok(LIGHT_CLIENT_GAS)
+}
+
+/// Precompile logic of the suspended light client precompiles.
+///
+/// Since the Nano hardfork every call fails while still charging the gas.
+pub fn suspended(_input: &[u8], gas_limit: u64) -> PrecompileResult {
+    charge(gas_limit)?;
+    Err(PrecompileError::other("suspended"))
+}
+
+/// Returns the error of inputs that need a verification that is not supported.
+pub(crate) fn unsupported(what: &str) -> PrecompileError {
+    PrecompileError::Other(format!("{what} verification is not supported"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_framing() {
+        let mut input = length_word(3).to_vec();
+        input.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(payload(&input), Ok(&[1u8, 2, 3][..]));
+
+        input.push(4);
+        assert!(payload(&input).is_err());
+        assert!(payload(&length_word(0)).is_err());
+        // Overflowing length.
+        let mut input = length_word(u64::MAX).to_vec();
+        input.push(0);
+        assert!(payload(&input).is_err());
+    }
+
+    #[test]
+    fn test_suspended() {
+        assert_eq!(
+            suspended(&[], LIGHT_CLIENT_GAS),
+            Err(PrecompileError::other("suspended"))
+        );
+        assert_eq!(
+            suspended(&[], LIGHT_CLIENT_GAS - 1),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+}
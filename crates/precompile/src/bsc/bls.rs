@@ -0,0 +1,198 @@
+//! `blsSignatureVerify` precompile at `0x66`.
+//!
+//! Verifies a BLS signature of fast finality votes, signed by one or more validators. Added in the
+//! Luban hardfork.
+use crate::{
+    bls12_381::crypto_backend, u64_to_address, Precompile, PrecompileError, PrecompileId,
+    PrecompileOutput, PrecompileResult,
+};
+use primitives::Bytes;
+use std::{borrow::Cow, vec::Vec};
+
+/// Address of the `blsSignatureVerify` precompile.
+pub const BLS_SIGNATURE_VERIFY_ADDRESS: u64 = 0x66;
+
+/// Identifier of the `blsSignatureVerify` precompile.
+pub const BLS_SIGNATURE_VERIFY_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("blsSignatureVerify"));
+
+/// `blsSignatureVerify` precompile.
+pub const BLS_SIGNATURE_VERIFY: Precompile = Precompile::new(
+    BLS_SIGNATURE_VERIFY_ID,
+    u64_to_address(BLS_SIGNATURE_VERIFY_ADDRESS),
+    bls_signature_verify,
+);
+
+/// Base gas cost of the precompile.
+pub const BLS_SIGNATURE_VERIFY_BASE_GAS: u64 = 1_000;
+/// Gas cost per public key.
+pub const BLS_SIGNATURE_VERIFY_PER_KEY_GAS: u64 = 3_500;
+
+/// Length of the message hash.
+pub const MSG_HASH_LENGTH: usize = 32;
+/// Length of a compressed G2 signature.
+pub const SIGNATURE_LENGTH: usize = 96;
+/// Length of a compressed G1 public key.
+pub const PUBKEY_LENGTH: usize = 48;
+
+/// Domain separation tag of the signatures, same as in the Ethereum consensus layer.
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// `blsSignatureVerify` precompile logic.
+///
+/// | message hash | signature | `[public key]` |
+/// | :----------: | :-------: | :------------: |
+/// |      32      |    96     |    `[48]`      |
+///
+/// Returns `0x01` if the signature is valid and empty bytes if it is not. Inputs with an invalid
+/// length or points revert. The gas cost is [`BLS_SIGNATURE_VERIFY_BASE_GAS`] plus
+/// [`BLS_SIGNATURE_VERIFY_PER_KEY_GAS`] for every public key, inputs with an invalid length only
+/// pay the base gas.
+pub fn bls_signature_verify(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    const MSG_AND_SIGNATURE_LENGTH: usize = MSG_HASH_LENGTH + SIGNATURE_LENGTH;
+
+    let valid_length = input.len() > MSG_AND_SIGNATURE_LENGTH
+        && (input.len() - MSG_AND_SIGNATURE_LENGTH).is_multiple_of(PUBKEY_LENGTH);
+    let pubkeys_count = if valid_length {
+        (input.len() - MSG_AND_SIGNATURE_LENGTH) / PUBKEY_LENGTH
+    } else {
+        0
+    };
+    let gas_used =
+        BLS_SIGNATURE_VERIFY_BASE_GAS + pubkeys_count as u64 * BLS_SIGNATURE_VERIFY_PER_KEY_GAS;
+    if gas_used > gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+    if !valid_length {
+        return Ok(PrecompileOutput::new_reverted(gas_used, Bytes::new()));
+    }
+
+    let (msg, rest) = input.split_at(MSG_HASH_LENGTH);
+    let (signature, pubkeys) = rest.split_at(SIGNATURE_LENGTH);
+    let pubkeys = pubkeys
+        .chunks_exact(PUBKEY_LENGTH)
+        .map(|pubkey| pubkey.try_into().unwrap())
+        .collect::<Vec<_>>();
+
+    let output = match crypto_backend::fast_aggregate_verify(
+        msg,
+        DST,
+        signature.try_into().unwrap(),
+        &pubkeys,
+    ) {
+        Ok(true) => PrecompileOutput::new(gas_used, Bytes::from_static(&[1])),
+        Ok(false) => PrecompileOutput::new(gas_used, Bytes::new()),
+        Err(_) => PrecompileOutput::new_reverted(gas_used, Bytes::new()),
+    };
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::hex;
+
+    // Vectors of the `sign` and `fast_aggregate_verify` tests of the Ethereum consensus specs, which
+    // use the same ciphersuite as the votes of the BSC validators.
+    const MSG: [u8; 32] = [0xab; 32];
+    const PUBKEYS: [[u8; 48]; 3] = [
+        hex!("b53d21a4cfd562c469cc81514d4ce5a6b577d8403d32a394dc265dd190b47fa9f829fdd7963afdf972e5e77854051f6f"),
+        hex!("a491d1b0ecd9bb917989f0e74f0dea0422eac4a873e5e2644f368dffb9a6e20fd6e10c1b77654d067c0618f6e5a7f79a"),
+        hex!("b301803f8b5ac4a1133581fc676dfedc60d891dd5fa99028805e5ea5b08d3491af75d0707adab3b70c6a6a580217bf81"),
+    ];
+    /// Signature of [`MSG`] by the first key.
+    const SIGNATURE: [u8; 96] = hex!("ae82747ddeefe4fd64cf9cedb9b04ae3e8a43420cd255e3c7cd06a8d88b7c7f8638543719981c5d16fa3527c468c25f0026704a6951bde891360c7e8d12ddee0559004ccdbe6046b55bae1b257ee97f7cdb955773d7cf29adf3ccbb9975e4eb9");
+    /// Aggregated signature of [`MSG`] by all keys.
+    const AGGREGATED_SIGNATURE: [u8; 96] = hex!("9712c3edd73a209c742b8250759db12549b3eaf43b5ca61376d9f30e2747dbcf842d8b2ac0901d2a093713e20284a7670fcf6954e9ab93de991bb9b313e664785a075fc285806fa5224c82bde146561b446ccfc706a64b8579513cfc4ff1d930");
+
+    fn input(msg: &[u8; 32], signature: &[u8; 96], pubkeys: &[[u8; 48]]) -> Vec<u8> {
+        let mut input = [&msg[..], &signature[..]].concat();
+        for pubkey in pubkeys {
+            input.extend_from_slice(pubkey);
+        }
+        input
+    }
+
+    #[test]
+    fn test_bls_signature_verify() {
+        let output = bls_signature_verify(&input(&MSG, &SIGNATURE, &PUBKEYS[..1]), 4_500).unwrap();
+        assert_eq!(output.gas_used, 4_500);
+        assert_eq!(output.bytes, Bytes::from_static(&[1]));
+
+        let output =
+            bls_signature_verify(&input(&MSG, &AGGREGATED_SIGNATURE, &PUBKEYS), 11_500).unwrap();
+        assert_eq!(output.gas_used, 11_500);
+        assert_eq!(output.bytes, Bytes::from_static(&[1]));
+
+        // Wrong signer or message.
+        let output = bls_signature_verify(&input(&MSG, &SIGNATURE, &PUBKEYS[1..2]), 4_500).unwrap();
+        assert!(output.bytes.is_empty());
+        let output =
+            bls_signature_verify(&input(&[0; 32], &AGGREGATED_SIGNATURE, &PUBKEYS), 11_500)
+                .unwrap();
+        assert!(output.bytes.is_empty());
+
+        assert_eq!(
+            bls_signature_verify(&input(&MSG, &AGGREGATED_SIGNATURE, &PUBKEYS), 11_499),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_bls_signature_verify_invalid_input() {
+        let reverted = |gas_used| Ok(PrecompileOutput::new_reverted(gas_used, Bytes::new()));
+        // No public key or a partial public key.
+        assert_eq!(
+            bls_signature_verify(&input(&MSG, &SIGNATURE, &[]), 1_000),
+            reverted(1_000)
+        );
+        let input_bytes = input(&MSG, &SIGNATURE, &PUBKEYS[..1]);
+        assert_eq!(
+            bls_signature_verify(&input_bytes[..input_bytes.len() - 1], 1_000),
+            reverted(1_000)
+        );
+        // Invalid points.
+        assert_eq!(
+            bls_signature_verify(&input(&MSG, &[0; 96], &PUBKEYS[..1]), 4_500),
+            reverted(4_500)
+        );
+        assert_eq!(
+            bls_signature_verify(&input(&MSG, &SIGNATURE, &[[0; 48]]), 4_500),
+            reverted(4_500)
+        );
+        // Point at infinity as public key.
+        let mut infinity = [0; 48];
+        infinity[0] = 0xc0;
+        assert_eq!(
+            bls_signature_verify(&input(&MSG, &SIGNATURE, &[infinity]), 4_500),
+            reverted(4_500)
+        );
+        assert_eq!(
+            crypto_backend::fast_aggregate_verify(&MSG, DST, &SIGNATURE, &[infinity]),
+            Err(PrecompileError::other("invalid bls public key"))
+        );
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        use crate::bls12_381::arkworks;
+
+        for (signature, pubkeys) in [
+            (&SIGNATURE, &PUBKEYS[..1]),
+            (&AGGREGATED_SIGNATURE, &PUBKEYS),
+        ] {
+            assert_eq!(
+                arkworks::fast_aggregate_verify(&MSG, DST, signature, pubkeys),
+                Ok(true)
+            );
+            assert_eq!(
+                crypto_backend::fast_aggregate_verify(&MSG, DST, signature, pubkeys),
+                Ok(true)
+            );
+            assert_eq!(
+                arkworks::fast_aggregate_verify(&[0; 32], DST, signature, pubkeys),
+                Ok(false)
+            );
+        }
+    }
+}
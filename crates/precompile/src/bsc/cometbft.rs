@@ -0,0 +1,209 @@
+//! `cometBFTLightBlockValidate` precompile at `0x67`.
+//!
+//! Validates a CometBFT light block of Greenfield against a light client consensus state and
+//! returns the updated consensus state. Added in the Luban hardfork.
+use super::{charge, read_length, tendermint, unsupported, LENGTH_WORD};
+use crate::{u64_to_address, Precompile, PrecompileError, PrecompileId, PrecompileResult};
+use primitives::{Address, B256};
+use std::{borrow::Cow, string::String, vec::Vec};
+
+/// Address of the `cometBFTLightBlockValidate` precompile.
+pub const COMETBFT_LIGHT_BLOCK_VALIDATE_ADDRESS: u64 = 0x67;
+
+/// Identifier of the `cometBFTLightBlockValidate` precompile.
+pub const COMETBFT_LIGHT_BLOCK_VALIDATE_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("cometBFTLightBlockValidate"));
+
+/// `cometBFTLightBlockValidate` precompile.
+pub const COMETBFT_LIGHT_BLOCK_VALIDATE: Precompile = Precompile::new(
+    COMETBFT_LIGHT_BLOCK_VALIDATE_ID,
+    u64_to_address(COMETBFT_LIGHT_BLOCK_VALIDATE_ADDRESS),
+    cometbft_light_block_validate,
+);
+
+/// Length of the chain id, right padded with zeros.
+pub const CHAIN_ID_LENGTH: usize = 32;
+/// Length of the height.
+pub const HEIGHT_LENGTH: usize = 8;
+/// Length of the next validator set hash.
+pub const VALIDATOR_SET_HASH_LENGTH: usize = 32;
+/// Length of an ed25519 validator public key.
+pub const VALIDATOR_PUBKEY_LENGTH: usize = 32;
+/// Length of the voting power of a validator.
+pub const VALIDATOR_VOTING_POWER_LENGTH: usize = 8;
+/// Length of the relayer address of a validator.
+pub const RELAYER_ADDRESS_LENGTH: usize = 20;
+/// Length of the relayer BLS public key of a validator.
+pub const RELAYER_BLS_KEY_LENGTH: usize = 48;
+
+/// CometBFT validator of a [`ConsensusState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    /// Ed25519 public key.
+    pub pub_key: [u8; VALIDATOR_PUBKEY_LENGTH],
+    /// Voting power.
+    pub voting_power: i64,
+    /// Address of the relayer of the validator.
+    pub relayer_address: Address,
+    /// BLS public key of the relayer of the validator.
+    pub relayer_bls_key: [u8; RELAYER_BLS_KEY_LENGTH],
+}
+
+/// Light client consensus state of Greenfield.
+///
+/// Encoded as:
+///
+/// | chain id | height | next validator set hash | `[{pubkey, voting power, relayer address, relayer bls key}]` |
+/// | :------: | :----: | :---------------------: | :----------------------------------------------------------: |
+/// |    32    |   8    |           32            |                      `[{32, 8, 20, 48}]`                     |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+    /// Chain id.
+    pub chain_id: String,
+    /// Height of the last validated light block.
+    pub height: u64,
+    /// Hash of the next validator set.
+    pub next_validator_set_hash: B256,
+    /// Current validator set.
+    pub validators: Vec<Validator>,
+}
+
+impl ConsensusState {
+    const MIN_LENGTH: usize = CHAIN_ID_LENGTH + HEIGHT_LENGTH + VALIDATOR_SET_HASH_LENGTH;
+    const VALIDATOR_LENGTH: usize = VALIDATOR_PUBKEY_LENGTH
+        + VALIDATOR_VOTING_POWER_LENGTH
+        + RELAYER_ADDRESS_LENGTH
+        + RELAYER_BLS_KEY_LENGTH;
+
+    /// Decodes the consensus state, at least one validator is required.
+    pub fn decode(input: &[u8]) -> Result<Self, PrecompileError> {
+        if input.len() <= Self::MIN_LENGTH
+            || !(input.len() - Self::MIN_LENGTH).is_multiple_of(Self::VALIDATOR_LENGTH)
+        {
+            return Err(PrecompileError::Other(format!(
+                "expected input size {}+{}*N, actual input size: {}",
+                Self::MIN_LENGTH,
+                Self::VALIDATOR_LENGTH,
+                input.len()
+            )));
+        }
+        let (chain_id, rest) = input.split_at(CHAIN_ID_LENGTH);
+        let (height, rest) = rest.split_at(HEIGHT_LENGTH);
+        let (next_validator_set_hash, validators) = rest.split_at(VALIDATOR_SET_HASH_LENGTH);
+
+        let validators = validators
+            .chunks_exact(Self::VALIDATOR_LENGTH)
+            .map(|validator| {
+                let (pub_key, rest) = validator.split_at(VALIDATOR_PUBKEY_LENGTH);
+                let (voting_power, rest) = rest.split_at(VALIDATOR_VOTING_POWER_LENGTH);
+                let (relayer_address, relayer_bls_key) = rest.split_at(RELAYER_ADDRESS_LENGTH);
+                Validator {
+                    pub_key: pub_key.try_into().unwrap(),
+                    voting_power: u64::from_be_bytes(voting_power.try_into().unwrap()) as i64,
+                    relayer_address: Address::from_slice(relayer_address),
+                    relayer_bls_key: relayer_bls_key.try_into().unwrap(),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            chain_id: tendermint::trim_zeros(chain_id),
+            height: u64::from_be_bytes(height.try_into().unwrap()),
+            next_validator_set_hash: B256::from_slice(next_validator_set_hash),
+            validators,
+        })
+    }
+
+    /// Encodes the consensus state.
+    ///
+    /// Fails if the chain id is longer than 32 bytes.
+    pub fn encode(&self) -> Result<Vec<u8>, PrecompileError> {
+        let mut out =
+            Vec::with_capacity(Self::MIN_LENGTH + self.validators.len() * Self::VALIDATOR_LENGTH);
+        out.extend_from_slice(&tendermint::pad_chain_id(&self.chain_id)?);
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(self.next_validator_set_hash.as_slice());
+        for validator in &self.validators {
+            out.extend_from_slice(&validator.pub_key);
+            out.extend_from_slice(&(validator.voting_power as u64).to_be_bytes());
+            out.extend_from_slice(validator.relayer_address.as_slice());
+            out.extend_from_slice(&validator.relayer_bls_key);
+        }
+        Ok(out)
+    }
+}
+
+/// `cometBFTLightBlockValidate` precompile logic.
+///
+/// Unlike the other light client precompiles the input has no payload length:
+///
+/// | consensus state length | consensus state | protobuf encoded light block |
+/// | :--------------------: | :-------------: | :--------------------------: |
+/// |           32           |                 |                              |
+///
+/// The output has the format of [`tendermint::encode_output`].
+pub fn cometbft_light_block_validate(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    charge(gas_limit)?;
+    let cs_length = read_length(input)
+        .ok_or_else(|| PrecompileError::other("invalid input: missing consensus state length"))?;
+    let cs_end = cs_length
+        .checked_add(LENGTH_WORD as u64)
+        .ok_or_else(|| PrecompileError::Other(format!("integer overflow, csLen: {cs_length}")))?;
+    if input.len() as u64 <= cs_end {
+        return Err(PrecompileError::Other(format!(
+            "expected input size {cs_end}, actual size: {}",
+            input.len()
+        )));
+    }
+    let _consensus_state = ConsensusState::decode(&input[LENGTH_WORD..cs_end as usize])?;
+    Err(unsupported("cometbft light block"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsc::length_word;
+
+    fn consensus_state() -> ConsensusState {
+        ConsensusState {
+            chain_id: "greenfield_1017-1".into(),
+            height: 7,
+            next_validator_set_hash: B256::repeat_byte(1),
+            validators: vec![Validator {
+                pub_key: [2; 32],
+                voting_power: 1_000,
+                relayer_address: Address::repeat_byte(3),
+                relayer_bls_key: [4; 48],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_consensus_state_roundtrip() {
+        let cs = consensus_state();
+        let encoded = cs.encode().unwrap();
+        assert_eq!(encoded.len(), 72 + 108);
+        assert_eq!(ConsensusState::decode(&encoded), Ok(cs));
+        assert!(ConsensusState::decode(&encoded[..72]).is_err());
+        assert!(ConsensusState::decode(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_cometbft_light_block_validate_input() {
+        let cs = consensus_state().encode().unwrap();
+        let mut input = length_word(cs.len() as u64).to_vec();
+        input.extend_from_slice(&cs);
+        // Light block is missing.
+        assert!(cometbft_light_block_validate(&input, 3_000).is_err());
+
+        input.push(0);
+        assert_eq!(
+            cometbft_light_block_validate(&input, 3_000),
+            Err(unsupported("cometbft light block"))
+        );
+        assert_eq!(
+            cometbft_light_block_validate(&input, 2_999),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+}
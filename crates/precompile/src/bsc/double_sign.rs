@@ -0,0 +1,561 @@
+//! `verifyDoubleSignEvidence` precompile at `0x68`.
+//!
+//! Verifies that a validator sealed two different headers at the same height, used by the slash
+//! contract. Added in the Feynman hardfork.
+use crate::{
+    crypto, u64_to_address, Precompile, PrecompileError, PrecompileId, PrecompileOutput,
+    PrecompileResult,
+};
+use alloy_rlp::{Decodable, Encodable};
+use primitives::{
+    alloy_primitives::{Bloom, B64},
+    keccak256, Address, Bytes, B256, U256,
+};
+use std::{borrow::Cow, vec::Vec};
+
+/// Address of the `verifyDoubleSignEvidence` precompile.
+pub const VERIFY_DOUBLE_SIGN_EVIDENCE_ADDRESS: u64 = 0x68;
+
+/// Identifier of the `verifyDoubleSignEvidence` precompile.
+pub const VERIFY_DOUBLE_SIGN_EVIDENCE_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("verifyDoubleSignEvidence"));
+
+/// `verifyDoubleSignEvidence` precompile.
+pub const VERIFY_DOUBLE_SIGN_EVIDENCE: Precompile = Precompile::new(
+    VERIFY_DOUBLE_SIGN_EVIDENCE_ID,
+    u64_to_address(VERIFY_DOUBLE_SIGN_EVIDENCE_ADDRESS),
+    verify_double_sign_evidence,
+);
+
+/// Gas cost of the precompile.
+pub const VERIFY_DOUBLE_SIGN_EVIDENCE_GAS: u64 = 10_000;
+
+/// Length of the seal at the end of the extra data of a header, `r | s | v`.
+pub const EXTRA_SEAL_LENGTH: usize = 65;
+
+/// Evidence of a validator sealing two headers at the same height.
+///
+/// RLP encoded as `[chain id, rlp(header1), rlp(header2)]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DoubleSignEvidence {
+    /// Chain id the headers were sealed for.
+    pub chain_id: U256,
+    /// First RLP encoded header.
+    pub header1: Bytes,
+    /// Second RLP encoded header.
+    pub header2: Bytes,
+}
+
+impl Decodable for DoubleSignEvidence {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut payload = alloy_rlp::Header::decode_bytes(buf, true)?;
+        let this = Self {
+            chain_id: Decodable::decode(&mut payload)?,
+            header1: Decodable::decode(&mut payload)?,
+            header2: Decodable::decode(&mut payload)?,
+        };
+        if !payload.is_empty() {
+            return Err(alloy_rlp::Error::Custom("trailing fields"));
+        }
+        Ok(this)
+    }
+}
+
+impl Encodable for DoubleSignEvidence {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        encode_list(&[&self.chain_id, &self.header1, &self.header2], out);
+    }
+}
+
+/// Block header, as sealed by the Parlia consensus.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    /// Parent hash.
+    pub parent_hash: B256,
+    /// Ommers hash.
+    pub ommers_hash: B256,
+    /// Beneficiary, the validator that sealed the header.
+    pub beneficiary: Address,
+    /// State root.
+    pub state_root: B256,
+    /// Transactions root.
+    pub transactions_root: B256,
+    /// Receipts root.
+    pub receipts_root: B256,
+    /// Logs bloom.
+    pub logs_bloom: Bloom,
+    /// Difficulty.
+    pub difficulty: U256,
+    /// Block number.
+    pub number: U256,
+    /// Gas limit.
+    pub gas_limit: u64,
+    /// Gas used.
+    pub gas_used: u64,
+    /// Timestamp.
+    pub timestamp: u64,
+    /// Extra data, ends with the seal of the validator.
+    pub extra_data: Bytes,
+    /// Mix hash.
+    pub mix_hash: B256,
+    /// Nonce.
+    pub nonce: B64,
+    /// Base fee, since London.
+    pub base_fee_per_gas: Option<U256>,
+    /// Withdrawals root, since Shanghai.
+    pub withdrawals_root: Option<B256>,
+    /// Blob gas used, since Cancun.
+    pub blob_gas_used: Option<u64>,
+    /// Excess blob gas, since Cancun.
+    pub excess_blob_gas: Option<u64>,
+    /// Parent beacon block root, since Cancun.
+    pub parent_beacon_block_root: Option<B256>,
+    /// Requests hash, since Prague.
+    pub requests_hash: Option<B256>,
+}
+
+impl Header {
+    /// Returns the seal of the header, the last [`EXTRA_SEAL_LENGTH`] bytes of the extra data.
+    pub fn seal(&self) -> Option<&[u8]> {
+        let start = self.extra_data.len().checked_sub(EXTRA_SEAL_LENGTH)?;
+        Some(&self.extra_data[start..])
+    }
+
+    /// Returns the hash the validator signs, the hash of the header without the seal.
+    ///
+    /// Returns `None` if the extra data is shorter than the seal.
+    pub fn seal_hash(&self, chain_id: U256) -> Option<B256> {
+        let extra_data =
+            &self.extra_data[..self.extra_data.len().checked_sub(EXTRA_SEAL_LENGTH)?];
+        let mut fields: Vec<&dyn Encodable> = vec![
+            &chain_id,
+            &self.parent_hash,
+            &self.ommers_hash,
+            &self.beneficiary,
+            &self.state_root,
+            &self.transactions_root,
+            &self.receipts_root,
+            &self.logs_bloom,
+            &self.difficulty,
+            &self.number,
+            &self.gas_limit,
+            &self.gas_used,
+            &self.timestamp,
+            &extra_data,
+            &self.mix_hash,
+            &self.nonce,
+        ];
+        // Fields added after Cancun are sealed once the parent beacon block root is set, it is
+        // always zero on BNB Smart Chain. Missing fields are encoded as empty strings.
+        if self.parent_beacon_block_root == Some(B256::ZERO) {
+            let empty: &dyn Encodable = &[0u8; 0];
+            fields.extend([
+                self.base_fee_per_gas.as_ref().map_or(empty, |v| v as _),
+                self.withdrawals_root.as_ref().map_or(empty, |v| v as _),
+                self.blob_gas_used.as_ref().map_or(empty, |v| v as _),
+                self.excess_blob_gas.as_ref().map_or(empty, |v| v as _),
+                self.parent_beacon_block_root
+                    .as_ref()
+                    .map_or(empty, |v| v as _),
+            ]);
+            if let Some(requests_hash) = &self.requests_hash {
+                fields.push(requests_hash);
+            }
+        }
+
+        let mut out = Vec::new();
+        encode_list(&fields, &mut out);
+        Some(keccak256(out))
+    }
+}
+
+impl Decodable for Header {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut payload = alloy_rlp::Header::decode_bytes(buf, true)?;
+        let buf = &mut payload;
+        let mut this = Self {
+            parent_hash: Decodable::decode(buf)?,
+            ommers_hash: Decodable::decode(buf)?,
+            beneficiary: Decodable::decode(buf)?,
+            state_root: Decodable::decode(buf)?,
+            transactions_root: Decodable::decode(buf)?,
+            receipts_root: Decodable::decode(buf)?,
+            logs_bloom: Decodable::decode(buf)?,
+            difficulty: Decodable::decode(buf)?,
+            number: Decodable::decode(buf)?,
+            gas_limit: Decodable::decode(buf)?,
+            gas_used: Decodable::decode(buf)?,
+            timestamp: Decodable::decode(buf)?,
+            extra_data: Decodable::decode(buf)?,
+            mix_hash: Decodable::decode(buf)?,
+            nonce: Decodable::decode(buf)?,
+            ..Default::default()
+        };
+
+        // Optional fields of later hardforks.
+        if !buf.is_empty() {
+            this.base_fee_per_gas = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            this.withdrawals_root = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            this.blob_gas_used = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            this.excess_blob_gas = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            this.parent_beacon_block_root = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            this.requests_hash = Some(Decodable::decode(buf)?);
+        }
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::Custom("trailing fields"));
+        }
+        Ok(this)
+    }
+}
+
+/// Encodes the fields as an RLP list.
+fn encode_list(fields: &[&dyn Encodable], out: &mut dyn alloy_rlp::BufMut) {
+    alloy_rlp::Header {
+        list: true,
+        payload_length: fields.iter().map(|field| field.length()).sum(),
+    }
+    .encode(out);
+    for field in fields {
+        field.encode(out);
+    }
+}
+
+/// Recovers the address of the validator that sealed the header.
+fn recover_signer(header: &Header, chain_id: U256) -> Option<Address> {
+    let seal = header.seal()?;
+    let seal_hash = header.seal_hash(chain_id)?;
+    let recid = seal[64];
+    if recid > 3 {
+        return None;
+    }
+    let address = crypto()
+        .secp256k1_ecrecover(seal[..64].try_into().unwrap(), recid, &seal_hash.0)
+        .ok()?;
+    Some(Address::from_slice(&address[12..]))
+}
+
+/// Verifies the evidence and returns the validator and the height of the headers.
+///
+/// The headers must have the same number and parent, different seals and seal hashes and must be
+/// sealed by the same validator.
+pub fn verify_evidence(evidence: &DoubleSignEvidence) -> Result<(Address, U256), PrecompileError> {
+    let invalid = |reason: &str| PrecompileError::Other(format!("invalid evidence: {reason}"));
+
+    let header1: Header = alloy_rlp::decode_exact(&evidence.header1)
+        .map_err(|_| invalid("header1 decoding failed"))?;
+    let header2: Header = alloy_rlp::decode_exact(&evidence.header2)
+        .map_err(|_| invalid("header2 decoding failed"))?;
+
+    if header1.number != header2.number {
+        return Err(invalid("different numbers"));
+    }
+    if header1.parent_hash != header2.parent_hash {
+        return Err(invalid("different parents"));
+    }
+    let (Some(seal1), Some(seal2)) = (header1.seal(), header2.seal()) else {
+        return Err(invalid("missing seal"));
+    };
+    if seal1 == seal2 {
+        return Err(invalid("same seal"));
+    }
+    if header1.seal_hash(evidence.chain_id) == header2.seal_hash(evidence.chain_id) {
+        return Err(invalid("same seal hash"));
+    }
+
+    let signer1 = recover_signer(&header1, evidence.chain_id);
+    let signer2 = recover_signer(&header2, evidence.chain_id);
+    match (signer1, signer2) {
+        (Some(signer1), Some(signer2)) if signer1 == signer2 => Ok((signer1, header1.number)),
+        (Some(_), Some(_)) => Err(invalid("different signers")),
+        _ => Err(invalid("signature recovery failed")),
+    }
+}
+
+/// `verifyDoubleSignEvidence` precompile logic.
+///
+/// The input is the RLP encoded [`DoubleSignEvidence`], the output is:
+///
+/// | signer | height |
+/// | :----: | :----: |
+/// |   20   |   32   |
+///
+/// Invalid evidence reverts.
+pub fn verify_double_sign_evidence(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if VERIFY_DOUBLE_SIGN_EVIDENCE_GAS > gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+
+    let result = alloy_rlp::decode_exact::<DoubleSignEvidence>(input)
+        .map_err(|_| PrecompileError::other("invalid evidence: decoding failed"))
+        .and_then(|evidence| verify_evidence(&evidence));
+    let Ok((signer, height)) = result else {
+        return Ok(PrecompileOutput::new_reverted(
+            VERIFY_DOUBLE_SIGN_EVIDENCE_GAS,
+            Bytes::new(),
+        ));
+    };
+
+    let mut output = Vec::with_capacity(52);
+    output.extend_from_slice(signer.as_slice());
+    output.extend_from_slice(&height.to_be_bytes::<32>());
+    Ok(PrecompileOutput::new(
+        VERIFY_DOUBLE_SIGN_EVIDENCE_GAS,
+        output.into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    const CHAIN_ID: U256 = U256::from_limbs([56, 0, 0, 0]);
+
+    fn sealed_header(key: &SigningKey, state_root: B256, cancun: bool) -> Header {
+        let mut header = Header {
+            parent_hash: B256::repeat_byte(1),
+            beneficiary: Address::repeat_byte(2),
+            state_root,
+            difficulty: U256::from(2),
+            number: U256::from(40_000_000),
+            gas_limit: 140_000_000,
+            timestamp: 1_700_000_000,
+            extra_data: Bytes::from(vec![0; 32 + EXTRA_SEAL_LENGTH]),
+            ..Default::default()
+        };
+        if cancun {
+            header.base_fee_per_gas = Some(U256::ZERO);
+            header.withdrawals_root = Some(B256::repeat_byte(3));
+            header.blob_gas_used = Some(0);
+            header.excess_blob_gas = Some(0);
+            header.parent_beacon_block_root = Some(B256::ZERO);
+        }
+
+        let seal_hash = header.seal_hash(CHAIN_ID).unwrap();
+        let (sig, recid) = key.sign_prehash_recoverable(seal_hash.as_slice()).unwrap();
+        let mut extra_data = header.extra_data.to_vec();
+        extra_data[32..96].copy_from_slice(&sig.to_bytes());
+        extra_data[96] = recid.to_byte();
+        header.extra_data = extra_data.into();
+        header
+    }
+
+    fn encode(header: &Header) -> Bytes {
+        let mut fields: Vec<&dyn Encodable> = vec![
+            &header.parent_hash,
+            &header.ommers_hash,
+            &header.beneficiary,
+            &header.state_root,
+            &header.transactions_root,
+            &header.receipts_root,
+            &header.logs_bloom,
+            &header.difficulty,
+            &header.number,
+            &header.gas_limit,
+            &header.gas_used,
+            &header.timestamp,
+            &header.extra_data,
+            &header.mix_hash,
+            &header.nonce,
+        ];
+        for field in [
+            header
+                .base_fee_per_gas
+                .as_ref()
+                .map(|v| v as &dyn Encodable),
+            header.withdrawals_root.as_ref().map(|v| v as _),
+            header.blob_gas_used.as_ref().map(|v| v as _),
+            header.excess_blob_gas.as_ref().map(|v| v as _),
+            header.parent_beacon_block_root.as_ref().map(|v| v as _),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            fields.push(field);
+        }
+        let mut out = Vec::new();
+        encode_list(&fields, &mut out);
+        out.into()
+    }
+
+    fn evidence(header1: &Header, header2: &Header) -> Vec<u8> {
+        alloy_rlp::encode(DoubleSignEvidence {
+            chain_id: CHAIN_ID,
+            header1: encode(header1),
+            header2: encode(header2),
+        })
+    }
+
+    fn signer(key: &SigningKey) -> Address {
+        let pubkey = key.verifying_key().to_encoded_point(false);
+        Address::from_slice(&keccak256(&pubkey.as_bytes()[1..])[12..])
+    }
+
+    /// RLP encodes a string of at most 55 bytes.
+    fn rlp_string(bytes: &[u8]) -> Vec<u8> {
+        assert!(bytes.len() <= 55);
+        [&[0x80 + bytes.len() as u8][..], bytes].concat()
+    }
+
+    /// Sign preimage of `encodeSigHeader` in the BSC client, written out field by field for the
+    /// header of [`test_seal_hash_encoding`].
+    fn sig_preimage(fields_since_cancun: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = [
+            vec![0x38],                                    // chain id 56
+            rlp_string(&[1; 32]),                          // parent hash
+            rlp_string(&[0; 32]),                          // ommers hash
+            rlp_string(&[2; 20]),                          // beneficiary
+            rlp_string(&[4; 32]),                          // state root
+            rlp_string(&[0; 32]),                          // transactions root
+            rlp_string(&[0; 32]),                          // receipts root
+            [&[0xb9, 0x01, 0x00][..], &[0; 256]].concat(), // logs bloom
+            vec![0x02],                                    // difficulty
+            rlp_string(&40_000_000u32.to_be_bytes()),      // number
+            rlp_string(&140_000_000u32.to_be_bytes()),     // gas limit
+            vec![0x80],                                    // gas used
+            rlp_string(&1_700_000_000u32.to_be_bytes()),   // timestamp
+            rlp_string(&[9; 32]),                          // extra data without the seal
+            rlp_string(&[0; 32]),                          // mix hash
+            rlp_string(&[0; 8]),                           // nonce
+        ]
+        .concat();
+        payload.extend(fields_since_cancun.concat());
+        let len = u16::try_from(payload.len()).unwrap();
+        [&[0xf9][..], &len.to_be_bytes(), &payload].concat()
+    }
+
+    #[test]
+    fn test_seal_hash_encoding() {
+        let mut extra_data = vec![9; 32];
+        extra_data.extend_from_slice(&[0xee; EXTRA_SEAL_LENGTH]);
+        let mut header = Header {
+            parent_hash: B256::repeat_byte(1),
+            beneficiary: Address::repeat_byte(2),
+            state_root: B256::repeat_byte(4),
+            difficulty: U256::from(2),
+            number: U256::from(40_000_000),
+            gas_limit: 140_000_000,
+            timestamp: 1_700_000_000,
+            extra_data: extra_data.into(),
+            base_fee_per_gas: Some(U256::ZERO),
+            ..Default::default()
+        };
+        // The base fee is not sealed before Cancun.
+        assert_eq!(
+            header.seal_hash(CHAIN_ID),
+            Some(keccak256(sig_preimage(&[])))
+        );
+
+        header.withdrawals_root = Some(B256::repeat_byte(3));
+        header.blob_gas_used = Some(0x20000);
+        header.excess_blob_gas = Some(0);
+        header.parent_beacon_block_root = Some(B256::ZERO);
+        let cancun = [
+            vec![0x80],                      // base fee
+            rlp_string(&[3; 32]),            // withdrawals root
+            rlp_string(&[0x02, 0x00, 0x00]), // blob gas used
+            vec![0x80],                      // excess blob gas
+            rlp_string(&[0; 32]),            // parent beacon block root
+        ];
+        assert_eq!(
+            header.seal_hash(CHAIN_ID),
+            Some(keccak256(sig_preimage(&cancun)))
+        );
+
+        header.requests_hash = Some(B256::repeat_byte(6));
+        let prague = [&cancun[..], &[rlp_string(&[6; 32])]].concat();
+        assert_eq!(
+            header.seal_hash(CHAIN_ID),
+            Some(keccak256(sig_preimage(&prague)))
+        );
+
+        // A non-zero parent beacon block root keeps the fields of before Cancun.
+        header.parent_beacon_block_root = Some(B256::repeat_byte(5));
+        assert_eq!(
+            header.seal_hash(CHAIN_ID),
+            Some(keccak256(sig_preimage(&[])))
+        );
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        for cancun in [false, true] {
+            let header = sealed_header(&key, B256::repeat_byte(4), cancun);
+            assert_eq!(alloy_rlp::decode_exact(encode(&header)), Ok(header));
+        }
+    }
+
+    #[test]
+    fn test_verify_double_sign_evidence() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        for cancun in [false, true] {
+            let header1 = sealed_header(&key, B256::repeat_byte(4), cancun);
+            let header2 = sealed_header(&key, B256::repeat_byte(5), cancun);
+
+            let output =
+                verify_double_sign_evidence(&evidence(&header1, &header2), 10_000).unwrap();
+            assert!(!output.reverted);
+            assert_eq!(output.gas_used, 10_000);
+            assert_eq!(output.bytes[..20], signer(&key)[..]);
+            assert_eq!(
+                U256::from_be_slice(&output.bytes[20..]),
+                U256::from(40_000_000)
+            );
+        }
+
+        let header1 = sealed_header(&key, B256::repeat_byte(4), false);
+        let header2 = sealed_header(&key, B256::repeat_byte(5), false);
+        assert_eq!(
+            verify_double_sign_evidence(&evidence(&header1, &header2), 9_999),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_invalid_evidence() {
+        let key = SigningKey::from_slice(&[7; 32]).unwrap();
+        let other_key = SigningKey::from_slice(&[8; 32]).unwrap();
+        let header1 = sealed_header(&key, B256::repeat_byte(4), false);
+        let header2 = sealed_header(&key, B256::repeat_byte(5), false);
+        let reverted = Ok(PrecompileOutput::new_reverted(10_000, Bytes::new()));
+
+        // Same header.
+        assert_eq!(
+            verify_double_sign_evidence(&evidence(&header1, &header1), 10_000),
+            reverted
+        );
+        // Different signers.
+        let other = sealed_header(&other_key, B256::repeat_byte(5), false);
+        assert_eq!(
+            verify_double_sign_evidence(&evidence(&header1, &other), 10_000),
+            reverted
+        );
+        // Different heights.
+        let mut next = header2.clone();
+        next.number += U256::from(1);
+        assert_eq!(
+            verify_double_sign_evidence(&evidence(&header1, &next), 10_000),
+            reverted
+        );
+        // Missing seal.
+        let mut unsealed = header2;
+        unsealed.extra_data = Bytes::from(vec![0; 64]);
+        assert_eq!(
+            verify_double_sign_evidence(&evidence(&header1, &unsealed), 10_000),
+            reverted
+        );
+        // Not RLP.
+        assert_eq!(verify_double_sign_evidence(&[1, 2, 3], 10_000), reverted);
+    }
+}
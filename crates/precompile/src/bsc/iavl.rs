@@ -0,0 +1,173 @@
+//! `iavlMerkleProofValidate` precompile at `0x65`.
+//!
+//! Validates an IAVL merkle proof of a key value pair in a store of the BNB Beacon Chain against
+//! an application hash.
+//!
+//! The Moran, Planck and Plato hardforks tightened the accepted proofs after the proof forgery
+//! exploits, the precompile keeps its address and input format in all of them.
+use super::{charge, payload, read_length, unsupported, LENGTH_WORD};
+use crate::{u64_to_address, Precompile, PrecompileError, PrecompileId, PrecompileResult};
+use primitives::B256;
+use std::borrow::Cow;
+
+/// Address of the `iavlMerkleProofValidate` precompile.
+pub const IAVL_MERKLE_PROOF_VALIDATE_ADDRESS: u64 = 0x65;
+
+/// Identifier of the `iavlMerkleProofValidate` precompile.
+pub const IAVL_MERKLE_PROOF_VALIDATE_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("iavlMerkleProofValidate"));
+
+/// `iavlMerkleProofValidate` precompile, active from genesis until the Nano hardfork and again
+/// since the Moran hardfork.
+pub const IAVL_MERKLE_PROOF_VALIDATE: Precompile = Precompile::new(
+    IAVL_MERKLE_PROOF_VALIDATE_ID,
+    u64_to_address(IAVL_MERKLE_PROOF_VALIDATE_ADDRESS),
+    iavl_merkle_proof_validate,
+);
+
+/// `iavlMerkleProofValidate` precompile suspended by the Nano hardfork.
+pub const IAVL_MERKLE_PROOF_VALIDATE_NANO: Precompile = Precompile::new(
+    IAVL_MERKLE_PROOF_VALIDATE_ID,
+    u64_to_address(IAVL_MERKLE_PROOF_VALIDATE_ADDRESS),
+    super::suspended,
+);
+
+/// Length of the store name, right padded with zeros.
+pub const STORE_NAME_LENGTH: usize = 32;
+/// Length of the application hash.
+pub const APP_HASH_LENGTH: usize = 32;
+
+/// Key value pair with the proof of its inclusion in a store.
+///
+/// | store name | key length | key | value length | value | app hash | proof |
+/// | :--------: | :--------: | :-: | :----------: | :---: | :------: | :---: |
+/// |     32     |     32     |     |      32      |       |    32    |       |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyValueMerkleProof<'a> {
+    /// Name of the store.
+    pub store_name: &'a [u8],
+    /// Key in the store.
+    pub key: &'a [u8],
+    /// Value of the key.
+    pub value: &'a [u8],
+    /// Application hash the proof is verified against.
+    pub app_hash: B256,
+    /// Encoded merkle proof.
+    pub proof: &'a [u8],
+}
+
+impl<'a> KeyValueMerkleProof<'a> {
+    /// Decodes the key value pair and its proof, the proof must not be empty.
+    pub fn decode(input: &'a [u8]) -> Result<Self, PrecompileError> {
+        let (store_name, rest) = split(input, STORE_NAME_LENGTH)?;
+        let (key, rest) = split_prefixed(rest)?;
+        let (value, rest) = split_prefixed(rest)?;
+        let (app_hash, proof) = split(rest, APP_HASH_LENGTH)?;
+        if proof.is_empty() {
+            return Err(PrecompileError::other(
+                "invalid input: missing merkle proof",
+            ));
+        }
+        let end = store_name
+            .iter()
+            .rposition(|b| *b != 0)
+            .map_or(0, |i| i + 1);
+        Ok(Self {
+            store_name: &store_name[..end],
+            key,
+            value,
+            app_hash: B256::from_slice(app_hash),
+            proof,
+        })
+    }
+}
+
+/// Splits `len` bytes from the start of the input.
+fn split(input: &[u8], len: usize) -> Result<(&[u8], &[u8]), PrecompileError> {
+    if input.len() < len {
+        return Err(PrecompileError::Other(format!(
+            "invalid input: expected at least {len} bytes, actual size is {}",
+            input.len()
+        )));
+    }
+    Ok(input.split_at(len))
+}
+
+/// Splits a field prefixed with its length word from the start of the input.
+fn split_prefixed(input: &[u8]) -> Result<(&[u8], &[u8]), PrecompileError> {
+    let len = read_length(input)
+        .ok_or_else(|| PrecompileError::other("invalid input: missing length"))?;
+    let len = usize::try_from(len)
+        .map_err(|_| PrecompileError::Other(format!("integer overflow, length: {len}")))?;
+    split(&input[LENGTH_WORD..], len)
+}
+
+/// `iavlMerkleProofValidate` precompile logic.
+///
+/// The input is the payload length followed by the [`KeyValueMerkleProof`], on success the
+/// output is a 32 byte word with the value `1`.
+pub fn iavl_merkle_proof_validate(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    charge(gas_limit)?;
+    let _proof = KeyValueMerkleProof::decode(payload(input)?)?;
+    Err(unsupported("iavl merkle proof"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bsc::length_word;
+
+    fn encode(store_name: &[u8], key: &[u8], value: &[u8], proof: &[u8]) -> Vec<u8> {
+        let mut out = [0u8; STORE_NAME_LENGTH].to_vec();
+        out[..store_name.len()].copy_from_slice(store_name);
+        out.extend_from_slice(&length_word(key.len() as u64));
+        out.extend_from_slice(key);
+        out.extend_from_slice(&length_word(value.len() as u64));
+        out.extend_from_slice(value);
+        out.extend_from_slice(&[7; APP_HASH_LENGTH]);
+        out.extend_from_slice(proof);
+        out
+    }
+
+    #[test]
+    fn test_decode() {
+        let input = encode(b"acc", b"key", b"value", b"proof");
+        assert_eq!(
+            KeyValueMerkleProof::decode(&input),
+            Ok(KeyValueMerkleProof {
+                store_name: b"acc",
+                key: b"key",
+                value: b"value",
+                app_hash: B256::repeat_byte(7),
+                proof: b"proof",
+            })
+        );
+
+        // Missing proof.
+        let input = encode(b"acc", b"key", b"value", b"");
+        assert!(KeyValueMerkleProof::decode(&input).is_err());
+
+        // Key longer than the input.
+        let mut input = encode(b"acc", b"key", b"value", b"proof");
+        input[STORE_NAME_LENGTH..STORE_NAME_LENGTH + LENGTH_WORD]
+            .copy_from_slice(&length_word(u64::MAX));
+        assert!(KeyValueMerkleProof::decode(&input).is_err());
+    }
+
+    #[test]
+    fn test_iavl_merkle_proof_validate_input() {
+        let payload = encode(b"acc", b"key", b"value", b"proof");
+        let mut input = length_word(payload.len() as u64).to_vec();
+        input.extend_from_slice(&payload);
+        assert_eq!(
+            iavl_merkle_proof_validate(&input, 3_000),
+            Err(unsupported("iavl merkle proof"))
+        );
+        assert_eq!(
+            iavl_merkle_proof_validate(&input[..input.len() - 1], 3_000),
+            Err(PrecompileError::Other(
+                "invalid input: input size should be 173, actual the size is 172".into()
+            ))
+        );
+    }
+}
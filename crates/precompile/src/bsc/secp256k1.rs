@@ -0,0 +1,139 @@
+//! `secp256k1SignatureRecover` precompile at `0x69`.
+//!
+//! Verifies a secp256k1 signature of a BNB Beacon Chain account and returns its Tendermint address,
+//! used to recover assets of the BNB Beacon Chain after its shutdown. Added in the Feynman
+//! hardfork.
+use crate::{
+    crypto, secp256k1, u64_to_address, Precompile, PrecompileError, PrecompileId, PrecompileOutput,
+    PrecompileResult,
+};
+use primitives::{alloy_primitives::B512, B256};
+use std::borrow::Cow;
+
+/// Address of the `secp256k1SignatureRecover` precompile.
+pub const SECP256K1_SIGNATURE_RECOVER_ADDRESS: u64 = 0x69;
+
+/// Identifier of the `secp256k1SignatureRecover` precompile.
+pub const SECP256K1_SIGNATURE_RECOVER_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("secp256k1SignatureRecover"));
+
+/// `secp256k1SignatureRecover` precompile.
+pub const SECP256K1_SIGNATURE_RECOVER: Precompile = Precompile::new(
+    SECP256K1_SIGNATURE_RECOVER_ID,
+    u64_to_address(SECP256K1_SIGNATURE_RECOVER_ADDRESS),
+    secp256k1_signature_recover,
+);
+
+/// Gas cost of the precompile, same as `ecrecover`.
+pub const SECP256K1_SIGNATURE_RECOVER_GAS: u64 = 3_000;
+
+/// Length of a compressed public key.
+pub const PUBKEY_LENGTH: usize = 33;
+/// Length of a compact signature.
+pub const SIGNATURE_LENGTH: usize = 64;
+/// Length of the message hash.
+pub const MSG_HASH_LENGTH: usize = 32;
+
+/// `secp256k1SignatureRecover` precompile logic.
+///
+/// | public key | signature | message hash |
+/// | :--------: | :-------: | :----------: |
+/// |     33     |    64     |      32      |
+///
+/// Returns the 20 byte Tendermint address of the public key, `ripemd160(sha256(public key))`, if
+/// the signature is valid. Like in Tendermint, signatures with a high `s` value are rejected.
+pub fn secp256k1_signature_recover(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    if SECP256K1_SIGNATURE_RECOVER_GAS > gas_limit {
+        return Err(PrecompileError::OutOfGas);
+    }
+    if input.len() != PUBKEY_LENGTH + SIGNATURE_LENGTH + MSG_HASH_LENGTH {
+        return Err(PrecompileError::Other(format!(
+            "invalid input: input size should be {}, actual the size is {}",
+            PUBKEY_LENGTH + SIGNATURE_LENGTH + MSG_HASH_LENGTH,
+            input.len()
+        )));
+    }
+
+    let (pubkey, rest) = input.split_at(PUBKEY_LENGTH);
+    let (sig, msg) = rest.split_at(SIGNATURE_LENGTH);
+    let pubkey: &[u8; PUBKEY_LENGTH] = pubkey.try_into().unwrap();
+    if !secp256k1::verify(pubkey, &B512::from_slice(sig), &B256::from_slice(msg)) {
+        return Err(PrecompileError::other("invalid signature"));
+    }
+
+    let address = crypto().ripemd160(&crypto().sha256(pubkey));
+    Ok(PrecompileOutput::new(
+        SECP256K1_SIGNATURE_RECOVER_GAS,
+        address[12..].to_vec().into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+    use primitives::hex;
+
+    // RFC 6979 vector of the private key one and the message "Satoshi Nakamoto".
+    const PUBKEY: [u8; 33] =
+        hex!("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798");
+    const SIGNATURE: [u8; 64] = hex!("934b1ea10a4b3c1757e2b0c017d0b6143ce3c9a7e6a4a49860d7a6ab210ee3d82442ce9d2b916064108014783e923ec36b49743e2ffa1c4496f01a512aafd9e5");
+    const MSG_HASH: [u8; 32] =
+        hex!("a0dc65ffca799873cbea0ac274015b9526505daaaed385155425f7337704883e");
+    /// RIPEMD-160 of the SHA-256 of [`PUBKEY`].
+    const ADDRESS: [u8; 20] = hex!("751e76e8199196d454941c45d1b3a323f1433bd6");
+
+    fn input(key: &SigningKey, msg_hash: &[u8; 32]) -> Vec<u8> {
+        let (sig, _) = key.sign_prehash_recoverable(msg_hash).unwrap();
+        let pubkey = key.verifying_key().to_encoded_point(true);
+        [pubkey.as_bytes(), &sig.to_bytes()[..], &msg_hash[..]].concat()
+    }
+
+    fn key() -> SigningKey {
+        let mut key = [0; 32];
+        key[31] = 1;
+        SigningKey::from_slice(&key).unwrap()
+    }
+
+    #[test]
+    fn test_secp256k1_signature_recover() {
+        let input = [&PUBKEY[..], &SIGNATURE, &MSG_HASH].concat();
+        assert_eq!(input, self::input(&key(), &MSG_HASH));
+        let output = secp256k1_signature_recover(&input, 3_000).unwrap();
+        assert_eq!(output.gas_used, 3_000);
+        assert_eq!(output.bytes[..], ADDRESS);
+
+        assert_eq!(
+            secp256k1_signature_recover(&input, 2_999),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_signature_recover_invalid() {
+        let key = key();
+        let valid = input(&key, &MSG_HASH);
+
+        // Wrong length.
+        assert!(secp256k1_signature_recover(&valid[1..], 3_000).is_err());
+
+        // Signature of another message.
+        let mut other_msg = valid.clone();
+        other_msg[PUBKEY_LENGTH + SIGNATURE_LENGTH..].fill(0);
+        assert_eq!(
+            secp256k1_signature_recover(&other_msg, 3_000),
+            Err(PrecompileError::other("invalid signature"))
+        );
+
+        // Malleable signature with a high `s`.
+        let (sig, _) = key.sign_prehash_recoverable(&MSG_HASH).unwrap();
+        let high_s = k256::ecdsa::Signature::from_scalars(sig.r(), -*sig.s()).unwrap();
+        let mut malleable = valid.clone();
+        malleable[PUBKEY_LENGTH..PUBKEY_LENGTH + SIGNATURE_LENGTH]
+            .copy_from_slice(&high_s.to_bytes());
+        assert_eq!(
+            secp256k1_signature_recover(&malleable, 3_000),
+            Err(PrecompileError::other("invalid signature"))
+        );
+    }
+}
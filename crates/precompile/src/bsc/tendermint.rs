@@ -0,0 +1,266 @@
+//! `tmHeaderValidate` precompile at `0x64`.
+//!
+//! Validates a Tendermint header of the BNB Beacon Chain against a light client consensus state
+//! and returns the updated consensus state.
+use super::{charge, length_word, payload, read_length, unsupported, LENGTH_WORD};
+use crate::{u64_to_address, Precompile, PrecompileError, PrecompileId, PrecompileResult};
+use primitives::B256;
+use std::{borrow::Cow, string::String, vec::Vec};
+
+/// Address of the `tmHeaderValidate` precompile.
+pub const TM_HEADER_VALIDATE_ADDRESS: u64 = 0x64;
+
+/// Identifier of the `tmHeaderValidate` precompile.
+pub const TM_HEADER_VALIDATE_ID: PrecompileId =
+    PrecompileId::Custom(Cow::Borrowed("tmHeaderValidate"));
+
+/// `tmHeaderValidate` precompile, active from genesis until the Nano hardfork and again since the
+/// Moran hardfork.
+pub const TM_HEADER_VALIDATE: Precompile = Precompile::new(
+    TM_HEADER_VALIDATE_ID,
+    u64_to_address(TM_HEADER_VALIDATE_ADDRESS),
+    tm_header_validate,
+);
+
+/// `tmHeaderValidate` precompile suspended by the Nano hardfork.
+pub const TM_HEADER_VALIDATE_NANO: Precompile = Precompile::new(
+    TM_HEADER_VALIDATE_ID,
+    u64_to_address(TM_HEADER_VALIDATE_ADDRESS),
+    super::suspended,
+);
+
+/// Length of the chain id, right padded with zeros.
+pub const CHAIN_ID_LENGTH: usize = 32;
+/// Length of the height.
+pub const HEIGHT_LENGTH: usize = 8;
+/// Length of the application hash.
+pub const APP_HASH_LENGTH: usize = 32;
+/// Length of the validator set hash.
+pub const VALIDATOR_SET_HASH_LENGTH: usize = 32;
+/// Length of an ed25519 validator public key.
+pub const VALIDATOR_PUBKEY_LENGTH: usize = 32;
+/// Length of the voting power of a validator.
+pub const VALIDATOR_VOTING_POWER_LENGTH: usize = 8;
+
+/// Tendermint validator of a [`ConsensusState`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    /// Ed25519 public key.
+    pub pub_key: [u8; VALIDATOR_PUBKEY_LENGTH],
+    /// Voting power.
+    pub voting_power: i64,
+}
+
+/// Light client consensus state of the BNB Beacon Chain.
+///
+/// Encoded as:
+///
+/// | chain id | height | app hash | validator set hash | `[{pubkey, voting power}]` |
+/// | :------: | :----: | :------: | :----------------: | :------------------------: |
+/// |    32    |   8    |    32    |         32         |        `[{32, 8}]`         |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusState {
+    /// Chain id.
+    pub chain_id: String,
+    /// Height of the last validated header.
+    pub height: u64,
+    /// Application hash of the last validated header.
+    pub app_hash: B256,
+    /// Hash of the current validator set.
+    pub validator_set_hash: B256,
+    /// Next validator set.
+    pub next_validators: Vec<Validator>,
+}
+
+impl ConsensusState {
+    const MIN_LENGTH: usize =
+        CHAIN_ID_LENGTH + HEIGHT_LENGTH + APP_HASH_LENGTH + VALIDATOR_SET_HASH_LENGTH;
+    const VALIDATOR_LENGTH: usize = VALIDATOR_PUBKEY_LENGTH + VALIDATOR_VOTING_POWER_LENGTH;
+
+    /// Decodes the consensus state, at least one validator is required.
+    pub fn decode(input: &[u8]) -> Result<Self, PrecompileError> {
+        if input.len() <= Self::MIN_LENGTH
+            || !(input.len() - Self::MIN_LENGTH).is_multiple_of(Self::VALIDATOR_LENGTH)
+        {
+            return Err(PrecompileError::Other(format!(
+                "expected input size {}+{}*N, actual input size: {}",
+                Self::MIN_LENGTH,
+                Self::VALIDATOR_LENGTH,
+                input.len()
+            )));
+        }
+        let (chain_id, rest) = input.split_at(CHAIN_ID_LENGTH);
+        let (height, rest) = rest.split_at(HEIGHT_LENGTH);
+        let (app_hash, rest) = rest.split_at(APP_HASH_LENGTH);
+        let (validator_set_hash, validators) = rest.split_at(VALIDATOR_SET_HASH_LENGTH);
+
+        let next_validators = validators
+            .chunks_exact(Self::VALIDATOR_LENGTH)
+            .map(|validator| {
+                let (pub_key, voting_power) = validator.split_at(VALIDATOR_PUBKEY_LENGTH);
+                Validator {
+                    pub_key: pub_key.try_into().unwrap(),
+                    voting_power: u64::from_be_bytes(voting_power.try_into().unwrap()) as i64,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            chain_id: trim_zeros(chain_id),
+            height: u64::from_be_bytes(height.try_into().unwrap()),
+            app_hash: B256::from_slice(app_hash),
+            validator_set_hash: B256::from_slice(validator_set_hash),
+            next_validators,
+        })
+    }
+
+    /// Encodes the consensus state.
+    ///
+    /// Fails if the chain id is longer than 32 bytes.
+    pub fn encode(&self) -> Result<Vec<u8>, PrecompileError> {
+        let mut out = Vec::with_capacity(
+            Self::MIN_LENGTH + self.next_validators.len() * Self::VALIDATOR_LENGTH,
+        );
+        out.extend_from_slice(&pad_chain_id(&self.chain_id)?);
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(self.app_hash.as_slice());
+        out.extend_from_slice(self.validator_set_hash.as_slice());
+        for validator in &self.next_validators {
+            out.extend_from_slice(&validator.pub_key);
+            out.extend_from_slice(&(validator.voting_power as u64).to_be_bytes());
+        }
+        Ok(out)
+    }
+}
+
+/// Removes leading and trailing zero bytes of a padded string.
+pub(crate) fn trim_zeros(bytes: &[u8]) -> String {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    let end = bytes.iter().rposition(|b| *b != 0).map_or(start, |i| i + 1);
+    String::from_utf8_lossy(&bytes[start..end]).into_owned()
+}
+
+/// Right pads the chain id with zeros.
+pub(crate) fn pad_chain_id(chain_id: &str) -> Result<[u8; CHAIN_ID_LENGTH], PrecompileError> {
+    if chain_id.len() > CHAIN_ID_LENGTH {
+        return Err(PrecompileError::Other(format!(
+            "chainID length should be no more than {CHAIN_ID_LENGTH}"
+        )));
+    }
+    let mut out = [0u8; CHAIN_ID_LENGTH];
+    out[..chain_id.len()].copy_from_slice(chain_id.as_bytes());
+    Ok(out)
+}
+
+/// Splits the input into the consensus state and the encoded header.
+///
+/// | consensus state length | consensus state | header |
+/// | :--------------------: | :-------------: | :----: |
+/// |           32           |                 |        |
+pub(crate) fn decode_consensus_state_input(
+    input: &[u8],
+) -> Result<(ConsensusState, &[u8]), PrecompileError> {
+    let cs_length = read_length(input)
+        .ok_or_else(|| PrecompileError::other("invalid input: missing consensus state length"))?;
+    let cs_end = cs_length
+        .checked_add(LENGTH_WORD as u64)
+        .ok_or_else(|| PrecompileError::Other(format!("integer overflow, csLen: {cs_length}")))?;
+    if input.len() as u64 <= cs_end {
+        return Err(PrecompileError::Other(format!(
+            "expected payload size {cs_end}, actual size: {}",
+            input.len()
+        )));
+    }
+    let cs_end = cs_end as usize;
+    let consensus_state = ConsensusState::decode(&input[LENGTH_WORD..cs_end])?;
+    Ok((consensus_state, &input[cs_end..]))
+}
+
+/// Encodes the output of the light client precompiles.
+///
+/// | validator set changed | empty | consensus state length | consensus state |
+/// | :-------------------: | :---: | :--------------------: | :-------------: |
+/// |           1           |  23   |           8            |                 |
+pub fn encode_output(validator_set_changed: bool, consensus_state: &[u8]) -> Vec<u8> {
+    let mut out = length_word(consensus_state.len() as u64).to_vec();
+    out[0] = validator_set_changed as u8;
+    out.extend_from_slice(consensus_state);
+    out
+}
+
+/// `tmHeaderValidate` precompile logic.
+///
+/// The input is the payload length followed by the payload:
+///
+/// | payload length | consensus state length | consensus state | amino encoded header |
+/// | :------------: | :--------------------: | :-------------: | :------------------: |
+/// |       32       |           32           |                 |                      |
+pub fn tm_header_validate(input: &[u8], gas_limit: u64) -> PrecompileResult {
+    charge(gas_limit)?;
+    let (_consensus_state, _header) = decode_consensus_state_input(payload(input)?)?;
+    Err(unsupported("tendermint header"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consensus_state() -> ConsensusState {
+        ConsensusState {
+            chain_id: "Binance-Chain-Tigris".into(),
+            height: 42,
+            app_hash: B256::repeat_byte(1),
+            validator_set_hash: B256::repeat_byte(2),
+            next_validators: vec![
+                Validator {
+                    pub_key: [3; 32],
+                    voting_power: 1_000,
+                },
+                Validator {
+                    pub_key: [4; 32],
+                    voting_power: 2_000,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_consensus_state_roundtrip() {
+        let cs = consensus_state();
+        let encoded = cs.encode().unwrap();
+        assert_eq!(encoded.len(), 104 + 2 * 40);
+        assert_eq!(&encoded[..20], b"Binance-Chain-Tigris");
+        assert_eq!(ConsensusState::decode(&encoded), Ok(cs));
+
+        // No validators or a partial validator.
+        assert!(ConsensusState::decode(&encoded[..104]).is_err());
+        assert!(ConsensusState::decode(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_tm_header_validate_input() {
+        let cs = consensus_state().encode().unwrap();
+        let mut payload = length_word(cs.len() as u64).to_vec();
+        payload.extend_from_slice(&cs);
+
+        // Header is missing.
+        let mut input = length_word(payload.len() as u64).to_vec();
+        input.extend_from_slice(&payload);
+        assert!(matches!(
+            tm_header_validate(&input, 3_000),
+            Err(PrecompileError::Other(err)) if err.starts_with("expected payload size")
+        ));
+
+        payload.push(0);
+        let mut input = length_word(payload.len() as u64).to_vec();
+        input.extend_from_slice(&payload);
+        assert_eq!(
+            tm_header_validate(&input, 3_000),
+            Err(unsupported("tendermint header"))
+        );
+        assert_eq!(
+            tm_header_validate(&input, 2_999),
+            Err(PrecompileError::OutOfGas)
+        );
+    }
+}
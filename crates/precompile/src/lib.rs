@@ -13,6 +13,8 @@ pub mod bls12_381;
 pub mod bls12_381_const;
 pub mod bls12_381_utils;
 pub mod bn254;
+#[cfg(feature = "bsc")]
+pub mod bsc;
 pub mod hash;
 mod id;
 pub mod identity;
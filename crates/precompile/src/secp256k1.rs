@@ -69,10 +69,10 @@ pub(crate) fn ecrecover_bytes(sig: [u8; 64], recid: u8, msg: [u8; 32]) -> Option
 // Select the correct implementation based on the enabled features.
 cfg_if::cfg_if! {
     if #[cfg(feature = "secp256k1")] {
-        pub use bitcoin_secp256k1::ecrecover;
+        pub use bitcoin_secp256k1::{ecrecover, verify};
     } else if #[cfg(feature = "libsecp256k1")] {
-        pub use parity_libsecp256k1::ecrecover;
+        pub use parity_libsecp256k1::{ecrecover, verify};
     } else {
-        pub use k256::ecrecover;
+        pub use k256::{ecrecover, verify};
     }
 }
@@ -1,8 +1,8 @@
 //! bitcoin_secp256k1 implementation of `ecrecover`. More about it in [`crate::secp256k1`].
 use primitives::{alloy_primitives::B512, keccak256, B256};
 use secp256k1::{
-    ecdsa::{RecoverableSignature, RecoveryId},
-    Message, SECP256K1,
+    ecdsa::{RecoverableSignature, RecoveryId, Signature},
+    Message, PublicKey, SECP256K1,
 };
 
 // Silence the unused crate dependency warning.
@@ -22,3 +22,18 @@ pub fn ecrecover(sig: &B512, recid: u8, msg: &B256) -> Result<B256, secp256k1::E
     hash[..12].fill(0);
     Ok(hash)
 }
+
+/// Verify a signature of a message hash with a compressed public key.
+///
+/// Signatures with a high `s` value are rejected.
+pub fn verify(pubkey: &[u8; 33], sig: &B512, msg: &B256) -> bool {
+    let (Ok(pubkey), Ok(sig)) = (
+        PublicKey::from_slice(pubkey),
+        Signature::from_compact(sig.as_slice()),
+    ) else {
+        return false;
+    };
+    SECP256K1
+        .verify_ecdsa(Message::from_digest(msg.0), &sig, &pubkey)
+        .is_ok()
+}
@@ -1,5 +1,5 @@
 //! k256 implementation of `ecrecover`. More about it in [`crate::secp256k1`].
-use k256::ecdsa::{Error, RecoveryId, Signature, VerifyingKey};
+use k256::ecdsa::{signature::hazmat::PrehashVerifier, Error, RecoveryId, Signature, VerifyingKey};
 use primitives::{alloy_primitives::B512, keccak256, B256};
 
 /// Recover the public key from a signature and a message.
@@ -29,3 +29,16 @@ pub fn ecrecover(sig: &B512, mut recid: u8, msg: &B256) -> Result<B256, Error> {
     hash[..12].fill(0);
     Ok(hash)
 }
+
+/// Verify a signature of a message hash with a compressed public key.
+///
+/// Signatures with a high `s` value are rejected.
+pub fn verify(pubkey: &[u8; 33], sig: &B512, msg: &B256) -> bool {
+    let (Ok(pubkey), Ok(sig)) = (
+        VerifyingKey::from_sec1_bytes(pubkey),
+        Signature::from_slice(sig.as_slice()),
+    ) else {
+        return false;
+    };
+    sig.normalize_s().is_none() && pubkey.verify_prehash(&msg[..], &sig).is_ok()
+}
@@ -1,5 +1,5 @@
 //! `libsecp256k1` implementation of `ecrecover`. More about it in [`crate::secp256k1`].
-use libsecp256k1::{recover, Error, Message, RecoveryId, Signature};
+use libsecp256k1::{recover, Error, Message, PublicKey, RecoveryId, Signature};
 use primitives::{alloy_primitives::B512, keccak256, B256};
 
 /// Recover the public key from a signature and a message.
@@ -17,3 +17,16 @@ pub fn ecrecover(sig: &B512, recid: u8, msg: &B256) -> Result<B256, Error> {
     hash[..12].fill(0);
     Ok(hash)
 }
+
+/// Verify a signature of a message hash with a compressed public key.
+///
+/// Signatures with a high `s` value are rejected.
+pub fn verify(pubkey: &[u8; 33], sig: &B512, msg: &B256) -> bool {
+    let (Ok(pubkey), Ok(sig)) = (
+        PublicKey::parse_compressed(pubkey),
+        Signature::parse_standard(sig),
+    ) else {
+        return false;
+    };
+    !sig.s.is_high() && libsecp256k1::verify(&Message::parse(msg.as_ref()), &sig, &pubkey)
+}